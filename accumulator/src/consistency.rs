@@ -0,0 +1,177 @@
+use crate::{Hasher, Keccak, VerifyingError};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use primitive_types::H256;
+
+/// A merkle consistency proof. Shows that the tree with `first_count` leaves
+/// is a prefix of the tree with `second_count` leaves, i.e. that the later
+/// tree was produced only by appending leaves to the earlier one.
+///
+/// The proof consists of the leading-edge branch of the first tree (which
+/// fully determines its root), the first leaf appended after it, and the
+/// right-hand siblings along that leaf's path in the second tree. Both roots
+/// are recomputed from the same branch, so any rewritten leaf in the shared
+/// prefix causes verification to fail.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyProof<const N: usize, H: Hasher = Keccak> {
+    /// The number of leaves in the first tree
+    pub first_count: usize,
+    /// The number of leaves in the second tree
+    pub second_count: usize,
+    /// The first tree's complete left subtrees, in "bottom-up" order. One
+    /// node for each bit set in `first_count`
+    pub branch: Vec<H256>,
+    /// The leaf at index `first_count` in the second tree
+    pub leaf: H256,
+    /// The second tree's non-empty right siblings along the path of `leaf`,
+    /// in "bottom-up" order
+    pub path: Vec<H256>,
+    /// The hasher used to evaluate the proof
+    #[serde(skip)]
+    pub hasher: PhantomData<H>,
+}
+
+impl<const N: usize, H: Hasher> ConsistencyProof<N, H> {
+    /// Calculate the roots of the first and second trees produced by
+    /// evaluating the proof
+    pub fn roots(&self) -> Result<(H256, H256), VerifyingError> {
+        let first_count = self.first_count;
+        let second_count = self.second_count;
+
+        // Checked first, so `second_count - 1` can't underflow
+        if first_count >= second_count {
+            return Err(VerifyingError::MalformedProof);
+        }
+        if N < usize::BITS as usize && (second_count - 1) >> N != 0 {
+            return Err(VerifyingError::MalformedProof);
+        }
+
+        let mut branch = self.branch.iter();
+        let mut path = self.path.iter();
+
+        // `first` starts at the empty slot the second tree has filled with
+        // `leaf`. Walking up, both trees share every left sibling.
+        let mut first = H::zero_hashes()[0];
+        let mut second = self.leaf;
+        for (layer, zero) in H::zero_hashes().iter().enumerate().take(N) {
+            let index = first_count >> layer;
+            if index & 1 == 1 {
+                let left = branch.next().ok_or(VerifyingError::MalformedProof)?;
+                first = H::hash_concat(left, first);
+                second = H::hash_concat(left, second);
+            } else {
+                let right = if (index + 1) << layer < second_count {
+                    *path.next().ok_or(VerifyingError::MalformedProof)?
+                } else {
+                    *zero
+                };
+                first = H::hash_concat(first, zero);
+                second = H::hash_concat(second, right);
+            }
+        }
+
+        // All nodes must be consumed
+        if branch.next().is_some() || path.next().is_some() {
+            return Err(VerifyingError::MalformedProof);
+        }
+
+        Ok((first, second))
+    }
+
+    /// Verify that the tree with root `first_root` is a prefix of the tree
+    /// with root `second_root`
+    pub fn verify(&self, first_root: H256, second_root: H256) -> Result<(), VerifyingError> {
+        let (first, second) = self.roots()?;
+        if first != first_root {
+            return Err(VerifyingError::VerificationFailed {
+                expected: first_root,
+                actual: first,
+            });
+        }
+        if second != second_root {
+            return Err(VerifyingError::VerificationFailed {
+                expected: second_root,
+                actual: second,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Merkle, ProvingError, Tree};
+
+    fn tree(n: u64) -> Tree<8> {
+        (0..n).map(H256::from_low_u64_be).collect()
+    }
+
+    #[test]
+    fn it_proves_consistency_between_sizes() {
+        let tree = tree(40);
+
+        for second in 1..=40 {
+            let second_root = tree.root_at(second).unwrap();
+            for first in 0..second {
+                let first_root = tree.root_at(first).unwrap();
+                let proof = tree.prove_consistency(first, second).unwrap();
+                proof.verify(first_root, second_root).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn it_rejects_rewritten_history() {
+        let tree = tree(20);
+        let proof = tree.prove_consistency(7, 20).unwrap();
+
+        let mut leaves: Vec<H256> = (0..20).map(H256::from_low_u64_be).collect();
+        leaves[3] = H256::repeat_byte(3);
+        let forked: Tree<8> = leaves.as_slice().into();
+
+        // same size, different history
+        assert!(proof
+            .verify(forked.root_at(7).unwrap(), tree.root())
+            .is_err());
+        assert!(proof
+            .verify(tree.root_at(7).unwrap(), forked.root())
+            .is_err());
+
+        let mut tampered = proof.clone();
+        tampered.branch[0] = H256::repeat_byte(1);
+        assert!(tampered
+            .verify(tree.root_at(7).unwrap(), tree.root())
+            .is_err());
+
+        let mut truncated = proof.clone();
+        truncated.path.pop();
+        assert!(truncated.roots().is_err());
+
+        let mut extended = proof;
+        extended.branch.push(H256::zero());
+        assert!(extended.roots().is_err());
+    }
+
+    #[test]
+    fn it_rejects_empty_second_tree() {
+        let mut proof = tree(20).prove_consistency(5, 20).unwrap();
+        proof.first_count = 0;
+        proof.second_count = 0;
+        assert!(matches!(proof.roots(), Err(VerifyingError::MalformedProof)));
+    }
+
+    #[test]
+    fn it_rejects_bad_ranges() {
+        let tree = tree(20);
+        assert!(matches!(
+            tree.prove_consistency(5, 5),
+            Err(ProvingError::InvalidConsistencyRange { .. })
+        ));
+        assert!(matches!(
+            tree.prove_consistency(5, 21),
+            Err(ProvingError::CountTooHigh { .. })
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{hash, DecodingError, Hasher, Proof};
+use alloc::{format, string::String, vec, vec::Vec};
+use primitive_types::H256;
+
+/// True if `index` fits in a tree of depth `n`
+fn in_range(index: usize, n: usize) -> bool {
+    n >= usize::BITS as usize || index >> n == 0
+}
+
+impl<const N: usize, H: Hasher> Proof<N, H> {
+    /// The solidity signature of the replica's `prove` function for trees of
+    /// this depth
+    pub fn prove_signature() -> String {
+        format!("prove(bytes32,bytes32[{}],uint256)", N)
+    }
+
+    /// The 4-byte selector of the replica's `prove` function for trees of
+    /// this depth
+    pub fn prove_selector() -> [u8; 4] {
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&hash(Self::prove_signature()).as_bytes()[..4]);
+        selector
+    }
+
+    /// ABI encode a call to the replica's `prove` function with this proof,
+    /// including the function selector. For depth 32 trees, this is
+    /// `Replica.prove(bytes32,bytes32[32],uint256)`.
+    pub fn to_prove_calldata(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(4 + 32 * (N + 2));
+        buf.extend_from_slice(&Self::prove_selector());
+        buf.extend_from_slice(self.leaf.as_bytes());
+        for node in self.path.iter() {
+            buf.extend_from_slice(node.as_bytes());
+        }
+        buf.extend_from_slice(&[0u8; 24]);
+        buf.extend_from_slice(&(self.index as u64).to_be_bytes());
+        buf
+    }
+
+    /// Decode a proof from ABI encoded `Replica.prove` calldata, including
+    /// the function selector.
+    pub fn from_prove_calldata(data: &[u8]) -> Result<Self, DecodingError> {
+        let expected = 4 + 32 * (N + 2);
+        if data.len() != expected {
+            return Err(DecodingError::InvalidLength {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        let (selector, data) = data.split_at(4);
+        if selector != Self::prove_selector() {
+            let mut actual = [0u8; 4];
+            actual.copy_from_slice(selector);
+            return Err(DecodingError::InvalidSelector(actual));
+        }
+
+        let mut words = data.chunks_exact(32);
+        let leaf = H256::from_slice(words.next().expect("checked length"));
+        let mut path = [H256::zero(); N];
+        for node in path.iter_mut() {
+            *node = H256::from_slice(words.next().expect("checked length"));
+        }
+
+        // Tree indices are at most 32 bits
+        let index = words.next().expect("checked length");
+        if index[..28].iter().any(|b| *b != 0) {
+            return Err(DecodingError::IndexTooHigh);
+        }
+        let mut index_bytes = [0u8; 4];
+        index_bytes.copy_from_slice(&index[28..]);
+        let index = u32::from_be_bytes(index_bytes) as usize;
+        if !in_range(index, N) {
+            return Err(DecodingError::IndexTooHigh);
+        }
+
+        Ok(Self::new(leaf, index, path))
+    }
+
+    /// Encode this proof in a compact form, omitting any path nodes which
+    /// are zero hashes.
+    ///
+    /// Format: `leaf (32) || index (u32 BE) || bitmap || nodes`. Bit `i` of
+    /// the bitmap (least significant bit of byte 0 first) is set if
+    /// `path[i]` is included in `nodes`. Otherwise it is the zero hash of
+    /// layer `i`. Proofs of recent leaves in sparse trees are typically far
+    /// smaller than the full encoding.
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut bitmap = vec![0u8; (N + 7) / 8];
+        let mut nodes = vec![];
+        for (i, (node, zero)) in self.path.iter().zip(H::zero_hashes().iter()).enumerate() {
+            if node != zero {
+                bitmap[i / 8] |= 1 << (i % 8);
+                nodes.extend_from_slice(node.as_bytes());
+            }
+        }
+
+        let mut buf = Vec::with_capacity(36 + bitmap.len() + nodes.len());
+        buf.extend_from_slice(self.leaf.as_bytes());
+        buf.extend_from_slice(&(self.index as u32).to_be_bytes());
+        buf.extend(bitmap);
+        buf.extend(nodes);
+        buf
+    }
+
+    /// Decode a proof from the compact form produced by `to_compact_bytes`
+    pub fn from_compact_bytes(data: &[u8]) -> Result<Self, DecodingError> {
+        let header = 36 + (N + 7) / 8;
+        if data.len() < header {
+            return Err(DecodingError::InvalidLength {
+                expected: header,
+                actual: data.len(),
+            });
+        }
+
+        let leaf = H256::from_slice(&data[..32]);
+        let mut index_bytes = [0u8; 4];
+        index_bytes.copy_from_slice(&data[32..36]);
+        let index = u32::from_be_bytes(index_bytes) as usize;
+        if !in_range(index, N) {
+            return Err(DecodingError::IndexTooHigh);
+        }
+
+        let bitmap = &data[36..header];
+        let included = |i: usize| bitmap[i / 8] & (1 << (i % 8)) != 0;
+        // Padding bits past the tree depth must be unset
+        if (N..bitmap.len() * 8).any(included) {
+            return Err(DecodingError::NonCanonical);
+        }
+        let expected = header + 32 * (0..N).filter(|i| included(*i)).count();
+        if data.len() != expected {
+            return Err(DecodingError::InvalidLength {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        let mut nodes = data[header..].chunks_exact(32);
+        let mut path = [H256::zero(); N];
+        for (i, node) in path.iter_mut().enumerate() {
+            *node = if included(i) {
+                let node = H256::from_slice(nodes.next().expect("checked length"));
+                // Zero hashes are always omitted by `to_compact_bytes`
+                if node == H::zero_hashes()[i] {
+                    return Err(DecodingError::NonCanonical);
+                }
+                node
+            } else {
+                H::zero_hashes()[i]
+            };
+        }
+
+        Ok(Self::new(leaf, index, path))
+    }
+}
+
+/// SCALE encoding, for the Substrate pallets. Fields are encoded in order,
+/// with the index as a `u32`.
+impl<const N: usize, H: Hasher> codec::Encode for Proof<N, H> {
+    fn size_hint(&self) -> usize {
+        32 + 4 + 32 * N
+    }
+
+    fn encode_to<T: codec::Output + ?Sized>(&self, dest: &mut T) {
+        dest.write(self.leaf.as_bytes());
+        (self.index as u32).encode_to(dest);
+        for node in self.path.iter() {
+            dest.write(node.as_bytes());
+        }
+    }
+}
+
+impl<const N: usize, H: Hasher> codec::Decode for Proof<N, H> {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let mut leaf = H256::zero();
+        input.read(leaf.as_bytes_mut())?;
+        let index = u32::decode(input)? as usize;
+        if !in_range(index, N) {
+            return Err("Index does not fit in a tree of this depth".into());
+        }
+        let mut path = [H256::zero(); N];
+        for node in path.iter_mut() {
+            input.read(node.as_bytes_mut())?;
+        }
+        Ok(Self::new(leaf, index, path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Keccak, Merkle, MerkleProof, NomadProof, NomadTree, Tree};
+    use codec::{Decode, Encode};
+
+    fn tree(n: u64) -> NomadTree {
+        (0..n).map(H256::from_low_u64_be).collect()
+    }
+
+    #[test]
+    fn it_encodes_prove_calldata() {
+        assert_eq!(
+            NomadProof::prove_signature(),
+            "prove(bytes32,bytes32[32],uint256)"
+        );
+        assert_eq!(NomadProof::prove_selector(), [0x37, 0x1d, 0x30, 0x71]);
+
+        let proof = tree(7).prove(5).unwrap();
+        let calldata = proof.to_prove_calldata();
+        assert_eq!(calldata.len(), 4 + 34 * 32);
+        assert_eq!(&calldata[..4], &NomadProof::prove_selector());
+        assert_eq!(&calldata[4..36], proof.leaf.as_bytes());
+        assert_eq!(&calldata[36..68], proof.path[0].as_bytes());
+        assert_eq!(calldata[calldata.len() - 1], 5);
+
+        assert_eq!(NomadProof::from_prove_calldata(&calldata).unwrap(), proof);
+
+        let mut wrong_selector = calldata.clone();
+        wrong_selector[0] ^= 1;
+        assert!(matches!(
+            NomadProof::from_prove_calldata(&wrong_selector),
+            Err(DecodingError::InvalidSelector(_))
+        ));
+        assert!(matches!(
+            NomadProof::from_prove_calldata(&calldata[1..]),
+            Err(DecodingError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn it_roundtrips_scale() {
+        let proof = tree(7).prove(5).unwrap();
+        let encoded = proof.encode();
+        assert_eq!(encoded.len(), 32 + 4 + 32 * 32);
+        assert_eq!(&encoded[32..36], &[5, 0, 0, 0]);
+        assert_eq!(NomadProof::decode(&mut encoded.as_slice()).unwrap(), proof);
+        assert!(NomadProof::decode(&mut &encoded[1..]).is_err());
+    }
+
+    #[test]
+    fn it_omits_zero_hashes_in_compact_encoding() {
+        let tree = tree(7);
+        for index in 0..7 {
+            let proof = tree.prove(index).unwrap();
+            let compact = proof.to_compact_bytes();
+            // 7 leaves only fill the bottom 3 layers
+            assert!(compact.len() <= 36 + 4 + 3 * 32);
+
+            let decoded = NomadProof::from_compact_bytes(&compact).unwrap();
+            assert_eq!(decoded, proof);
+            assert_eq!(decoded.root(), tree.root());
+
+            assert!(NomadProof::from_compact_bytes(&compact[..compact.len() - 1]).is_err());
+        }
+
+        // Odd depths are padded to a whole byte of bitmap
+        let small: Tree<5> = (0..32).map(H256::from_low_u64_be).collect();
+        let proof = small.prove(31).unwrap();
+        let compact = proof.to_compact_bytes();
+        assert_eq!(compact.len(), 36 + 1 + 5 * 32);
+        assert_eq!(Proof::from_compact_bytes(&compact).unwrap(), proof);
+    }
+
+    #[test]
+    fn it_rejects_non_canonical_compact_encodings() {
+        let proof = tree(7).prove(5).unwrap();
+        let compact = proof.to_compact_bytes();
+        assert_eq!(compact[36] & 1 << 3, 0);
+
+        // Explicitly include the zero hash of layer 3
+        let mut included_zero = compact.clone();
+        included_zero[36] |= 1 << 3;
+        let nodes = 36 + 4 + 32 * 3;
+        included_zero.splice(
+            nodes..nodes,
+            Keccak::zero_hashes()[3].as_bytes().iter().copied(),
+        );
+        assert_eq!(
+            NomadProof::from_compact_bytes(&included_zero),
+            Err(DecodingError::NonCanonical)
+        );
+
+        // Set a padding bit past the tree depth
+        let small: Tree<5> = (0..32).map(H256::from_low_u64_be).collect();
+        let mut padded = small.prove(31).unwrap().to_compact_bytes();
+        padded[36] |= 1 << 7;
+        assert_eq!(
+            Proof::<5>::from_compact_bytes(&padded),
+            Err(DecodingError::NonCanonical)
+        );
+    }
+}
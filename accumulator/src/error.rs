@@ -1,27 +1,82 @@
-use ethers::prelude::H256;
-use thiserror;
+use primitive_types::H256;
 
 /// Tree Errors
-#[derive(Debug, thiserror::Error, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum ProvingError {
     /// Index is above tree max size
-    #[error("Requested proof for index above u32::MAX: {0}")]
+    #[cfg_attr(
+        feature = "std",
+        error("Requested proof for index above u32::MAX: {0}")
+    )]
     IndexTooHigh(usize),
     /// Requested proof for a zero element
-    #[error("Requested proof for a zero element. Requested: {index}. Tree has: {count}")]
+    #[cfg_attr(
+        feature = "std",
+        error("Requested proof for a zero element. Requested: {index}. Tree has: {count}")
+    )]
     ZeroProof {
         /// The index requested
         index: usize,
         /// The number of leaves
         count: usize,
     },
+    /// Requested a historical proof for a tree size above the current count
+    #[cfg_attr(
+        feature = "std",
+        error("Requested proof against a tree of {requested} leaves. Tree has: {count}")
+    )]
+    CountTooHigh {
+        /// The tree size requested
+        requested: usize,
+        /// The number of leaves
+        count: usize,
+    },
+    /// Requested a consistency proof between invalid tree sizes
+    #[cfg_attr(feature = "std", error("Requested consistency proof from {first} leaves to {second} leaves. First must be less than second"))]
+    InvalidConsistencyRange {
+        /// The earlier tree size
+        first: usize,
+        /// The later tree size
+        second: usize,
+    },
+    /// Requested a multiproof for an empty set of leaves
+    #[cfg_attr(
+        feature = "std",
+        error("Requested a multiproof for an empty set of leaves")
+    )]
+    NoLeaves,
+    /// Attempted to update a proof that does not verify against the tree
+    #[cfg_attr(
+        feature = "std",
+        error("Proof does not verify against the tree. Tree root is {expected}, proof root is {actual}")
+    )]
+    RootMismatch {
+        /// The tree's root
+        expected: H256,
+        /// The root produced by the proof
+        actual: H256,
+    },
+    /// Error ingesting leaves while updating a proof
+    #[cfg_attr(feature = "std", error("{0}"))]
+    IngestionError(IngestionError),
+}
+
+impl From<IngestionError> for ProvingError {
+    fn from(e: IngestionError) -> Self {
+        Self::IngestionError(e)
+    }
 }
 
 /// Tree Errors
-#[derive(Debug, thiserror::Error, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum VerifyingError {
     /// Failed proof verification
-    #[error("Proof verification failed. Root is {expected}, produced is {actual}")]
+    #[cfg_attr(
+        feature = "std",
+        error("Proof verification failed. Root is {expected}, produced is {actual}")
+    )]
     #[allow(dead_code)]
     VerificationFailed {
         /// The expected root (this tree's current root)
@@ -29,21 +84,51 @@ pub enum VerifyingError {
         /// The root produced by branch evaluation
         actual: H256,
     },
+    /// Proof is structurally invalid and cannot be evaluated
+    #[cfg_attr(feature = "std", error("Proof is malformed"))]
+    MalformedProof,
+}
+
+/// Proof decoding errors
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum DecodingError {
+    /// Input has the wrong length
+    #[cfg_attr(
+        feature = "std",
+        error("Expected {expected} bytes. Got {actual} bytes")
+    )]
+    InvalidLength {
+        /// The expected length
+        expected: usize,
+        /// The length of the input
+        actual: usize,
+    },
+    /// Calldata is for a different function
+    #[cfg_attr(feature = "std", error("Unexpected function selector: {0:?}"))]
+    InvalidSelector([u8; 4]),
+    /// Index does not fit in a tree of this depth
+    #[cfg_attr(feature = "std", error("Index does not fit in a tree of this depth"))]
+    IndexTooHigh,
+    /// Input decodes, but is not the encoding this crate would produce
+    #[cfg_attr(feature = "std", error("Encoding is not canonical"))]
+    NonCanonical,
 }
 
 /// Error type for merkle tree ops.
-#[derive(Debug, PartialEq, Clone, Copy, thiserror::Error)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum IngestionError {
     /// Trying to push in a leaf
-    #[error("Trying to push in a leaf")]
+    #[cfg_attr(feature = "std", error("Trying to push in a leaf"))]
     LeafReached,
     /// No more space in the MerkleTree
-    #[error("No more space in the MerkleTree")]
+    #[cfg_attr(feature = "std", error("No more space in the MerkleTree"))]
     MerkleTreeFull,
     /// MerkleTree is invalid
-    #[error("MerkleTree is invalid")]
+    #[cfg_attr(feature = "std", error("MerkleTree is invalid"))]
     Invalid,
     /// Incorrect Depth provided
-    #[error("Incorrect Depth provided")]
+    #[cfg_attr(feature = "std", error("Incorrect Depth provided"))]
     DepthTooSmall,
 }
@@ -1,17 +1,23 @@
-use ethers::core::types::H256;
 use once_cell::sync::Lazy;
+use primitive_types::H256;
 
-use crate::{error::IngestionError, hash_concat, EMPTY_SLICE, TREE_DEPTH, ZERO_HASHES};
+use crate::{error::IngestionError, Hasher, EMPTY_SLICE, TREE_DEPTH};
+
+pub use crate::utils::merkle_root_from_branch;
 
 // Some code has been derived from
 // https://github.com/sigp/lighthouse/blob/c6baa0eed131c5e8ecc5860778ffc7d4a4c18d2d/consensus/merkle_proof/src/lib.rs#L25
 // It has been modified as follows:
 //    - improve legibility
 //    - remove eth2-specific features.
-//    - use keccak256
+//    - use a pluggable hasher, keccak256 by default
 //    - remove ring dependency
 // In accordance with its license terms, the apache2 license is reproduced below
 
+/// Minimum number of leaves for which subtrees are hashed in parallel
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 1 << 12;
+
 /// Zero nodes to act as "synthetic" left and right subtrees of other zero nodes.
 pub static ZERO_NODES: Lazy<Vec<MerkleTree>> =
     Lazy::new(|| (0..=TREE_DEPTH).map(MerkleTree::Zero).collect());
@@ -20,6 +26,10 @@ pub static ZERO_NODES: Lazy<Vec<MerkleTree>> =
 ///
 /// Efficiently represents a Merkle tree of fixed depth where only the first N
 /// indices are populated by non-zero leaves (perfect for the deposit contract tree).
+///
+/// The tree's structure does not depend on its hash function. Methods which
+/// compute hashes take the `Hasher` as a type parameter, and callers must use
+/// the same hasher for the life of the tree.
 #[derive(Debug, PartialEq)]
 pub enum MerkleTree {
     /// Leaf node with the hash of its content.
@@ -34,16 +44,16 @@ pub enum MerkleTree {
 
 impl MerkleTree {
     /// Retrieve the root hash of this Merkle tree.
-    pub fn hash(&self) -> H256 {
+    pub fn hash<H: Hasher>(&self) -> H256 {
         match *self {
             MerkleTree::Leaf(h) => h,
             MerkleTree::Node(h, _, _) => h,
-            MerkleTree::Zero(depth) => ZERO_HASHES[depth],
+            MerkleTree::Zero(depth) => H::zero_hashes()[depth],
         }
     }
 
     /// Create a new Merkle tree from a list of leaves and a fixed depth.
-    pub fn create(leaves: &[H256], depth: usize) -> Self {
+    pub fn create<H: Hasher>(leaves: &[H256], depth: usize) -> Self {
         use MerkleTree::*;
 
         if leaves.is_empty() {
@@ -64,18 +74,43 @@ impl MerkleTree {
                     leaves.split_at(subtree_capacity)
                 };
 
-                let left_subtree = MerkleTree::create(left_leaves, depth - 1);
-                let right_subtree = MerkleTree::create(right_leaves, depth - 1);
-                let hash = hash_concat(left_subtree.hash(), right_subtree.hash());
+                let (left_subtree, right_subtree) =
+                    MerkleTree::create_subtrees::<H>(left_leaves, right_leaves, depth - 1);
+                let hash = H::hash_concat(left_subtree.hash::<H>(), right_subtree.hash::<H>());
 
                 Node(hash, Box::new(left_subtree), Box::new(right_subtree))
             }
         }
     }
 
+    /// Create the left and right subtrees of a node. With the `rayon`
+    /// feature, large subtrees are created in parallel.
+    #[cfg(feature = "rayon")]
+    fn create_subtrees<H: Hasher>(left: &[H256], right: &[H256], depth: usize) -> (Self, Self) {
+        if left.len() + right.len() < PARALLEL_THRESHOLD {
+            return (
+                Self::create::<H>(left, depth),
+                Self::create::<H>(right, depth),
+            );
+        }
+        rayon::join(
+            || Self::create::<H>(left, depth),
+            || Self::create::<H>(right, depth),
+        )
+    }
+
+    /// Create the left and right subtrees of a node
+    #[cfg(not(feature = "rayon"))]
+    fn create_subtrees<H: Hasher>(left: &[H256], right: &[H256], depth: usize) -> (Self, Self) {
+        (
+            Self::create::<H>(left, depth),
+            Self::create::<H>(right, depth),
+        )
+    }
+
     /// Push an element in the MerkleTree.
     /// MerkleTree and depth must be correct, as the algorithm expects valid data.
-    pub fn push_leaf(&mut self, elem: H256, depth: usize) -> Result<(), IngestionError> {
+    pub fn push_leaf<H: Hasher>(&mut self, elem: H256, depth: usize) -> Result<(), IngestionError> {
         use MerkleTree::*;
 
         if depth == 0 {
@@ -85,7 +120,7 @@ impl MerkleTree {
         match self {
             Leaf(_) => return Err(IngestionError::LeafReached),
             Zero(_) => {
-                *self = MerkleTree::create(&[elem], depth);
+                *self = MerkleTree::create::<H>(&[elem], depth);
             }
             Node(ref mut hash, ref mut left, ref mut right) => {
                 let left: &mut MerkleTree = &mut *left;
@@ -95,25 +130,25 @@ impl MerkleTree {
                     (Leaf(_), Leaf(_)) => return Err(IngestionError::MerkleTreeFull),
                     // There is a right node so insert in right node
                     (Node(_, _, _), Node(_, _, _)) => {
-                        if let Err(e) = right.push_leaf(elem, depth - 1) {
+                        if let Err(e) = right.push_leaf::<H>(elem, depth - 1) {
                             return Err(e);
                         }
                     }
                     // Both branches are zero, insert in left one
                     (Zero(_), Zero(_)) => {
-                        *left = MerkleTree::create(&[elem], depth - 1);
+                        *left = MerkleTree::create::<H>(&[elem], depth - 1);
                     }
                     // Leaf on left branch and zero on right branch, insert on right side
                     (Leaf(_), Zero(_)) => {
-                        *right = MerkleTree::create(&[elem], depth - 1);
+                        *right = MerkleTree::create::<H>(&[elem], depth - 1);
                     }
                     // Try inserting on the left node -> if it fails because it is full, insert in right side.
                     (Node(_, _, _), Zero(_)) => {
-                        match left.push_leaf(elem, depth - 1) {
+                        match left.push_leaf::<H>(elem, depth - 1) {
                             Ok(_) => (),
                             // Left node is full, insert in right node
                             Err(IngestionError::MerkleTreeFull) => {
-                                *right = MerkleTree::create(&[elem], depth - 1);
+                                *right = MerkleTree::create::<H>(&[elem], depth - 1);
                             }
                             Err(e) => return Err(e),
                         };
@@ -121,13 +156,39 @@ impl MerkleTree {
                     // All other possibilities are invalid MerkleTrees
                     (_, _) => return Err(IngestionError::Invalid),
                 };
-                hash.assign_from_slice(hash_concat(left.hash(), right.hash()).as_ref());
+                hash.assign_from_slice(
+                    H::hash_concat(left.hash::<H>(), right.hash::<H>()).as_ref(),
+                );
             }
         }
 
         Ok(())
     }
 
+    /// Discard all but the first `count` leaves of the MerkleTree,
+    /// recomputing the hashes along the path of the new last leaf.
+    /// MerkleTree and depth must be correct, and `count` must not exceed the
+    /// number of leaves in the tree.
+    pub fn truncate<H: Hasher>(&mut self, count: usize, depth: usize) {
+        use MerkleTree::*;
+
+        if count == 0 {
+            *self = Zero(depth);
+            return;
+        }
+
+        if let Node(ref mut hash, ref mut left, ref mut right) = self {
+            let subtree_capacity = 2usize.pow(depth as u32 - 1);
+            if count <= subtree_capacity {
+                left.truncate::<H>(count, depth - 1);
+                **right = Zero(depth - 1);
+            } else {
+                right.truncate::<H>(count - subtree_capacity, depth - 1);
+            }
+            hash.assign_from_slice(H::hash_concat(left.hash::<H>(), right.hash::<H>()).as_ref());
+        }
+    }
+
     /// Get a reference to the left and right subtrees if they exist.
     pub fn left_and_right_branches(&self) -> Option<(&Self, &Self)> {
         match *self {
@@ -146,7 +207,7 @@ impl MerkleTree {
     ///
     /// The Merkle proof is in "bottom-up" order, starting with a leaf node
     /// and moving up the tree. Its length will be exactly equal to `depth`.
-    pub fn generate_proof(&self, index: usize, depth: usize) -> (H256, Vec<H256>) {
+    pub fn generate_proof<H: Hasher>(&self, index: usize, depth: usize) -> (H256, Vec<H256>) {
         let mut proof = vec![];
         let mut current_node = self;
         let mut current_depth = depth;
@@ -157,10 +218,10 @@ impl MerkleTree {
 
             // Go right, include the left branch in the proof.
             if ith_bit == 1 {
-                proof.push(left.hash());
+                proof.push(left.hash::<H>());
                 current_node = right;
             } else {
-                proof.push(right.hash());
+                proof.push(right.hash::<H>());
                 current_node = left;
             }
             current_depth -= 1;
@@ -172,31 +233,81 @@ impl MerkleTree {
         // Put proof in bottom-up order.
         proof.reverse();
 
-        (current_node.hash(), proof)
+        (current_node.hash::<H>(), proof)
     }
-}
-
-/// Compute a root hash from a leaf and a Merkle proof.
-pub fn merkle_root_from_branch(leaf: H256, branch: &[H256], depth: usize, index: usize) -> H256 {
-    assert_eq!(branch.len(), depth, "proof length should equal depth");
 
-    let mut current = leaf;
+    /// Return the hash of the node at `index` within `layer`, where layer 0
+    /// holds the leaves and layer `depth` holds the root.
+    pub fn node_hash<H: Hasher>(&self, index: usize, layer: usize, depth: usize) -> H256 {
+        let mut current_node = self;
+        let mut current_depth = depth;
+        while current_depth > layer {
+            let ith_bit = (index >> (current_depth - 1 - layer)) & 0x01;
+            // Note: unwrap is safe because leaves are only ever constructed at depth == 0.
+            let (left, right) = current_node.left_and_right_branches().unwrap();
+            current_node = if ith_bit == 1 { right } else { left };
+            current_depth -= 1;
+        }
+        current_node.hash::<H>()
+    }
 
-    for (i, next) in branch.iter().enumerate().take(depth) {
-        let ith_bit = (index >> i) & 0x01;
-        if ith_bit == 1 {
-            current = hash_concat(next, current);
+    /// Return the hash of the node at `index` within `layer`, as it was when
+    /// the tree contained only its first `count` leaves.
+    pub fn node_hash_at<H: Hasher>(
+        &self,
+        index: usize,
+        layer: usize,
+        depth: usize,
+        count: usize,
+    ) -> H256 {
+        let first = index << layer;
+        let last = ((index + 1) << layer) - 1;
+
+        if first >= count {
+            // Subtree was entirely empty
+            H::zero_hashes()[layer]
+        } else if last < count {
+            // Subtree was complete, and has not changed since
+            self.node_hash::<H>(index, layer, depth)
         } else {
-            current = hash_concat(current, next);
+            H::hash_concat(
+                self.node_hash_at::<H>(index * 2, layer - 1, depth, count),
+                self.node_hash_at::<H>(index * 2 + 1, layer - 1, depth, count),
+            )
         }
     }
+}
 
-    current
+/// Hash every non-empty node of a tree of `depth` containing `leaves`, layer
+/// by layer. Returns `depth + 1` layers, from the leaves up to the root. Layer
+/// `i` contains the nodes covering the leaves, each of which is hashed with
+/// the zero hash of layer `i - 1` if it has no right child. If `leaves` is
+/// empty, every layer is empty.
+///
+/// With the `rayon` feature, each layer is hashed in parallel.
+pub fn build_layers<H: Hasher>(leaves: &[H256], depth: usize) -> Vec<Vec<H256>> {
+    let mut layers = Vec::with_capacity(depth + 1);
+    layers.push(leaves.to_vec());
+    for layer in 0..depth {
+        let zero = H::zero_hashes()[layer];
+        let hash_pair = |pair: &[H256]| H::hash_concat(pair[0], pair.get(1).unwrap_or(&zero));
+
+        #[cfg(feature = "rayon")]
+        let next = {
+            use rayon::prelude::*;
+            layers[layer].par_chunks(2).map(hash_pair).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let next = layers[layer].chunks(2).map(hash_pair).collect();
+
+        layers.push(next);
+    }
+    layers
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{light, Merkle};
+    use crate::{hash_concat, light, Keccak, Merkle, ZERO_HASHES};
 
     use super::*;
 
@@ -212,7 +323,7 @@ mod tests {
         root: H256,
     ) -> bool {
         if branch.len() == depth {
-            merkle_root_from_branch(leaf, branch, depth, index) == root
+            merkle_root_from_branch::<Keccak>(leaf, branch, depth, index) == root
         } else {
             false
         }
@@ -222,9 +333,9 @@ mod tests {
     fn sparse_zero_correct() {
         let depth = 2;
         let zero = H256::from([0x00; 32]);
-        let dense_tree = MerkleTree::create(&[zero, zero, zero, zero], depth);
-        let sparse_tree = MerkleTree::create(&[], depth);
-        assert_eq!(dense_tree.hash(), sparse_tree.hash());
+        let dense_tree = MerkleTree::create::<Keccak>(&[zero, zero, zero, zero], depth);
+        let sparse_tree = MerkleTree::create::<Keccak>(&[], depth);
+        assert_eq!(dense_tree.hash::<Keccak>(), sparse_tree.hash::<Keccak>());
     }
 
     #[test]
@@ -241,8 +352,8 @@ mod tests {
 
         let root = hash_concat(node_b0x, node_b1x);
 
-        let tree = MerkleTree::create(&[leaf_b00, leaf_b01, leaf_b10, leaf_b11], 2);
-        assert_eq!(tree.hash(), root);
+        let tree = MerkleTree::create::<Keccak>(&[leaf_b00, leaf_b01, leaf_b10, leaf_b11], 2);
+        assert_eq!(tree.hash::<Keccak>(), root);
     }
 
     #[test]
@@ -337,60 +448,88 @@ mod tests {
     #[test]
     fn push_complete_example() {
         let depth = 2;
-        let mut tree = MerkleTree::create(&[], depth);
+        let mut tree = MerkleTree::create::<Keccak>(&[], depth);
 
         let leaf_b00 = H256::from([0xAA; 32]);
 
-        let res = tree.push_leaf(leaf_b00, 0);
+        let res = tree.push_leaf::<Keccak>(leaf_b00, 0);
         assert_eq!(res, Err(IngestionError::DepthTooSmall));
-        let expected_tree = MerkleTree::create(&[], depth);
-        assert_eq!(tree.hash(), expected_tree.hash());
+        let expected_tree = MerkleTree::create::<Keccak>(&[], depth);
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
 
-        tree.push_leaf(leaf_b00, depth)
+        tree.push_leaf::<Keccak>(leaf_b00, depth)
             .expect("Pushing in empty tree failed");
-        let expected_tree = MerkleTree::create(&[leaf_b00], depth);
-        assert_eq!(tree.hash(), expected_tree.hash());
+        let expected_tree = MerkleTree::create::<Keccak>(&[leaf_b00], depth);
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
 
         let leaf_b01 = H256::from([0xBB; 32]);
-        tree.push_leaf(leaf_b01, depth)
+        tree.push_leaf::<Keccak>(leaf_b01, depth)
             .expect("Pushing in left then right node failed");
-        let expected_tree = MerkleTree::create(&[leaf_b00, leaf_b01], depth);
-        assert_eq!(tree.hash(), expected_tree.hash());
+        let expected_tree = MerkleTree::create::<Keccak>(&[leaf_b00, leaf_b01], depth);
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
 
         let leaf_b10 = H256::from([0xCC; 32]);
-        tree.push_leaf(leaf_b10, depth)
+        tree.push_leaf::<Keccak>(leaf_b10, depth)
             .expect("Pushing in right then left node failed");
-        let expected_tree = MerkleTree::create(&[leaf_b00, leaf_b01, leaf_b10], depth);
-        assert_eq!(tree.hash(), expected_tree.hash());
+        let expected_tree = MerkleTree::create::<Keccak>(&[leaf_b00, leaf_b01, leaf_b10], depth);
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
 
         let leaf_b11 = H256::from([0xDD; 32]);
-        tree.push_leaf(leaf_b11, depth)
+        tree.push_leaf::<Keccak>(leaf_b11, depth)
             .expect("Pushing in outtermost leaf failed");
-        let expected_tree = MerkleTree::create(&[leaf_b00, leaf_b01, leaf_b10, leaf_b11], depth);
-        assert_eq!(tree.hash(), expected_tree.hash());
+        let expected_tree =
+            MerkleTree::create::<Keccak>(&[leaf_b00, leaf_b01, leaf_b10, leaf_b11], depth);
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
 
         let leaf_b12 = H256::from([0xEE; 32]);
-        let res = tree.push_leaf(leaf_b12, depth);
+        let res = tree.push_leaf::<Keccak>(leaf_b12, depth);
         assert_eq!(res, Err(IngestionError::MerkleTreeFull));
-        assert_eq!(tree.hash(), expected_tree.hash());
+        assert_eq!(tree.hash::<Keccak>(), expected_tree.hash::<Keccak>());
     }
 
     #[test]
     fn big_test() {
         let leaves: Vec<_> = (0..64).map(H256::from_low_u64_be).collect();
 
-        let mut tree = MerkleTree::create(&[], 32);
+        let mut tree = MerkleTree::create::<Keccak>(&[], 32);
         leaves.iter().for_each(|leaf| {
-            tree.push_leaf(*leaf, 32).unwrap();
+            tree.push_leaf::<Keccak>(*leaf, 32).unwrap();
         });
 
         leaves.iter().enumerate().for_each(|(i, leaf)| {
-            let (l, proof) = tree.generate_proof(i, 32);
+            let (l, proof) = tree.generate_proof::<Keccak>(i, 32);
             assert_eq!(l, *leaf);
-            assert!(verify_merkle_proof(*leaf, &proof, 32, i, tree.hash()));
+            assert!(verify_merkle_proof(
+                *leaf,
+                &proof,
+                32,
+                i,
+                tree.hash::<Keccak>()
+            ));
         });
     }
 
+    #[test]
+    fn it_builds_layers() {
+        // large enough to hash in parallel with the `rayon` feature
+        let leaves: Vec<_> = (0..5000).map(H256::from_low_u64_be).collect();
+
+        for count in [1, 2, 7, 64, 5000] {
+            let tree = MerkleTree::create::<Keccak>(&leaves[..count], TREE_DEPTH);
+            let layers = build_layers::<Keccak>(&leaves[..count], TREE_DEPTH);
+            assert_eq!(layers.len(), TREE_DEPTH + 1);
+            assert_eq!(layers[0], &leaves[..count]);
+            assert_eq!(layers[TREE_DEPTH], vec![tree.hash::<Keccak>()]);
+
+            for (i, layer) in layers.iter().enumerate() {
+                assert_eq!(layer.len(), ((count - 1) >> i) + 1);
+            }
+        }
+
+        let layers = build_layers::<Keccak>(&[], TREE_DEPTH);
+        assert!(layers.iter().all(Vec::is_empty));
+    }
+
     #[test]
     fn it_correctly_calculate_zeroes() {
         ZERO_HASHES
@@ -398,7 +537,7 @@ mod tests {
             .zip(ZERO_NODES.iter())
             .take(TREE_DEPTH)
             .for_each(|(left, right)| {
-                assert_eq!(*left, right.hash());
+                assert_eq!(*left, right.hash::<Keccak>());
             });
     }
 
@@ -406,14 +545,14 @@ mod tests {
     fn it_is_compatible_with_incremental_merkle() {
         let leaf = H256::repeat_byte(1);
 
-        let mut full = MerkleTree::create(&[], TREE_DEPTH);
+        let mut full = MerkleTree::create::<Keccak>(&[], TREE_DEPTH);
         let mut incr = light::LightMerkle::<32>::default();
-        let second = MerkleTree::create(&[leaf], TREE_DEPTH);
+        let second = MerkleTree::create::<Keccak>(&[leaf], TREE_DEPTH);
 
-        full.push_leaf(leaf, TREE_DEPTH).unwrap();
+        full.push_leaf::<Keccak>(leaf, TREE_DEPTH).unwrap();
         incr.ingest(leaf).unwrap();
-        assert_eq!(second.hash(), incr.root());
-        assert_eq!(full.hash(), incr.root());
+        assert_eq!(second.hash::<Keccak>(), incr.root());
+        assert_eq!(full.hash::<Keccak>(), incr.root());
     }
 }
 
@@ -0,0 +1,172 @@
+//! A `const fn` keccak256 of two concatenated 32-byte words, so that the zero
+//! hash table can be computed at compile time.
+
+use primitive_types::H256;
+
+use crate::TREE_DEPTH;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+const ROTATIONS: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+const LANES: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The keccak-f[1600] permutation
+const fn keccak_f(mut state: [u64; 25]) -> [u64; 25] {
+    let mut round = 0;
+    while round < 24 {
+        // theta
+        let mut columns = [0u64; 5];
+        let mut x = 0;
+        while x < 5 {
+            columns[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+            x += 1;
+        }
+        x = 0;
+        while x < 5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            let mut y = 0;
+            while y < 25 {
+                state[y + x] ^= d;
+                y += 5;
+            }
+            x += 1;
+        }
+
+        // rho and pi
+        let mut last = state[1];
+        let mut i = 0;
+        while i < 24 {
+            let next = state[LANES[i]];
+            state[LANES[i]] = last.rotate_left(ROTATIONS[i]);
+            last = next;
+            i += 1;
+        }
+
+        // chi
+        let mut y = 0;
+        while y < 25 {
+            let row = [
+                state[y],
+                state[y + 1],
+                state[y + 2],
+                state[y + 3],
+                state[y + 4],
+            ];
+            let mut x = 0;
+            while x < 5 {
+                state[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+                x += 1;
+            }
+            y += 5;
+        }
+
+        // iota
+        state[0] ^= ROUND_CONSTANTS[round];
+        round += 1;
+    }
+    state
+}
+
+/// Return the keccak256 digest of the concatenation of the arguments
+pub(crate) const fn hash_concat(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    // 64 bytes fit in a single 136 byte block
+    let mut state = [0u64; 25];
+    let mut i = 0;
+    while i < 4 {
+        let mut lane = [0u8; 8];
+        let mut j = 0;
+        while j < 8 {
+            lane[j] = left[i * 8 + j];
+            j += 1;
+        }
+        state[i] = u64::from_le_bytes(lane);
+        j = 0;
+        while j < 8 {
+            lane[j] = right[i * 8 + j];
+            j += 1;
+        }
+        state[i + 4] = u64::from_le_bytes(lane);
+        i += 1;
+    }
+
+    // keccak padding: 0x01 after the message, 0x80 at the end of the block
+    state[8] ^= 0x01;
+    state[16] ^= 0x80 << 56;
+
+    let state = keccak_f(state);
+
+    let mut digest = [0u8; 32];
+    i = 0;
+    while i < 4 {
+        let lane = state[i].to_le_bytes();
+        let mut j = 0;
+        while j < 8 {
+            digest[i * 8 + j] = lane[j];
+            j += 1;
+        }
+        i += 1;
+    }
+    digest
+}
+
+/// Calculate the keccak256 zero hashes for each layer of the tree
+pub(crate) const fn zero_hashes() -> [H256; TREE_DEPTH + 1] {
+    let mut hashes = [H256([0u8; 32]); TREE_DEPTH + 1];
+    let mut i = 0;
+    while i < TREE_DEPTH {
+        hashes[i + 1] = H256(hash_concat(&hashes[i].0, &hashes[i].0));
+        i += 1;
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_runtime_hasher() {
+        let left = H256::repeat_byte(1);
+        let right = H256::from_low_u64_be(0x1234_5678);
+        assert_eq!(
+            H256(hash_concat(&left.0, &right.0)),
+            crate::hash_concat(left, right)
+        );
+
+        let mut expected = H256::zero();
+        for zero in zero_hashes().iter() {
+            assert_eq!(*zero, expected);
+            expected = crate::hash_concat(expected, expected);
+        }
+    }
+}
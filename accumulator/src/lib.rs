@@ -1,14 +1,21 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![warn(missing_debug_implementations)]
 #![warn(missing_copy_implementations)]
 
+extern crate alloc;
+
 /// A full incremental merkle. Suitable for running off-chain.
+#[cfg(feature = "std")]
 pub mod full;
 
 /// Hashing utils
 pub mod utils;
 
+/// Compile-time keccak256
+mod keccak;
+
 /// Common error types for the merkle trees.
 pub mod error;
 
@@ -18,59 +25,80 @@ pub mod light;
 /// Merkle Proof struct
 pub mod proof;
 
+/// Proof encodings for use outside of Nomad agents
+pub mod encoding;
+
+/// Merkle MultiProof struct
+pub mod multiproof;
+
+/// Merkle consistency proof struct
+pub mod consistency;
+
 /// A full incremental merkle tree. Suitable for proving.
+#[cfg(feature = "std")]
 pub mod tree;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
 /// Wasm bindings for common operations
 pub mod wasm;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
 #[cfg_attr(target_arch = "wasm32", global_allocator)]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-use ethers::{core::types::H256, prelude::U256};
-use once_cell::sync::Lazy;
+use primitive_types::{H256, U256};
 
 /// Tree depth
 pub const TREE_DEPTH: usize = 32;
 /// A Nomad protocol standard-depth tree
+#[cfg(feature = "std")]
 pub type NomadTree = tree::Tree<TREE_DEPTH>;
 /// An incremental Nomad protocol standard-depth tree
 pub type NomadLightMerkle = light::LightMerkle<TREE_DEPTH>;
 /// A Nomad protocol standard-depth proof
 pub type NomadProof = proof::Proof<TREE_DEPTH>;
+/// A Nomad protocol standard-depth multiproof
+pub type NomadMultiProof = multiproof::MultiProof<TREE_DEPTH>;
+/// A Nomad protocol standard-depth consistency proof
+pub type NomadConsistencyProof = consistency::ConsistencyProof<TREE_DEPTH>;
 
+#[cfg(feature = "std")]
 const EMPTY_SLICE: &[H256] = &[];
 
+pub use consistency::*;
 pub use error::*;
-use full::*;
 pub use light::*;
+pub use multiproof::*;
 pub use proof::*;
+#[cfg(feature = "std")]
 pub use tree::*;
 
 pub use utils::*;
 
-/// A cache of the zero hashes for each layer of the tree.
-pub static ZERO_HASHES: Lazy<[H256; TREE_DEPTH + 1]> = Lazy::new(|| {
-    let mut hashes = [H256::zero(); TREE_DEPTH + 1];
-    for i in 0..TREE_DEPTH {
-        hashes[i + 1] = hash_concat(hashes[i], hashes[i]);
-    }
-    hashes
-});
+/// The keccak256 zero hashes for each layer of the tree, computed at compile
+/// time. See `Hasher::zero_hashes` for other hashers.
+pub static ZERO_HASHES: [H256; TREE_DEPTH + 1] = keccak::zero_hashes();
 
 /// A merkle proof
 pub trait MerkleProof {
     /// Calculate the merkle root of this proof's branch
     fn root(&self) -> H256;
+
+    /// Calculate the merkle root of this proof's branch, or error if the proof
+    /// is malformed
+    fn try_root(&self) -> Result<H256, VerifyingError> {
+        Ok(self.root())
+    }
 }
 
 /// A simple trait for merkle-based accumulators
-pub trait Merkle: std::fmt::Debug + Default {
+pub trait Merkle: core::fmt::Debug + Default {
     /// A proof of some leaf in this tree
     type Proof: MerkleProof;
 
+    /// A proof of some set of leaves in this tree
+    type MultiProof: MerkleProof;
+
     /// The maximum number of elements the tree can ingest
     fn max_elements() -> U256;
 
@@ -88,7 +116,18 @@ pub trait Merkle: std::fmt::Debug + Default {
 
     /// Verify a proof against this tree's root.
     fn verify(&self, proof: &Self::Proof) -> Result<(), VerifyingError> {
-        let actual = proof.root();
+        let actual = proof.try_root()?;
+        let expected = self.root();
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(VerifyingError::VerificationFailed { expected, actual })
+        }
+    }
+
+    /// Verify a multiproof against this tree's root.
+    fn verify_multi(&self, proof: &Self::MultiProof) -> Result<(), VerifyingError> {
+        let actual = proof.try_root()?;
         let expected = self.root();
         if expected == actual {
             Ok(())
@@ -1,38 +1,47 @@
-use ethers::{core::types::H256, prelude::U256};
+use core::marker::PhantomData;
+use primitive_types::{H256, U256};
 
 use crate::{
-    error::IngestionError, utils::hash_concat, Merkle, MerkleProof, Proof, TREE_DEPTH, ZERO_HASHES,
+    error::IngestionError, Hasher, Keccak, Merkle, MerkleProof, MultiProof, Proof, ProvingError,
+    TREE_DEPTH,
 };
 use serde::{Deserialize, Serialize};
 
 /// An incremental merkle tree, modeled on the eth2 deposit contract
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct LightMerkle<const N: usize> {
+pub struct LightMerkle<const N: usize, H: Hasher = Keccak> {
     #[serde(with = "arrays")]
     branch: [H256; N],
     count: usize,
+    #[serde(skip)]
+    hasher: PhantomData<H>,
 }
 
-impl<const N: usize> LightMerkle<N> {
+impl<const N: usize, H: Hasher> LightMerkle<N, H> {
     /// Instantiate new LightMerkle from branch and count
     pub fn new(branch: [H256; N], count: usize) -> Self {
-        Self { branch, count }
+        Self {
+            branch,
+            count,
+            hasher: PhantomData,
+        }
     }
 }
 
-impl<const N: usize> Default for LightMerkle<N> {
+impl<const N: usize, H: Hasher> Default for LightMerkle<N, H> {
     fn default() -> Self {
         let mut branch: [H256; N] = [Default::default(); N];
         branch
             .iter_mut()
             .enumerate()
-            .for_each(|(i, elem)| *elem = ZERO_HASHES[i]);
-        Self { branch, count: 0 }
+            .for_each(|(i, elem)| *elem = H::zero_hashes()[i]);
+        Self::new(branch, 0)
     }
 }
 
-impl<const N: usize> Merkle for LightMerkle<N> {
-    type Proof = Proof<N>;
+impl<const N: usize, H: Hasher> Merkle for LightMerkle<N, H> {
+    type Proof = Proof<N, H>;
+    type MultiProof = MultiProof<N, H>;
 
     /// Return the maximum number of leaves in this tree
     fn max_elements() -> U256 {
@@ -49,9 +58,9 @@ impl<const N: usize> Merkle for LightMerkle<N> {
 
         self.branch.iter().enumerate().for_each(|(i, elem)| {
             node = if (size & 1) == 1 {
-                crate::utils::hash_concat(elem, node)
+                H::hash_concat(elem, node)
             } else {
-                crate::utils::hash_concat(node, ZERO_HASHES[i])
+                H::hash_concat(node, H::zero_hashes()[i])
             };
             size /= 2;
         });
@@ -76,14 +85,14 @@ impl<const N: usize> Merkle for LightMerkle<N> {
                 self.branch[i] = node;
                 return Ok(self.root());
             }
-            node = hash_concat(self.branch[i], node);
+            node = H::hash_concat(self.branch[i], node);
             size /= 2;
         }
         unreachable!()
     }
 }
 
-impl<const N: usize> LightMerkle<N> {
+impl<const N: usize, H: Hasher> LightMerkle<N, H> {
     /// Return the maximum number of leaves in this tree
     pub fn max_leaves() -> U256 {
         crate::utils::max_leaves(N)
@@ -102,22 +111,80 @@ impl<const N: usize> LightMerkle<N> {
 
     /// Calculate the initital root of a tree of this depth
     pub fn initial_root() -> H256 {
-        LightMerkle::<N>::default().root()
+        LightMerkle::<N, H>::default().root()
     }
     /// Get the leading-edge branch.
     pub fn branch(&self) -> &[H256; N] {
         &self.branch
     }
 
+    /// Instantiate the tree containing exactly the leaves up to and including
+    /// the leaf proven by `proof`.
+    ///
+    /// The branch is recovered from the proof. Only the leaf and its
+    /// left-hand siblings are used, so the proof may be against any later
+    /// root.
+    pub fn from_proof(proof: &Proof<N, H>) -> Self {
+        let count = proof.index + 1;
+        let mut node = proof.leaf;
+        let mut branch = Self::default().branch;
+        for (layer, sibling) in proof.path.iter().enumerate() {
+            let index = count >> layer;
+            if index & 1 == 1 {
+                // The lowest set bit of `count` is the last leaf's subtree,
+                // and every higher one is a left-hand sibling on its path
+                branch[layer] = if (count - 1) >> layer == index - 1 {
+                    node
+                } else {
+                    *sibling
+                };
+            }
+            node = if (proof.index >> layer) & 1 == 1 {
+                H::hash_concat(sibling, node)
+            } else {
+                H::hash_concat(node, sibling)
+            };
+        }
+        Self::new(branch, count)
+    }
+
+    /// Roll the tree back to contain only the leaves up to and including the
+    /// leaf proven by `proof`, e.g. after a reorg. Returns the new root.
+    ///
+    /// The light tree does not store the leaves it has ingested, so the
+    /// branch is recovered from the proof as in `LightMerkle::from_proof`.
+    /// The proof must verify against the tree's current root, so that the
+    /// recovered branch is the one this tree was built on. To roll back to an
+    /// empty tree, use `LightMerkle::default`.
+    pub fn rollback(&mut self, proof: &Proof<N, H>) -> Result<H256, ProvingError> {
+        let count = proof.index + 1;
+        if count > self.count {
+            return Err(ProvingError::CountTooHigh {
+                requested: count,
+                count: self.count,
+            });
+        }
+        if !self.verify(proof) {
+            return Err(ProvingError::RootMismatch {
+                expected: self.root(),
+                actual: proof.root(),
+            });
+        }
+
+        *self = Self::from_proof(proof);
+        Ok(self.root())
+    }
+
     /// Verify a incremental merkle proof of inclusion
-    pub fn verify(&self, proof: &Proof<N>) -> bool {
+    pub fn verify(&self, proof: &Proof<N, H>) -> bool {
         proof.root() == self.root()
     }
 }
 
 /// Const generic array deserialization
 pub mod arrays {
-    use std::{convert::TryInto, marker::PhantomData};
+    use alloc::{format, vec::Vec};
+    use core::{convert::TryInto, marker::PhantomData};
 
     use serde::{
         de::{SeqAccess, Visitor},
@@ -145,7 +212,7 @@ pub mod arrays {
     {
         type Value = [T; N];
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str(&format!("an array of length {}", N))
         }
 
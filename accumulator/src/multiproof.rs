@@ -0,0 +1,197 @@
+use crate::{Hasher, Keccak, MerkleProof, VerifyingError};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use primitive_types::H256;
+
+/// A merkle multiproof object. A set of leaves, their indices in the tree, and
+/// the sibling nodes required to reconstruct the root.
+///
+/// Sibling nodes shared by several leaves are included only once. `hashes`
+/// is in "bottom-up" order: all required nodes of the leaf layer (sorted by
+/// position), then all required nodes of the layer above, and so on.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiProof<const N: usize, H: Hasher = Keccak> {
+    /// The leaves, sorted by index
+    pub leaves: Vec<H256>,
+    /// The leaf indices, strictly increasing
+    pub indices: Vec<usize>,
+    /// The sibling nodes not derivable from the leaves
+    pub hashes: Vec<H256>,
+    /// The hasher used to evaluate the proof
+    #[serde(skip)]
+    pub hasher: PhantomData<H>,
+}
+
+impl<const N: usize, H: Hasher> MultiProof<N, H> {
+    /// Return an iterator over `(index, leaf)` pairs in this proof
+    pub fn leaves(&self) -> impl Iterator<Item = (usize, H256)> + '_ {
+        self.indices
+            .iter()
+            .copied()
+            .zip(self.leaves.iter().copied())
+    }
+
+    /// Return the number of leaves proven by this multiproof
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// True if this multiproof contains no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Check the leaf indices are non-empty, strictly increasing, and fit
+    /// within a tree of depth `N`
+    fn check_indices(&self) -> Result<(), VerifyingError> {
+        if self.leaves.is_empty() || self.leaves.len() != self.indices.len() {
+            return Err(VerifyingError::MalformedProof);
+        }
+        let in_range = |index: usize| N >= usize::BITS as usize || index >> N == 0;
+        let sorted = self.indices.windows(2).all(|w| w[0] < w[1]);
+        if !sorted || !self.indices.iter().copied().all(in_range) {
+            return Err(VerifyingError::MalformedProof);
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, H: Hasher> MerkleProof for MultiProof<N, H> {
+    /// Calculate the merkle root produced by evaluating the proof
+    ///
+    /// # Panics
+    ///
+    /// If the proof is malformed. Use `try_root` to handle this case.
+    fn root(&self) -> H256 {
+        self.try_root().expect("malformed multiproof")
+    }
+
+    /// Calculate the merkle root produced by evaluating the proof, or error
+    /// if the proof is malformed
+    fn try_root(&self) -> Result<H256, VerifyingError> {
+        self.check_indices()?;
+
+        let mut nodes: Vec<(usize, H256)> = self.leaves().collect();
+        let mut hashes = self.hashes.iter();
+
+        for _ in 0..N {
+            let mut next = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (index, node) = nodes[i];
+                let parent = if index & 1 == 1 {
+                    let sibling = hashes.next().ok_or(VerifyingError::MalformedProof)?;
+                    H::hash_concat(sibling, node)
+                } else if i + 1 < nodes.len() && nodes[i + 1].0 == index + 1 {
+                    // Both children are known, no sibling needs to be consumed
+                    i += 1;
+                    H::hash_concat(node, nodes[i].1)
+                } else {
+                    let sibling = hashes.next().ok_or(VerifyingError::MalformedProof)?;
+                    H::hash_concat(node, sibling)
+                };
+                next.push((index >> 1, parent));
+                i += 1;
+            }
+            nodes = next;
+        }
+
+        // All siblings must be consumed
+        if hashes.next().is_some() {
+            return Err(VerifyingError::MalformedProof);
+        }
+
+        debug_assert_eq!(nodes.len(), 1);
+        Ok(nodes[0].1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Merkle, Tree};
+
+    fn leaves(n: u64) -> Vec<H256> {
+        (0..n).map(H256::from_low_u64_be).collect()
+    }
+
+    #[test]
+    fn it_proves_and_verifies_sets_of_leaves() {
+        let tree: Tree<8> = leaves(37).into();
+
+        let sets: &[&[usize]] = &[
+            &[0],
+            &[36],
+            &[0, 1],
+            &[1, 2],
+            &[0, 1, 2, 3],
+            &[3, 17, 36],
+            &[5, 4, 5, 20],
+        ];
+        for set in sets {
+            let proof = tree.prove_multi(set).unwrap();
+            tree.verify_multi(&proof).unwrap();
+            assert_eq!(proof.root(), tree.root());
+            for (index, leaf) in proof.leaves() {
+                assert_eq!(tree.prove(index).unwrap().leaf, leaf);
+            }
+        }
+
+        let all: Vec<usize> = (0..37).collect();
+        let proof = tree.prove_multi(&all).unwrap();
+        assert_eq!(proof.len(), 37);
+        tree.verify_multi(&proof).unwrap();
+    }
+
+    #[test]
+    fn it_shares_sibling_nodes() {
+        let tree: Tree<16> = leaves(8).into();
+
+        let single = tree.prove(2).unwrap();
+        let proof = tree.prove_multi(&[2, 3]).unwrap();
+        // leaves 2 and 3 are siblings, so no sibling is needed at the leaf
+        // layer, and the remainder of the path is shared.
+        assert_eq!(proof.hashes.len(), 15);
+        assert_eq!(&proof.hashes[..], &single.path[1..]);
+    }
+
+    #[test]
+    fn it_rejects_bad_proofs() {
+        let tree: Tree<8> = leaves(10).into();
+        let proof = tree.prove_multi(&[1, 4, 9]).unwrap();
+
+        let mut wrong_leaf = proof.clone();
+        wrong_leaf.leaves[1] = H256::repeat_byte(1);
+        assert!(tree.verify_multi(&wrong_leaf).is_err());
+
+        let mut extra_hash = proof.clone();
+        extra_hash.hashes.push(H256::zero());
+        assert!(extra_hash.try_root().is_err());
+
+        let mut missing_hash = proof.clone();
+        missing_hash.hashes.pop();
+        assert!(missing_hash.try_root().is_err());
+
+        let mut unsorted = proof.clone();
+        unsorted.indices.swap(0, 1);
+        assert!(unsorted.try_root().is_err());
+
+        let mut out_of_range = proof;
+        out_of_range.indices[2] = 256;
+        assert!(out_of_range.try_root().is_err());
+
+        assert!(tree.prove_multi(&[]).is_err());
+        assert!(tree.prove_multi(&[1, 10]).is_err());
+    }
+
+    #[test]
+    fn it_roundtrips_through_serde() {
+        let tree: Tree<8> = leaves(10).into();
+        let proof = tree.prove_multi(&[0, 7]).unwrap();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: MultiProof<8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(proof, decoded);
+    }
+}
@@ -1,11 +1,14 @@
-use crate::{merkle_root_from_branch, MerkleProof};
-use ethers::prelude::H256;
+use crate::{
+    merkle_root_from_branch, Hasher, Keccak, LightMerkle, Merkle, MerkleProof, ProvingError,
+};
+use core::marker::PhantomData;
+use primitive_types::H256;
 
 /// A merkle proof object. The leaf, its path to the root, and its index in the
 /// tree.
 #[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct Proof<const N: usize> {
+pub struct Proof<const N: usize, H: Hasher = Keccak> {
     /// The leaf
     pub leaf: H256,
     /// The index
@@ -13,10 +16,123 @@ pub struct Proof<const N: usize> {
     /// The merkle branch
     #[serde(with = "const_array_serde")]
     pub path: [H256; N],
+    /// The hasher used to evaluate the branch
+    #[serde(skip)]
+    pub hasher: PhantomData<H>,
+}
+
+impl<const N: usize, H: Hasher> Proof<N, H> {
+    /// Instantiate a new proof from a leaf, its index, and its path
+    pub fn new(leaf: H256, index: usize, path: [H256; N]) -> Self {
+        Self {
+            leaf,
+            index,
+            path,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Update this proof to verify against the root of `frontier` after
+    /// `leaves` are appended to it. The frontier itself is not modified.
+    ///
+    /// `frontier` must be the tree this proof currently verifies against, or
+    /// an earlier or later state of it from which `update_from_frontier`
+    /// succeeds. Holders of a proof without its tree may pass
+    /// `LightMerkle::from_proof(&proof)` and every leaf appended after the
+    /// proven leaf.
+    pub fn update(
+        &self,
+        frontier: &LightMerkle<N, H>,
+        leaves: &[H256],
+    ) -> Result<Self, ProvingError> {
+        let mut tree = *frontier;
+        let mut path = self.update_from_frontier(frontier)?.path;
+        for leaf in leaves.iter() {
+            // Walk up the subtrees completed by this leaf, replacing any
+            // right-hand siblings on our path
+            let mut node = *leaf;
+            let mut index = tree.count();
+            for (layer, sibling) in path.iter_mut().enumerate() {
+                if index == (self.index >> layer) ^ 1 {
+                    *sibling = node;
+                }
+                if index & 1 == 0 {
+                    break;
+                }
+                node = H::hash_concat(tree.branch()[layer], node);
+                index >>= 1;
+            }
+            tree.ingest(*leaf)?;
+        }
+
+        let proof = Self::new(self.leaf, self.index, self.refresh(&tree, path));
+        debug_assert_eq!(proof.root(), tree.root());
+        Ok(proof)
+    }
+
+    /// Update this proof to verify against the root of `frontier`, a later
+    /// state of the tree this proof verifies against, without the leaves
+    /// appended since.
+    ///
+    /// A right-hand sibling that has been completed can only be recovered
+    /// if it is still on the frontier. If it has already been hashed into a
+    /// higher branch node, this errors with `ProvingError::RootMismatch`,
+    /// and the proof must be updated from the new leaves with `update`.
+    pub fn update_from_frontier(&self, frontier: &LightMerkle<N, H>) -> Result<Self, ProvingError> {
+        let proof = Self::new(self.leaf, self.index, self.refresh(frontier, self.path));
+        proof.check_frontier(frontier)?;
+        Ok(proof)
+    }
+
+    /// Check this proof is of a leaf in `frontier`, against its root
+    fn check_frontier(&self, frontier: &LightMerkle<N, H>) -> Result<(), ProvingError> {
+        if self.index >= frontier.count() {
+            return Err(ProvingError::ZeroProof {
+                index: self.index,
+                count: frontier.count(),
+            });
+        }
+        let (expected, actual) = (frontier.root(), self.root());
+        if expected != actual {
+            return Err(ProvingError::RootMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Replace the siblings in `path` which are empty, partially filled, or
+    /// on the leading-edge branch of `frontier`. Any other nodes in `path`
+    /// are kept.
+    fn refresh(&self, frontier: &LightMerkle<N, H>, mut path: [H256; N]) -> [H256; N] {
+        let count = frontier.count();
+        let zero_hashes = H::zero_hashes();
+
+        // The root of the subtree at the current layer containing the first
+        // empty leaf
+        let mut edge = zero_hashes[0];
+        for (layer, sibling) in path.iter_mut().enumerate() {
+            let index = (self.index >> layer) ^ 1;
+            let edge_index = count >> layer;
+            if index == edge_index {
+                *sibling = edge;
+            } else if index > edge_index {
+                *sibling = zero_hashes[layer];
+            } else if index + 1 == edge_index && edge_index & 1 == 1 {
+                *sibling = frontier.branch()[layer];
+            }
+
+            edge = if edge_index & 1 == 1 {
+                H::hash_concat(frontier.branch()[layer], edge)
+            } else {
+                H::hash_concat(edge, zero_hashes[layer])
+            };
+        }
+        path
+    }
 }
 
 mod const_array_serde {
     use super::H256;
+    use alloc::{format, vec::Vec};
     use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S, const N: usize>(item: &[H256; N], serializer: S) -> Result<S::Ok, S::Error>
@@ -49,9 +165,85 @@ mod const_array_serde {
     }
 }
 
-impl<const N: usize> MerkleProof for Proof<N> {
+impl<const N: usize, H: Hasher> MerkleProof for Proof<N, H> {
     /// Calculate the merkle root produced by evaluating the proof
     fn root(&self) -> H256 {
-        merkle_root_from_branch(self.leaf, self.path.as_ref(), N, self.index)
+        merkle_root_from_branch::<H>(self.leaf, self.path.as_ref(), N, self.index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Tree;
+
+    fn leaves(n: u64) -> Vec<H256> {
+        (0..n).map(H256::from_low_u64_be).collect()
+    }
+
+    #[test]
+    fn it_updates_proofs_with_new_leaves() {
+        let leaves = leaves(20);
+        let trees: Vec<Tree<5>> = (0..=leaves.len()).map(|n| leaves[..n].into()).collect();
+        let frontiers: Vec<LightMerkle<5>> = (0..=leaves.len())
+            .map(|n| LightMerkle::from_leaves(&leaves[..n]))
+            .collect();
+
+        for count in 1..leaves.len() {
+            for new_count in count..=leaves.len() {
+                for index in 0..count {
+                    let proof = trees[count].prove(index).unwrap();
+                    let updated = proof
+                        .update(&frontiers[count], &leaves[count..new_count])
+                        .unwrap();
+                    assert_eq!(updated, trees[new_count].prove(index).unwrap());
+
+                    // or from the proof alone
+                    let from_proof = LightMerkle::from_proof(&proof);
+                    assert_eq!(
+                        proof
+                            .update(&from_proof, &leaves[index + 1..new_count])
+                            .unwrap(),
+                        updated
+                    );
+
+                    // the frontier alone is sufficient unless a completed
+                    // sibling has been hashed into a higher branch node
+                    if let Ok(from_frontier) = proof.update_from_frontier(&frontiers[new_count]) {
+                        assert_eq!(from_frontier, updated);
+                    }
+                }
+            }
+        }
+
+        // Leaves 2 and 3 are hashed together, and only their parent's parent
+        // is on the frontier
+        let proof = Tree::<5>::from(&leaves[..1]).prove(0).unwrap();
+        let frontier = LightMerkle::<5>::from_leaves(&leaves[..4]);
+        assert!(matches!(
+            proof.update_from_frontier(&frontier),
+            Err(ProvingError::RootMismatch { .. })
+        ));
+        // Partially filled siblings are always recovered
+        let proof = Tree::<5>::from(&leaves[..2]).prove(0).unwrap();
+        let frontier = LightMerkle::<5>::from_leaves(&leaves[..3]);
+        assert_eq!(
+            proof.update_from_frontier(&frontier).unwrap(),
+            Tree::<5>::from(&leaves[..3]).prove(0).unwrap()
+        );
+
+        // The proof must be of a leaf in the frontier's history
+        let proof = Tree::<5>::from(&leaves[..5]).prove(1).unwrap();
+        let mut forked = leaves[..6].to_vec();
+        forked[0] = H256::repeat_byte(1);
+        let frontier = LightMerkle::<5>::from_leaves(&forked);
+        assert!(matches!(
+            proof.update(&frontier, &leaves[6..8]),
+            Err(ProvingError::RootMismatch { .. })
+        ));
+        assert!(matches!(
+            proof.update(&LightMerkle::from_leaves(&leaves[..1]), &[]),
+            Err(ProvingError::ZeroProof { .. })
+        ));
     }
 }
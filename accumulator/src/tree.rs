@@ -1,21 +1,27 @@
-use crate::{full::MerkleTree, IngestionError, LightMerkle, Merkle, Proof, ProvingError};
-use ethers::{core::types::H256, prelude::U256};
+use crate::{
+    full::MerkleTree, ConsistencyProof, Hasher, IngestionError, Keccak, LightMerkle, Merkle,
+    MultiProof, Proof, ProvingError,
+};
+use core::marker::PhantomData;
+use primitive_types::{H256, U256};
 
 /// A simplified interface for a full sparse merkle tree
 #[derive(Debug, PartialEq)]
-pub struct Tree<const N: usize> {
+pub struct Tree<const N: usize, H: Hasher = Keccak> {
     count: usize,
     tree: Box<MerkleTree>,
+    hasher: PhantomData<H>,
 }
 
-impl<const N: usize> Default for Tree<N> {
+impl<const N: usize, H: Hasher> Default for Tree<N, H> {
     fn default() -> Self {
         Self::from_leaves(&[])
     }
 }
 
-impl<const N: usize> Merkle for Tree<N> {
-    type Proof = Proof<N>;
+impl<const N: usize, H: Hasher> Merkle for Tree<N, H> {
+    type Proof = Proof<N, H>;
+    type MultiProof = MultiProof<N, H>;
 
     /// Return the maximum number of leaves in this tree
     fn max_elements() -> U256 {
@@ -27,7 +33,7 @@ impl<const N: usize> Merkle for Tree<N> {
     }
 
     fn root(&self) -> H256 {
-        self.tree.hash()
+        self.tree.hash::<H>()
     }
 
     fn depth(&self) -> usize {
@@ -36,30 +42,33 @@ impl<const N: usize> Merkle for Tree<N> {
 
     fn ingest(&mut self, element: H256) -> Result<H256, IngestionError> {
         self.count += 1;
-        self.tree.push_leaf(element, N)?;
-        Ok(self.tree.hash())
+        self.tree.push_leaf::<H>(element, N)?;
+        Ok(self.tree.hash::<H>())
     }
 }
 
-impl<const N: usize> Tree<N> {
-    /// Instantiate a new tree with a known depth and a starting leaf-set
+impl<const N: usize, H: Hasher> Tree<N, H> {
+    /// Instantiate a new tree with a known depth and a starting leaf-set.
+    ///
+    /// With the `rayon` feature, large subtrees are hashed in parallel.
     pub fn from_leaves(leaves: &[H256]) -> Self {
         Self {
             count: leaves.len(),
-            tree: Box::new(MerkleTree::create(leaves, N)),
+            tree: Box::new(MerkleTree::create::<H>(leaves, N)),
+            hasher: PhantomData,
         }
     }
 
     /// Calculate the initital root of a tree of this depth
     pub fn initial_root() -> H256 {
-        LightMerkle::<N>::default().root()
+        LightMerkle::<N, H>::default().root()
     }
 
     /// Return the leaf at `index` and a Merkle proof of its inclusion.
     ///
     /// The Merkle proof is in "bottom-up" order, starting with a leaf node
     /// and moving up the tree. Its length will be exactly equal to `depth`.
-    pub fn prove(&self, index: usize) -> Result<Proof<N>, ProvingError> {
+    pub fn prove(&self, index: usize) -> Result<Proof<N, H>, ProvingError> {
         if index > 2usize.pow(N.try_into().unwrap()) - 1 {
             return Err(ProvingError::IndexTooHigh(index));
         }
@@ -69,15 +78,164 @@ impl<const N: usize> Tree<N> {
             return Err(ProvingError::ZeroProof { index, count });
         }
 
-        let (leaf, nodes) = self.tree.generate_proof(index, N);
+        let (leaf, nodes) = self.tree.generate_proof::<H>(index, N);
         debug_assert_eq!(nodes.len(), N);
         let mut path = [H256::default(); N];
         path.copy_from_slice(&nodes[..N]);
-        Ok(Proof { leaf, index, path })
+        Ok(Proof::new(leaf, index, path))
+    }
+
+    /// Calculate the root the tree had when it contained only its first
+    /// `count` leaves.
+    pub fn root_at(&self, count: usize) -> Result<H256, ProvingError> {
+        if count > self.count() {
+            return Err(ProvingError::CountTooHigh {
+                requested: count,
+                count: self.count(),
+            });
+        }
+        Ok(self.tree.node_hash_at::<H>(0, N, N, count))
+    }
+
+    /// Roll the tree back to contain only its first `count` leaves, e.g.
+    /// after a reorg. Returns the new root.
+    pub fn rollback(&mut self, count: usize) -> Result<H256, ProvingError> {
+        if count > self.count() {
+            return Err(ProvingError::CountTooHigh {
+                requested: count,
+                count: self.count(),
+            });
+        }
+        self.tree.truncate::<H>(count, N);
+        self.count = count;
+        Ok(self.root())
+    }
+
+    /// Return the leaf at `index` and a Merkle proof of its inclusion in the
+    /// tree as it was when it contained only its first `count` leaves.
+    ///
+    /// The proof evaluates to `self.root_at(count)`, so it may be used against
+    /// an older root that a replica has already accepted.
+    pub fn prove_at(&self, index: usize, count: usize) -> Result<Proof<N, H>, ProvingError> {
+        if count > self.count() {
+            return Err(ProvingError::CountTooHigh {
+                requested: count,
+                count: self.count(),
+            });
+        }
+        if index >= count {
+            return Err(ProvingError::ZeroProof { index, count });
+        }
+
+        let leaf = self.tree.node_hash::<H>(index, 0, N);
+        let mut path = [H256::default(); N];
+        for (layer, node) in path.iter_mut().enumerate() {
+            let sibling = (index >> layer) ^ 1;
+            *node = self.tree.node_hash_at::<H>(sibling, layer, N, count);
+        }
+        Ok(Proof::new(leaf, index, path))
+    }
+
+    /// Return a proof that the tree with its first `first` leaves is a prefix
+    /// of the tree with its first `second` leaves.
+    ///
+    /// The proof may be checked against the two roots alone, with
+    /// `ConsistencyProof::verify`.
+    pub fn prove_consistency(
+        &self,
+        first: usize,
+        second: usize,
+    ) -> Result<ConsistencyProof<N, H>, ProvingError> {
+        if second > self.count() {
+            return Err(ProvingError::CountTooHigh {
+                requested: second,
+                count: self.count(),
+            });
+        }
+        if first >= second {
+            return Err(ProvingError::InvalidConsistencyRange { first, second });
+        }
+
+        let mut branch = vec![];
+        let mut path = vec![];
+        for layer in 0..N {
+            let index = first >> layer;
+            if index & 1 == 1 {
+                branch.push(self.tree.node_hash::<H>(index - 1, layer, N));
+            } else if (index + 1) << layer < second {
+                path.push(self.tree.node_hash_at::<H>(index + 1, layer, N, second));
+            }
+        }
+
+        Ok(ConsistencyProof {
+            first_count: first,
+            second_count: second,
+            branch,
+            leaf: self.tree.node_hash::<H>(first, 0, N),
+            path,
+            hasher: PhantomData,
+        })
+    }
+
+    /// Return the leaves at `indices` and a Merkle multiproof of their
+    /// inclusion.
+    ///
+    /// Indices may be passed in any order, and duplicates are ignored. The
+    /// proof contains each required sibling node exactly once.
+    pub fn prove_multi(&self, indices: &[usize]) -> Result<MultiProof<N, H>, ProvingError> {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        if indices.is_empty() {
+            return Err(ProvingError::NoLeaves);
+        }
+
+        let count = self.count();
+        for &index in indices.iter() {
+            if index > 2usize.pow(N.try_into().unwrap()) - 1 {
+                return Err(ProvingError::IndexTooHigh(index));
+            }
+            if index >= count {
+                return Err(ProvingError::ZeroProof { index, count });
+            }
+        }
+
+        let leaves = indices
+            .iter()
+            .map(|&index| self.tree.node_hash::<H>(index, 0, N))
+            .collect();
+
+        // Walk up the tree layer by layer, collecting each sibling that can't
+        // be computed from nodes already in the proof
+        let mut hashes = vec![];
+        let mut nodes = indices.clone();
+        for layer in 0..N {
+            let mut next = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let index = nodes[i];
+                if index & 1 == 0 && i + 1 < nodes.len() && nodes[i + 1] == index + 1 {
+                    i += 1;
+                } else {
+                    hashes.push(self.tree.node_hash::<H>(index ^ 1, layer, N));
+                }
+                next.push(index >> 1);
+                i += 1;
+            }
+            nodes = next;
+        }
+
+        Ok(MultiProof {
+            leaves,
+            indices,
+            hashes,
+            hasher: PhantomData,
+        })
     }
 }
 
-impl<T, const N: usize> From<T> for Tree<N>
+impl<T, const N: usize, H: Hasher> From<T> for Tree<N, H>
 where
     T: AsRef<[H256]>,
 {
@@ -86,7 +244,7 @@ where
     }
 }
 
-impl<const N: usize> std::iter::FromIterator<H256> for Tree<N> {
+impl<const N: usize, H: Hasher> std::iter::FromIterator<H256> for Tree<N, H> {
     /// Will panic if the tree fills
     fn from_iter<I: IntoIterator<Item = H256>>(iter: I) -> Self {
         let mut prover = Self::default();
@@ -95,7 +253,7 @@ impl<const N: usize> std::iter::FromIterator<H256> for Tree<N> {
     }
 }
 
-impl<const N: usize> std::iter::Extend<H256> for Tree<N> {
+impl<const N: usize, H: Hasher> std::iter::Extend<H256> for Tree<N, H> {
     /// Will panic if the tree fills
     fn extend<I: IntoIterator<Item = H256>>(&mut self, iter: I) {
         for i in iter {
@@ -103,3 +261,107 @@ impl<const N: usize> std::iter::Extend<H256> for Tree<N> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MerkleProof;
+
+    #[test]
+    fn it_proves_against_historical_roots() {
+        let leaves: Vec<H256> = (0..20).map(H256::from_low_u64_be).collect();
+        let tree: Tree<8> = leaves.as_slice().into();
+
+        for count in 0..=leaves.len() {
+            let old: Tree<8> = leaves[..count].into();
+            assert_eq!(tree.root_at(count).unwrap(), old.root());
+
+            for index in 0..count {
+                let proof = tree.prove_at(index, count).unwrap();
+                assert_eq!(proof, old.prove(index).unwrap());
+                assert_eq!(proof.root(), old.root());
+            }
+        }
+
+        assert!(matches!(
+            tree.prove_at(20, 20),
+            Err(ProvingError::ZeroProof { .. })
+        ));
+        assert!(tree.prove_at(3, 21).is_err());
+        assert!(tree.root_at(21).is_err());
+    }
+
+    #[test]
+    fn it_rolls_back_to_earlier_counts() {
+        let leaves: Vec<H256> = (0..20).map(H256::from_low_u64_be).collect();
+        let forked: Vec<H256> = (100..120).map(H256::from_low_u64_be).collect();
+
+        for count in 0..=leaves.len() {
+            let mut tree: Tree<8> = leaves.as_slice().into();
+            let mut light = LightMerkle::<8>::from_leaves(&leaves);
+            let old: Tree<8> = leaves[..count].into();
+
+            // The light tree rolls back with a proof against its current root
+            if count > 0 {
+                let proof = tree.prove(count - 1).unwrap();
+                assert_eq!(light.rollback(&proof).unwrap(), old.root());
+                assert_eq!(light.count(), count);
+            }
+            assert_eq!(tree.rollback(count).unwrap(), old.root());
+            assert_eq!(tree.count(), count);
+
+            // Both trees can be re-ingested onto a different history
+            let mut expected = leaves[..count].to_vec();
+            for leaf in forked[count..].iter() {
+                expected.push(*leaf);
+                tree.ingest(*leaf).unwrap();
+                if count > 0 {
+                    light.ingest(*leaf).unwrap();
+                }
+            }
+            let expected: Tree<8> = expected.as_slice().into();
+            assert_eq!(tree, expected);
+            if count > 0 {
+                assert_eq!(light.root(), expected.root());
+            }
+        }
+
+        let mut tree: Tree<8> = leaves.as_slice().into();
+        let mut light = LightMerkle::<8>::from_leaves(&leaves[..5]);
+        assert!(tree.rollback(21).is_err());
+        assert!(light.rollback(&tree.prove(5).unwrap()).is_err());
+
+        // A proof against a different tree is rejected, leaving the tree as is
+        let mut light = LightMerkle::<8>::from_leaves(&leaves);
+        let other: Tree<8> = forked.as_slice().into();
+        assert!(matches!(
+            light.rollback(&other.prove(3).unwrap()),
+            Err(ProvingError::RootMismatch { .. })
+        ));
+        assert_eq!(light.root(), tree.root());
+    }
+
+    fn check_hasher<H: Hasher>() -> H256 {
+        let leaves: Vec<H256> = (0..13).map(H256::from_low_u64_be).collect();
+        let tree: Tree<8, H> = leaves.as_slice().into();
+        let light = LightMerkle::<8, H>::from_leaves(&leaves);
+        assert_eq!(tree.root(), light.root());
+
+        for index in 0..leaves.len() {
+            let proof = tree.prove(index).unwrap();
+            tree.verify(&proof).unwrap();
+            assert!(light.verify(&proof));
+        }
+        tree.root()
+    }
+
+    #[test]
+    fn it_supports_alternate_hashers() {
+        let keccak = check_hasher::<crate::Keccak>();
+        let sha256 = check_hasher::<crate::Sha256>();
+        let blake2b = check_hasher::<crate::Blake2b256>();
+        assert_ne!(keccak, sha256);
+        assert_ne!(keccak, blake2b);
+        assert_ne!(sha256, blake2b);
+    }
+}
@@ -1,6 +1,12 @@
+#[cfg(feature = "std")]
+use blake2::VarBlake2b;
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
 use sha3::{Digest, Keccak256};
 
-use ethers::core::types::{H256, U256};
+use primitive_types::{H256, U256};
+
+use crate::{TREE_DEPTH, ZERO_HASHES};
 
 /// Return the keccak256 digest of the preimage
 pub fn hash(preimage: impl AsRef<[u8]>) -> H256 {
@@ -18,7 +24,146 @@ pub fn hash_concat(left: impl AsRef<[u8]>, right: impl AsRef<[u8]>) -> H256 {
     )
 }
 
+/// Compute a root hash from a leaf and a Merkle proof.
+pub fn merkle_root_from_branch<H: Hasher>(
+    leaf: H256,
+    branch: &[H256],
+    depth: usize,
+    index: usize,
+) -> H256 {
+    assert_eq!(branch.len(), depth, "proof length should equal depth");
+
+    let mut current = leaf;
+
+    for (i, next) in branch.iter().enumerate().take(depth) {
+        let ith_bit = (index >> i) & 0x01;
+        if ith_bit == 1 {
+            current = H::hash_concat(next, current);
+        } else {
+            current = H::hash_concat(current, next);
+        }
+    }
+
+    current
+}
+
 /// Max number of leaves in a tree
 pub(crate) fn max_leaves(n: usize) -> U256 {
     U256::from(2).pow(n.into()) - 1
 }
+
+/// A hash function used to build merkle trees.
+///
+/// Trees, light trees and proofs are generic over their hasher, and default
+/// to `Keccak`, which matches the Nomad contracts on EVM chains.
+pub trait Hasher:
+    core::fmt::Debug + Default + Clone + Copy + PartialEq + Send + Sync + 'static
+{
+    /// Return the digest of the concatenation of the arguments
+    fn hash_concat(left: impl AsRef<[u8]>, right: impl AsRef<[u8]>) -> H256;
+
+    /// Return a cache of the zero hashes for each layer of the tree under
+    /// this hasher
+    fn zero_hashes() -> &'static [H256; TREE_DEPTH + 1];
+}
+
+/// Calculate the zero hashes for each layer of the tree under hasher `H`
+#[cfg(feature = "std")]
+fn calculate_zero_hashes<H: Hasher>() -> [H256; TREE_DEPTH + 1] {
+    let mut hashes = [H256::zero(); TREE_DEPTH + 1];
+    for i in 0..TREE_DEPTH {
+        hashes[i + 1] = H::hash_concat(hashes[i], hashes[i]);
+    }
+    hashes
+}
+
+/// The keccak256 hasher used by the Nomad contracts on EVM chains
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Keccak;
+
+impl Hasher for Keccak {
+    fn hash_concat(left: impl AsRef<[u8]>, right: impl AsRef<[u8]>) -> H256 {
+        hash_concat(left, right)
+    }
+
+    fn zero_hashes() -> &'static [H256; TREE_DEPTH + 1] {
+        &ZERO_HASHES
+    }
+}
+
+/// The sha256 hasher. Requires the `std` feature
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256;
+
+#[cfg(feature = "std")]
+impl Hasher for Sha256 {
+    fn hash_concat(left: impl AsRef<[u8]>, right: impl AsRef<[u8]>) -> H256 {
+        H256::from_slice(
+            sha2::Sha256::new()
+                .chain(left.as_ref())
+                .chain(right.as_ref())
+                .finalize()
+                .as_slice(),
+        )
+    }
+
+    fn zero_hashes() -> &'static [H256; TREE_DEPTH + 1] {
+        static ZEROES: Lazy<[H256; TREE_DEPTH + 1]> = Lazy::new(calculate_zero_hashes::<Sha256>);
+        &ZEROES
+    }
+}
+
+/// The blake2b hasher with a 256-bit output, as used by Substrate chains.
+/// Requires the `std` feature
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Blake2b256;
+
+#[cfg(feature = "std")]
+impl Hasher for Blake2b256 {
+    fn hash_concat(left: impl AsRef<[u8]>, right: impl AsRef<[u8]>) -> H256 {
+        use blake2::digest::{Update, VariableOutput};
+
+        let mut hasher = VarBlake2b::new(32).expect("!output size");
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
+        let mut digest = H256::zero();
+        hasher.finalize_variable(|res| digest.assign_from_slice(res));
+        digest
+    }
+
+    fn zero_hashes() -> &'static [H256; TREE_DEPTH + 1] {
+        static ZEROES: Lazy<[H256; TREE_DEPTH + 1]> =
+            Lazy::new(calculate_zero_hashes::<Blake2b256>);
+        &ZEROES
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_matches_known_digests() {
+        // digests of 64 zero bytes
+        assert_eq!(
+            Keccak::zero_hashes()[1],
+            "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            Sha256::zero_hashes()[1],
+            "0xf5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            Blake2b256::zero_hashes()[1],
+            "0x0eb923b0cbd24df54401d998531feead35a47a99f4deed205de4af81120f9761"
+                .parse()
+                .unwrap()
+        );
+    }
+}
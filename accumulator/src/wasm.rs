@@ -1,10 +1,141 @@
 #![allow(missing_copy_implementations)]
 #![allow(clippy::unused_unit)]
 
+use primitive_types::H256;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{merkle_root_from_branch, Keccak, MerkleProof, MultiProof};
+
+/// Parse an array of hex strings as hashes
+fn parse_hashes(values: &js_sys::Array) -> Result<Vec<H256>, JsValue> {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .as_string()
+                .ok_or_else(|| JsValue::from("Expected an array of strings"))?
+                .parse()
+                .map_err(|e| JsValue::from(format!("Unable to parse element as H256: {}", e)))
+        })
+        .collect()
+}
+
+/// Parse a fixed-size branch from an array of hex strings
+fn parse_branch<const N: usize>(values: &js_sys::Array) -> Result<[H256; N], JsValue> {
+    let hashes = parse_hashes(values)?;
+    if hashes.len() != N {
+        return Err(JsValue::from(format!(
+            "Expected a branch of {} elements. Got {} elements",
+            N,
+            hashes.len()
+        )));
+    }
+    let mut branch = [H256::zero(); N];
+    branch.copy_from_slice(&hashes);
+    Ok(branch)
+}
+
+/// A proof as serialized by the processor, either bare or wrapped with its
+/// message as in the processor's S3 bucket
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProofJson<P> {
+    Bare(P),
+    Proven { proof: P },
+}
+
+impl<P> ProofJson<P> {
+    fn into_proof(self) -> P {
+        match self {
+            ProofJson::Bare(proof) | ProofJson::Proven { proof } => proof,
+        }
+    }
+}
+
+/// A proof of any depth
+#[derive(Deserialize)]
+struct AnyProof {
+    leaf: H256,
+    index: usize,
+    path: Vec<H256>,
+}
+
+/// Parse a proof from a JSON string or a JS object
+fn parse_proof<P: for<'de> Deserialize<'de>>(value: &JsValue) -> Result<P, JsValue> {
+    let value = match value.as_string() {
+        Some(json) => js_sys::JSON::parse(&json)?,
+        None => value.clone(),
+    };
+    value
+        .into_serde::<ProofJson<P>>()
+        .map(ProofJson::into_proof)
+        .map_err(|e| JsValue::from(format!("Unable to parse proof: {}", e)))
+}
+
+#[wasm_bindgen(js_name = "verifyProof")]
+/// Verify a keccak256 merkle proof of any depth against `root`.
+///
+/// The proof may be a JSON string or object, either bare or as stored in the
+/// processor's S3 bucket.
+pub fn verify_proof(root: &str, proof: JsValue) -> Result<bool, JsValue> {
+    let root: H256 = root
+        .parse()
+        .map_err(|e| JsValue::from(format!("Unable to parse root as H256: {}", e)))?;
+    let proof: AnyProof = parse_proof(&proof)?;
+    let depth = proof.path.len();
+    if depth < usize::BITS as usize && proof.index >> depth != 0 {
+        return Err(JsValue::from(format!(
+            "Index {} does not fit in a tree of depth {}",
+            proof.index, depth
+        )));
+    }
+    Ok(merkle_root_from_branch::<Keccak>(proof.leaf, &proof.path, depth, proof.index) == root)
+}
+
+/// Evaluate a multiproof of depth `N` parsed from a JSON string or object
+fn multi_proof_root<const N: usize>(proof: &JsValue) -> Result<H256, JsValue> {
+    parse_proof::<MultiProof<N>>(proof)?
+        .try_root()
+        .map_err(|e| JsValue::from(format!("Unable to evaluate multiproof: {}", e)))
+}
+
+#[wasm_bindgen(js_name = "verifyMultiProof")]
+/// Verify a keccak256 merkle multiproof against `root`.
+///
+/// The proof may be a JSON string or object, either bare or wrapped as
+/// `{ proof }`. A multiproof doesn't encode its tree's depth, so `depth` must
+/// be one of the exported depths. It defaults to 32, the depth of the home's
+/// message tree.
+pub fn verify_multi_proof(
+    root: &str,
+    proof: JsValue,
+    depth: Option<usize>,
+) -> Result<bool, JsValue> {
+    let root: H256 = root
+        .parse()
+        .map_err(|e| JsValue::from(format!("Unable to parse root as H256: {}", e)))?;
+    let evaluate = match depth.unwrap_or(32) {
+        2 => multi_proof_root::<2>,
+        4 => multi_proof_root::<4>,
+        8 => multi_proof_root::<8>,
+        16 => multi_proof_root::<16>,
+        32 => multi_proof_root::<32>,
+        depth => {
+            return Err(JsValue::from(format!(
+                "Unsupported multiproof depth {}",
+                depth
+            )))
+        }
+    };
+    Ok(evaluate(&proof)? == root)
+}
+
 macro_rules! export_tree {
     ($depth:literal) => {
         affix::paste! {
             mod [<internal_ $depth>] {
+                use primitive_types::H256;
                 use wasm_bindgen::prelude::*;
                 use crate::{Merkle, MerkleProof};
 
@@ -18,8 +149,19 @@ macro_rules! export_tree {
                 #[doc = "A merkle proof of depth " $depth]
                 pub struct [<Proof $depth>](pub(crate) crate::Proof<$depth>);
 
+                #[wasm_bindgen(inspectable)]
+                #[derive(Debug, Clone, PartialEq)]
+                #[doc = "A merkle multiproof of depth " $depth]
+                pub struct [<MultiProof $depth>](pub(crate) crate::MultiProof<$depth>);
+
+                #[wasm_bindgen(inspectable)]
+                #[derive(Debug, Default, Clone)]
+                #[doc = "An incremental merkle tree of depth " $depth ", storing only its leading-edge branch"]
+                pub struct [<LightMerkle $depth>](pub(crate) crate::LightMerkle<$depth>);
+
                 type Internal = crate::Tree<$depth>;
                 type InternalProof = crate::Proof<$depth>;
+                type InternalMultiProof = crate::MultiProof<$depth>;
 
                 impl From<InternalProof> for [<Proof $depth>]{
                     fn from(p: InternalProof) -> [<Proof $depth>]{
@@ -27,6 +169,18 @@ macro_rules! export_tree {
                     }
                 }
 
+                impl From<InternalMultiProof> for [<MultiProof $depth>]{
+                    fn from(p: InternalMultiProof) -> [<MultiProof $depth>]{
+                        [<MultiProof $depth>](p)
+                    }
+                }
+
+                impl From<crate::LightMerkle<$depth>> for [<LightMerkle $depth>] {
+                    fn from(p: crate::LightMerkle<$depth>) -> [<LightMerkle $depth>] {
+                        [<LightMerkle $depth>](p)
+                    }
+                }
+
                 impl From<Internal> for [<Tree $depth>] {
                     fn from(p: Internal) -> [<Tree $depth>] {
                         [<Tree $depth>](p)
@@ -58,7 +212,7 @@ macro_rules! export_tree {
                     ///
                     /// This will fail if the underlying tree is full.
                     pub fn ingest(&mut self, element: &str) -> Result<String, JsValue> {
-                        let h: ethers::prelude::H256 = element
+                        let h: H256 = element
                             .parse()
                             .map_err(|e| JsValue::from(format!("Unable to parse element as H256: {}", e)))?;
                         self.0
@@ -98,6 +252,26 @@ macro_rules! export_tree {
                             .verify(&proof.0)
                             .map_err(|e| JsValue::from(format!("Proof verification failed: {}", e)))
                     }
+
+                    #[wasm_bindgen(js_name = "proveMulti")]
+                    /// Return the leaves at `indices` and a Merkle multiproof of their
+                    /// inclusion.
+                    ///
+                    /// Sibling nodes shared by several leaves are included only once.
+                    pub fn prove_multi(&self, indices: &[usize]) -> Result<[<MultiProof $depth>], JsValue> {
+                        self.0
+                            .prove_multi(indices)
+                            .map(Into::into)
+                            .map_err(|e| JsValue::from(format!("Unable to get multiproof for indices {:?}: {}", indices, e)))
+                    }
+
+                    #[wasm_bindgen(js_name = "verifyMulti")]
+                    /// Verify a multiproof against this tree's root.
+                    pub fn verify_multi(&self, proof: [<MultiProof $depth>]) -> Result<(), JsValue> {
+                        self.0
+                            .verify_multi(&proof.0)
+                            .map_err(|e| JsValue::from(format!("Multiproof verification failed: {}", e)))
+                    }
                 }
 
                 #[wasm_bindgen]
@@ -136,6 +310,200 @@ macro_rules! export_tree {
                             .map(JsValue::from)
                             .collect()
                     }
+
+                    #[wasm_bindgen]
+                    /// Update this proof to verify against the tree after `leaves` are
+                    /// appended. `leaves` must be every leaf after the proven leaf.
+                    pub fn update(&self, leaves: js_sys::Array) -> Result<[<Proof $depth>], JsValue> {
+                        let leaves = super::parse_hashes(&leaves)?;
+                        self.0
+                            .update(&crate::LightMerkle::from_proof(&self.0), &leaves)
+                            .map(Into::into)
+                            .map_err(|e| JsValue::from(format!("Unable to update proof: {}", e)))
+                    }
+
+                    #[wasm_bindgen(js_name = "updateFromFrontier")]
+                    /// Update this proof to verify against `frontier`, a later state of
+                    /// its tree.
+                    ///
+                    /// This fails if a sibling on the proof's path has been completed and
+                    /// is no longer on the branch. Use `update` with the new leaves instead.
+                    pub fn update_from_frontier(&self, frontier: &[<LightMerkle $depth>]) -> Result<[<Proof $depth>], JsValue> {
+                        self.0
+                            .update_from_frontier(&frontier.0)
+                            .map(Into::into)
+                            .map_err(|e| JsValue::from(format!("Unable to update proof: {}", e)))
+                    }
+
+                    #[wasm_bindgen(js_name = "fromJson")]
+                    /// Parse a proof from a JSON string or object, either bare or as
+                    /// stored in the processor's S3 bucket.
+                    pub fn from_json(json: JsValue) -> Result<[<Proof $depth>], JsValue> {
+                        super::parse_proof::<InternalProof>(&json).map(Into::into)
+                    }
+
+                    #[wasm_bindgen(js_name = "toJson")]
+                    /// Serialize this proof as a JSON object
+                    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+                        JsValue::from_serde(&self.0)
+                            .map_err(|e| JsValue::from(format!("Unable to serialize proof: {}", e)))
+                    }
+                }
+
+                #[wasm_bindgen]
+                impl [<LightMerkle $depth>] {
+                    #[wasm_bindgen(constructor)]
+                    #[doc = "Instantiate a new incremental merkle tree of depth " $depth]
+                    pub fn new() -> [<LightMerkle $depth>] {
+                        Self(Default::default())
+                    }
+
+                    #[wasm_bindgen(js_name = "fromBranch")]
+                    /// Instantiate a tree from its leading-edge branch and leaf count, e.g.
+                    /// as read from the home contract.
+                    pub fn from_branch(branch: js_sys::Array, count: usize) -> Result<[<LightMerkle $depth>], JsValue> {
+                        let branch = super::parse_branch::<$depth>(&branch)?;
+                        Ok(Self(crate::LightMerkle::new(branch, count)))
+                    }
+
+                    #[wasm_bindgen(js_name = "fromProof")]
+                    /// Instantiate the tree containing exactly the leaves up to and
+                    /// including the leaf proven by `proof`.
+                    pub fn from_proof(proof: &[<Proof $depth>]) -> [<LightMerkle $depth>] {
+                        Self(crate::LightMerkle::from_proof(&proof.0))
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Get the current count of leaves in the tree
+                    pub fn count(&self) -> usize {
+                        self.0.count()
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Get the tree's depth
+                    pub fn depth(&self) -> usize {
+                        self.0.depth()
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Get the leading-edge branch
+                    pub fn branch(&self) -> js_sys::Array {
+                        self.0
+                            .branch()
+                            .iter()
+                            .map(|hash| format!("{:?}", hash))
+                            .map(JsValue::from)
+                            .collect()
+                    }
+
+                    #[wasm_bindgen]
+                    /// Retrieve the root hash of this Merkle tree.
+                    pub fn root(&self) -> String {
+                        format!("{:?}", self.0.root())
+                    }
+
+                    #[wasm_bindgen]
+                    /// Push a leaf to the tree. Appends it to the first unoccupied slot
+                    ///
+                    /// This will fail if the underlying tree is full.
+                    pub fn ingest(&mut self, element: &str) -> Result<String, JsValue> {
+                        let h: H256 = element
+                            .parse()
+                            .map_err(|e| JsValue::from(format!("Unable to parse element as H256: {}", e)))?;
+                        self.0
+                            .ingest(h)
+                            .map(|root| format!("{:?}", root))
+                            .map_err(|e| format!("Unable to ingest element: {}", e).into())
+                    }
+
+                    #[wasm_bindgen]
+                    /// Verify a proof against this tree's root.
+                    pub fn verify(&self, proof: &[<Proof $depth>]) -> bool {
+                        self.0.verify(&proof.0)
+                    }
+
+                    #[wasm_bindgen]
+                    /// Roll the tree back to contain only the leaves up to and including
+                    /// the leaf proven by `proof`, which must verify against the current
+                    /// root. Returns the new root.
+                    pub fn rollback(&mut self, proof: &[<Proof $depth>]) -> Result<String, JsValue> {
+                        self.0
+                            .rollback(&proof.0)
+                            .map(|root| format!("{:?}", root))
+                            .map_err(|e| JsValue::from(format!("Unable to roll back tree: {}", e)))
+                    }
+                }
+
+                #[wasm_bindgen]
+                impl [<MultiProof $depth>] {
+                    #[wasm_bindgen(js_name = "fromJson")]
+                    /// Parse a multiproof from a JSON string or object, either bare or
+                    /// wrapped as `{ proof }`.
+                    pub fn from_json(json: JsValue) -> Result<[<MultiProof $depth>], JsValue> {
+                        super::parse_proof::<InternalMultiProof>(&json).map(Into::into)
+                    }
+
+                    #[wasm_bindgen(js_name = "toJson")]
+                    /// Serialize this multiproof as a JSON object
+                    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+                        JsValue::from_serde(&self.0)
+                            .map_err(|e| JsValue::from(format!("Unable to serialize multiproof: {}", e)))
+                    }
+
+                    #[wasm_bindgen]
+                    /// Verify this multiproof against `root`.
+                    pub fn verify(&self, root: &str) -> Result<bool, JsValue> {
+                        let root: H256 = root
+                            .parse()
+                            .map_err(|e| JsValue::from(format!("Unable to parse root as H256: {}", e)))?;
+                        self.0
+                            .try_root()
+                            .map(|actual| actual == root)
+                            .map_err(|e| JsValue::from(format!("Unable to evaluate multiproof: {}", e)))
+                    }
+
+                    #[wasm_bindgen]
+                    /// Retrieve the root hash of this Merkle multiproof.
+                    pub fn root(&self) -> Result<String, JsValue> {
+                        self.0
+                            .try_root()
+                            .map(|root| format!("{:?}", root))
+                            .map_err(|e| JsValue::from(format!("Unable to evaluate multiproof: {}", e)))
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Retrieve the leaf hashes of this merkle multiproof.
+                    pub fn leaves(&self) -> js_sys::Array {
+                        self.0
+                            .leaves
+                            .iter()
+                            .map(|hash| format!("{:?}", hash))
+                            .map(JsValue::from)
+                            .collect()
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Retrieve the leaf indices of this merkle multiproof.
+                    pub fn indices(&self) -> Vec<usize> {
+                        self.0.indices.clone()
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Get the depth of the tree associated with this multiproof.
+                    pub fn depth(&self) -> usize {
+                        $depth
+                    }
+
+                    #[wasm_bindgen(getter)]
+                    /// Retrieve the sibling nodes of this merkle multiproof.
+                    pub fn hashes(&self) -> js_sys::Array {
+                        self.0
+                            .hashes
+                            .iter()
+                            .map(|hash| format!("{:?}", hash))
+                            .map(JsValue::from)
+                            .collect()
+                    }
                 }
             }
         }
@@ -8,7 +8,9 @@ use tokio::{sync::Mutex, task::JoinHandle, time::sleep};
 use tracing::{info, Instrument};
 
 use ethers::core::types::H256;
-use nomad_base::{decl_agent, decl_channel, AgentCore, CachingHome, CachingReplica, NomadAgent};
+use nomad_base::{
+    decl_agent, decl_channel, AgentCore, CachingHome, CachingReplica, ChainSetup, NomadAgent,
+};
 use nomad_core::{Common, Home, Message, Replica};
 use nomad_xyz_configuration::agent::kathy::ChatGenConfig;
 
@@ -43,6 +45,7 @@ impl Kathy {
 }
 
 decl_channel!(Kathy {
+    replica_setup: ChainSetup,
     home_lock: Arc<Mutex<()>>,
     generator: ChatGenerator,
     messages_dispatched: prometheus::IntCounter,
@@ -68,6 +71,7 @@ impl NomadAgent for Kathy {
     fn build_channel(&self, replica: &str) -> Self::Channel {
         Self::Channel {
             base: self.channel_base(replica),
+            replica_setup: self.core.settings.replicas[replica].clone(),
             home_lock: self.home_lock.clone(),
             generator: self.generator.clone(),
             messages_dispatched: self.messages_dispatched.with_label_values(&[
@@ -85,6 +89,7 @@ impl NomadAgent for Kathy {
             async move {
                 let home = channel.home();
                 let destination = channel.replica().local_domain();
+                let replica_setup = channel.replica_setup;
                 let mut generator = channel.generator;
                 let home_lock = channel.home_lock;
                 let messages_dispatched = channel.messages_dispatched;
@@ -96,6 +101,8 @@ impl NomadAgent for Kathy {
 
                     match msg {
                         Some(body) => {
+                            let recipient_address =
+                                replica_setup.display_identifier(&recipient.into());
                             let message = Message {
                                 destination,
                                 recipient,
@@ -106,11 +113,19 @@ impl NomadAgent for Kathy {
                                 "Enqueuing message of length {} to {}::{}",
                                 length = message.body.len(),
                                 destination = message.destination,
-                                recipient = message.recipient
+                                recipient = recipient_address
                             );
 
                             let guard = home_lock.lock().await;
-                            home.dispatch(&message).await?;
+                            let committed = home.dispatch_committed(&message).await?;
+                            info!(
+                                target: "outgoing_messages",
+                                leaf_index = committed.leaf_index,
+                                nonce = committed.message.nonce,
+                                committed_root = ?committed.committed_root,
+                                leaf = ?committed.to_leaf(),
+                                "Message committed"
+                            );
 
                             messages_dispatched.inc();
 
@@ -13,7 +13,7 @@ use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
 use nomad_base::{
     cancel_task, decl_agent, decl_channel, AgentCore, CachingHome, CachingReplica,
-    ChainCommunicationError, NomadAgent, NomadDB, ProcessorError,
+    ChainCommunicationError, ChainSetup, NomadAgent, NomadDB, ProcessorError,
 };
 use nomad_core::{
     accumulator::{MerkleProof, NomadProof},
@@ -37,6 +37,7 @@ pub(crate) struct Replica {
     interval: u64,
     replica: Arc<CachingReplica>,
     home: Arc<CachingHome>,
+    home_setup: ChainSetup,
     db: NomadDB,
     allowed: Option<Arc<HashSet<H256>>>,
     denied: Option<Arc<HashSet<H256>>>,
@@ -155,12 +156,19 @@ impl Replica {
         };
 
         info!(target: "seen_committed_messages", leaf_index = message.leaf_index);
+        if let Ok(bridge_message) = message.message.bridge_message() {
+            debug!(
+                leaf_index = message.leaf_index,
+                bridge_message = %bridge_message,
+                "Decoded BridgeRouter message"
+            );
+        }
         let sender = message.message.sender;
 
         // if we have an allow list, filter senders not on it
         if let Some(false) = self.allowed.as_ref().map(|set| set.contains(&sender)) {
             info!(
-                sender = ?sender,
+                sender = %self.home_setup.display_identifier(&sender.into()),
                 domain = domain,
                 nonce = nonce,
                 "Skipping message because sender not on allow list."
@@ -171,7 +179,7 @@ impl Replica {
         // if we have a deny list, filter senders on it
         if let Some(true) = self.denied.as_ref().map(|set| set.contains(&sender)) {
             info!(
-                sender = ?sender,
+                sender = %self.home_setup.display_identifier(&sender.into()),
                 domain = domain,
                 nonce = nonce,
                 "Skipping message because sender on deny list."
@@ -316,6 +324,7 @@ impl Processor {
 }
 
 decl_channel!(Processor {
+    home_setup: ChainSetup,
     next_message_nonce: prometheus::IntGauge,
     allowed: Option<Arc<HashSet<H256>>>,
     denied: Option<Arc<HashSet<H256>>>,
@@ -357,6 +366,7 @@ impl NomadAgent for Processor {
     fn build_channel(&self, replica: &str) -> Self::Channel {
         Self::Channel {
             base: self.channel_base(replica),
+            home_setup: self.core.settings.home.clone(),
             next_message_nonce: self.next_message_nonces.with_label_values(&[
                 self.home().name(),
                 replica,
@@ -375,6 +385,7 @@ impl NomadAgent for Processor {
                     interval: channel.interval,
                     replica: channel.replica(),
                     home: channel.home(),
+                    home_setup: channel.home_setup,
                     db: channel.db(),
                     allowed: channel.allowed,
                     denied: channel.denied,
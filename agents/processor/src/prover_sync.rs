@@ -1,8 +1,8 @@
 use color_eyre::eyre::{bail, Result};
 use ethers::core::types::H256;
-use nomad_base::{ChainCommunicationError, NomadDB};
+use nomad_base::{ChainCommunicationError, NomadDB, PersistentTree, PersistentTreeError};
 use nomad_core::{
-    accumulator::{Merkle, NomadTree, ProvingError},
+    accumulator::{Merkle, MerkleProof, NomadLightMerkle, NomadProof, NomadTree, ProvingError},
     db::DbError,
 };
 use std::{fmt::Display, time::Duration};
@@ -10,13 +10,23 @@ use tokio::{
     task::JoinHandle,
     time::{sleep, timeout},
 };
-use tracing::{debug, error, info, info_span, instrument, Instrument};
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
+
+/// Times to wait for a missing leaf before giving up
+const MAX_LEAF_WAITS: usize = 5;
+
+/// Times to rewind and re-fetch leaves that don't produce a signed root
+/// before giving up
+const MAX_REWINDS: usize = 5;
+
+/// Seconds to wait for invalidated leaves to be re-fetched after a rewind
+const REWIND_DELAY_SECS: u64 = 60;
 
 /// Struct to sync prover.
 #[derive(Debug)]
 pub struct ProverSync {
     db: NomadDB,
-    prover: NomadTree,
+    prover: PersistentTree,
 }
 
 impl Display for ProverSync {
@@ -58,9 +68,19 @@ pub enum ProverSyncError {
         /// Leaf index for missing leaf
         leaf_index: usize,
     },
+    /// No tree size was recorded for a root, so it can't be proven against
+    #[error("No tree size recorded for root {root:?}")]
+    UnknownRoot {
+        /// Root without a recorded tree size
+        root: H256,
+    },
     /// ProverSync attempts Prover operation and receives ProvingError
     #[error(transparent)]
     ProvingError(#[from] ProvingError),
+    /// ProverSync attempts operation on its disk-backed tree and receives
+    /// PersistentTreeError
+    #[error(transparent)]
+    PersistentTreeError(#[from] PersistentTreeError),
     /// ProverSync receives ChainCommunicationError from chain API
     #[error(transparent)]
     ChainCommunicationError(#[from] ChainCommunicationError),
@@ -95,37 +115,118 @@ impl ProverSync {
             }
             // ignore the storage request if it's out of range (e.g. leaves
             // up-to-date but no update containing leaves produced yet)
-            Err(ProvingError::ZeroProof { index: _, count: _ }) => Ok(()),
+            Err(PersistentTreeError::ProvingError(ProvingError::ZeroProof {
+                index: _,
+                count: _,
+            })) => Ok(()),
             // bubble up any other errors
             Err(e) => Err(e.into()),
         }
     }
 
+    /// Prove the leaf at `leaf_index` against `root`, which may be older than
+    /// the prover's current root, using the tree size recorded for it
+    pub fn historical_proof(
+        &self,
+        leaf_index: u32,
+        root: H256,
+    ) -> Result<NomadProof, ProverSyncError> {
+        let count = self
+            .db
+            .tree_size_by_root(root)?
+            .ok_or(ProverSyncError::UnknownRoot { root })?;
+
+        let proof = self.prover.prove_at(leaf_index as usize, count as usize)?;
+        if proof.root() != root {
+            return Err(ProverSyncError::MismatchedRoots {
+                local_root: proof.root(),
+                new_root: root,
+            });
+        }
+        Ok(proof)
+    }
+
     /// Given rocksdb handle `db` containing merkle tree leaves,
-    /// instantiates new `ProverSync` and fills prover's merkle tree
+    /// instantiates new `ProverSync` and resumes prover's merkle tree from
+    /// the nodes stored on disk
     #[instrument(level = "debug", skip(db))]
     pub fn from_disk(db: NomadDB) -> Self {
-        // Ingest all leaves in db into prover tree
-        let mut prover = NomadTree::default();
-
-        if let Some(root) = db.retrieve_prover_latest_committed().expect("db error") {
-            for i in 0.. {
-                match db.leaf_by_leaf_index(i) {
-                    Ok(Some(leaf)) => {
-                        debug!(leaf_index = i, "Ingesting leaf from_disk");
-                        prover.ingest(leaf).expect("!tree full");
-                        if prover.root() == root {
-                            break;
-                        }
-                    }
-                    Ok(None) => break,
+        let committed = db.retrieve_prover_latest_committed().expect("db error");
+
+        // Resume the prover tree at the size recorded under the latest
+        // committed root
+        let resumed = committed
+            .and_then(|root| {
+                db.tree_size_by_root(root)
+                    .expect("db error")
+                    .map(|size| (root, size))
+            })
+            .and_then(
+                |(root, size)| match PersistentTree::open(db.clone(), size as usize) {
+                    Ok(prover) if prover.root() == root => Some(prover),
+                    Ok(_) => None,
                     Err(e) => {
-                        error!(error = %e, "Error in ProverSync::from_disk");
-                        panic!("Error in ProverSync::from_disk");
+                        debug!(error = %e, "Could not resume ProverSync from disk");
+                        None
                     }
+                },
+            );
+
+        if let Some(prover) = resumed {
+            info!(root = ?prover.root(), size = prover.count(), "Resumed ProverSync from disk");
+            return Self { prover, db };
+        }
+
+        // Otherwise, the tree's nodes were never written (or are incomplete),
+        // so rebuild it from all leaves in db
+        let mut leaves = vec![];
+        for i in 0.. {
+            match db.leaf_by_leaf_index(i) {
+                Ok(Some(leaf)) => leaves.push(leaf),
+                Ok(None) => break,
+                Err(e) => {
+                    error!(error = %e, "Error in ProverSync::from_disk");
+                    panic!("Error in ProverSync::from_disk");
                 }
             }
-            info!(target_latest_root = ?root, root = ?prover.root(), "Reloaded ProverSync from disk");
+        }
+
+        // The tree contains the leaves up to the latest committed root
+        let size = match committed {
+            Some(root) => match db.tree_size_by_root(root).expect("db error") {
+                Some(size) if size as usize <= leaves.len() => size as usize,
+                _ => {
+                    let mut light = NomadLightMerkle::default();
+                    leaves
+                        .iter()
+                        .position(|leaf| light.ingest(*leaf).expect("!tree full") == root)
+                        .map_or(0, |index| index + 1)
+                }
+            },
+            None => 0,
+        };
+
+        let mut prover = PersistentTree::build(db.clone(), &leaves[..size]).expect("db error");
+        if let Some(root) = committed {
+            if prover.root() == root {
+                // Record the size, so that the next start resumes from disk
+                db.store_tree_size_by_root(root, size as u32)
+                    .expect("db error");
+                info!(target_latest_root = ?root, root = ?prover.root(), size, "Rebuilt ProverSync tree");
+            } else {
+                // A tree whose root matches no signed update would never
+                // find its next update. Start over from the first update
+                // instead
+                error!(
+                    target_latest_root = ?root,
+                    root = ?prover.root(),
+                    size,
+                    leaves = leaves.len(),
+                    "Leaves in db do not produce latest committed root {}. Rebuilding ProverSync tree from the first update",
+                    root
+                );
+                prover.rollback(0).expect("db error");
+            }
         }
 
         let sync = Self { prover, db };
@@ -146,23 +247,43 @@ impl ProverSync {
     }
 
     /// Given new root, update prover tree with leaves until prover tree root
-    /// matches new_root
+    /// matches new_root. Errors with `MismatchedRoots` if the next leaf
+    /// can't be under the signed root, and the tree still doesn't match it
     #[instrument(level = "debug", skip(self))]
     async fn update_prover_tree(&mut self, new_root: H256) -> Result<(), ProverSyncError> {
-        // Update in-memory prover tree
+        // Leaves dispatched after the update was submitted can't be under
+        // its new root
+        let update_block = self
+            .db
+            .retrieve_update_metadata(new_root)?
+            .map(|metadata| metadata.block_number);
+
+        // Update disk-backed prover tree
         while self.prover.root() != new_root {
             let tree_size = self.prover.count();
-            let leaf_fut = self.db.wait_for_leaf(tree_size as u32);
 
-            // Wait for leaf for 60 seconds and error out if not found
-            info!("Waiting for leaf at index {}...", tree_size);
-            let leaf = match timeout(Duration::from_secs(60), leaf_fut).await {
-                Ok(res) => res.map_err(ProverSyncError::DbError),
-                Err(_) => Err(ProverSyncError::LeafNotFound {
+            // Wait for leaf for 60 seconds at a time, and error out if it is
+            // still not found after `MAX_LEAF_WAITS` attempts
+            let mut leaf = None;
+            for attempt in 1..=MAX_LEAF_WAITS {
+                info!(attempt, "Waiting for leaf at index {}...", tree_size);
+                let leaf_fut = self.db.wait_for_leaf(tree_size as u32);
+                if let Ok(res) = timeout(Duration::from_secs(60), leaf_fut).await {
+                    leaf = Some(res?);
+                    break;
+                }
+            }
+            let leaf = leaf.ok_or(ProverSyncError::LeafNotFound {
+                new_root,
+                leaf_index: tree_size,
+            })?;
+
+            if self.beyond_update(leaf, new_root, update_block)? {
+                return Err(ProverSyncError::MismatchedRoots {
+                    local_root: self.prover.root(),
                     new_root,
-                    leaf_index: tree_size,
-                }),
-            }?;
+                });
+            }
 
             info!(
                 index = tree_size,
@@ -171,12 +292,41 @@ impl ProverSync {
                 tree_size,
                 leaf
             );
-            self.prover.ingest(leaf).expect("!tree_full");
+            self.prover.ingest(leaf)?;
         }
 
         Ok(())
     }
 
+    /// True if `leaf` was dispatched after `new_root` was committed, or after
+    /// the block its update was submitted in, so that it can't be under
+    /// `new_root`
+    fn beyond_update(
+        &self,
+        leaf: H256,
+        new_root: H256,
+        update_block: Option<u64>,
+    ) -> Result<bool, ProverSyncError> {
+        if let Some(message) = self.db.message_by_leaf(leaf)? {
+            if message.committed_root == new_root {
+                return Ok(true);
+            }
+        }
+
+        match (update_block, self.db.retrieve_message_metadata(leaf)?) {
+            (Some(update_block), Some(metadata)) => Ok(metadata.block_number > update_block),
+            _ => Ok(false),
+        }
+    }
+
+    /// Rewind the prover tree to `size` leaves, and invalidate the leaves
+    /// after it in db, so that they are re-fetched instead of re-ingested
+    fn rewind(&mut self, size: usize) -> Result<(), ProverSyncError> {
+        self.prover.rollback(size)?;
+        self.db.invalidate_leaves_from(size as u32)?;
+        Ok(())
+    }
+
     /// Consume self and poll for signed updates at regular interval. Update
     /// local merkle tree with all leaves between local root and
     /// new root. Use short interval for bootup syncing and longer
@@ -185,6 +335,9 @@ impl ProverSync {
         let span = info_span!("ProverSync", self = %self);
         tokio::spawn(
             async move {
+                // Consecutive rewinds for the current update
+                let mut rewinds = 0;
+
                 loop {
                     // Try to retrieve new signed update
                     let local_root = self.local_root();
@@ -202,24 +355,54 @@ impl ProverSync {
                             new_root
                         );
 
-                        // Update in-memory prover tree until local tree root
+                        // Update prover tree until local tree root
                         // matches newly found new_root
                         let pre_update_size = self.prover.count();
-                        self.update_prover_tree(new_root).await?;
-
-                        // Double check that update new root now equals current prover root
-                        let current_root = self.prover.root();
-                        if current_root != new_root {
-                            bail!(ProverSyncError::MismatchedRoots {
-                                local_root: current_root,
-                                new_root,
-                            });
+                        match self.update_prover_tree(new_root).await {
+                            Ok(()) => rewinds = 0,
+                            // The leaves in the db do not produce the signed
+                            // root (e.g. after a reorg). Rewind to the
+                            // previous root, which matches a signed update,
+                            // and re-ingest from there once the leaves are
+                            // re-fetched. A missing leaf is not a reorg, and
+                            // is bubbled up after retrying
+                            Err(e @ ProverSyncError::MismatchedRoots { .. })
+                                if rewinds < MAX_REWINDS =>
+                            {
+                                rewinds += 1;
+                                warn!(
+                                    error = %e,
+                                    previous_root = ?previous_root,
+                                    size = pre_update_size,
+                                    rewinds,
+                                    "Rewinding prover tree to previous root {}",
+                                    previous_root,
+                                );
+                                self.rewind(pre_update_size)?;
+                                sleep(Duration::from_secs(REWIND_DELAY_SECS)).await;
+                                continue;
+                            }
+                            Err(e) => bail!(e),
                         }
 
-                        // Ensure there is a proof in the db for all leaves
+                        // Record the tree size under the new root, so that
+                        // historical proofs against it can be produced later
+                        self.db
+                            .store_tree_size_by_root(new_root, self.prover.count() as u32)?;
+
+                        // Ensure there is a proof in the db for all leaves,
+                        // against the signed root
                         for idx in pre_update_size..self.prover.count() {
-                            if self.db.proof_by_leaf_index(idx as u32)?.is_none() {
-                                self.store_proof(idx as u32)?;
+                            let idx = idx as u32;
+                            if self.db.proof_by_leaf_index(idx)?.is_none() {
+                                let proof = self.historical_proof(idx, new_root)?;
+                                self.db.store_proof(idx, &proof)?;
+                                info!(
+                                    leaf_index = idx,
+                                    root = ?new_root,
+                                    "Storing proof for leaf {}",
+                                    idx
+                                );
                             }
                         }
 
@@ -240,3 +423,112 @@ impl ProverSync {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nomad_core::{MessageMeta, NomadMessage, RawCommittedMessage, RawCommittedMessageWithMeta};
+    use nomad_test::test_utils::run_test_db;
+
+    fn message(leaf_index: u32, committed_root: H256, body: u8) -> RawCommittedMessageWithMeta {
+        let message = NomadMessage {
+            origin: 1000,
+            sender: H256::from_low_u64_be(1),
+            nonce: leaf_index,
+            destination: 2000,
+            recipient: H256::from_low_u64_be(2),
+            body: vec![body],
+        };
+        RawCommittedMessageWithMeta {
+            raw_message: RawCommittedMessage {
+                leaf_index,
+                committed_root,
+                message: message.to_vec(),
+            },
+            metadata: MessageMeta {
+                block_number: 10 + leaf_index as u64,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn it_rewinds_on_leaves_not_under_signed_root() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+
+            let canonical = [message(0, H256::zero(), 0), message(1, H256::zero(), 1)];
+            let mut light = NomadLightMerkle::default();
+            for message in canonical.iter() {
+                light.ingest(message.raw_message.leaf()).unwrap();
+            }
+            let new_root = light.root();
+
+            // Reorged-out leaf at index 1, followed by a leaf dispatched
+            // after the signed root was committed
+            let stale = [
+                canonical[0].clone(),
+                message(1, H256::zero(), 0xff),
+                message(2, new_root, 2),
+            ];
+            db.store_messages(&stale).unwrap();
+
+            let mut sync = ProverSync::from_disk(db.clone());
+            assert_eq!(sync.prover.count(), 0);
+
+            match sync.update_prover_tree(new_root).await {
+                Err(ProverSyncError::MismatchedRoots {
+                    local_root,
+                    new_root: root,
+                }) => {
+                    assert_eq!(root, new_root);
+                    assert_ne!(local_root, new_root);
+                }
+                res => panic!("expected MismatchedRoots, got {:?}", res),
+            }
+            assert_eq!(sync.prover.count(), 2);
+
+            sync.rewind(0).unwrap();
+            assert_eq!(sync.prover.count(), 0);
+            assert_eq!(db.leaf_by_leaf_index(0).unwrap(), None);
+            assert_eq!(db.retrieve_latest_leaf_index().unwrap(), None);
+
+            // The re-fetched leaves produce the signed root
+            db.store_messages(&canonical).unwrap();
+            sync.update_prover_tree(new_root).await.unwrap();
+            assert_eq!(sync.prover.root(), new_root);
+            assert_eq!(sync.prover.count(), 2);
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn it_records_tree_size_when_rebuilding_from_leaves() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+
+            let messages: Vec<_> = (0..3).map(|i| message(i, H256::zero(), i as u8)).collect();
+            db.store_messages(&messages).unwrap();
+
+            let mut light = NomadLightMerkle::default();
+            for message in messages[..2].iter() {
+                light.ingest(message.raw_message.leaf()).unwrap();
+            }
+            let committed = light.root();
+            db.store_prover_latest_committed(committed).unwrap();
+
+            let sync = ProverSync::from_disk(db.clone());
+            assert_eq!(sync.prover.root(), committed);
+            assert_eq!(db.tree_size_by_root(committed).unwrap(), Some(2));
+
+            // Leaves that never produce the committed root are not built
+            // into an unmatched tree
+            db.store_prover_latest_committed(H256::repeat_byte(1))
+                .unwrap();
+            let sync = ProverSync::from_disk(db.clone());
+            assert_eq!(sync.prover.count(), 0);
+            assert_eq!(sync.local_root(), H256::zero());
+        })
+        .await
+    }
+}
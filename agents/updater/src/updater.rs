@@ -5,10 +5,9 @@ use crate::{
 };
 use async_trait::async_trait;
 use color_eyre::{eyre::ensure, Result};
-use ethers::{signers::Signer, types::Address};
 use futures_util::future::select_all;
-use nomad_base::{AgentCore, AttestationSigner, CachingHome, NomadAgent, NomadDB};
-use nomad_core::{Common, FromSignerConf};
+use nomad_base::{AgentCore, AttestationSigner, Attester, CachingHome, NomadAgent, NomadDB};
+use nomad_core::{Common, FromSignerConf, NomadIdentifier};
 use prometheus::IntCounter;
 use tokio::task::JoinHandle;
 use tracing::{info, Instrument};
@@ -141,7 +140,7 @@ impl NomadAgent for Updater {
 
     fn run(channel: Self::Channel) -> JoinHandle<Result<()>> {
         let home = channel.home.clone();
-        let address = channel.signer.address();
+        let identifier = channel.signer.identifier();
         let db = channel.db.clone();
 
         let produce = UpdateProducer::new(
@@ -162,12 +161,12 @@ impl NomadAgent for Updater {
 
         tokio::spawn(
             async move {
-                let expected: Address = home.updater().await?.into();
+                let expected: NomadIdentifier = home.updater().await?.into();
                 ensure!(
-                    expected == address,
+                    expected == identifier,
                     "Contract updater does not match keys. On-chain: {}. Local: {}",
                     expected,
-                    address
+                    identifier
                 );
 
                 // Only spawn updater tasks once syncing has finished
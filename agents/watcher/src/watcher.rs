@@ -1053,11 +1053,7 @@ mod test {
                     .expect__double_update()
                     .withf(move |d: &DoubleUpdate| *d == double)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
             {
                 mock_replica_1
@@ -1070,11 +1066,7 @@ mod test {
                     .expect__double_update()
                     .withf(move |d: &DoubleUpdate| *d == double)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
             {
                 mock_replica_2
@@ -1087,11 +1079,7 @@ mod test {
                     .expect__double_update()
                     .withf(move |d: &DoubleUpdate| *d == double)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
 
             // Connection manager expectations
@@ -1102,11 +1090,7 @@ mod test {
                     .expect__unenroll_replica()
                     .withf(move |f: &SignedFailureNotification| *f == signed_failure)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
             {
                 // connection_manager_2.unenroll_replica called once
@@ -1115,11 +1099,7 @@ mod test {
                     .expect__unenroll_replica()
                     .withf(move |f: &SignedFailureNotification| *f == signed_failure)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
 
             // Watcher agent setup
@@ -1298,11 +1278,7 @@ mod test {
                     .expect__unenroll_replica()
                     .withf(move |f: &SignedFailureNotification| *f == signed_failure)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
             {
                 // connection_manager_2.unenroll_replica called once
@@ -1311,11 +1287,7 @@ mod test {
                     .expect__unenroll_replica()
                     .withf(move |f: &SignedFailureNotification| *f == signed_failure)
                     .times(1)
-                    .return_once(move |_| {
-                        Ok(TxOutcome {
-                            txid: H256::default(),
-                        })
-                    });
+                    .return_once(move |_| Ok(TxOutcome::new(H256::default())));
             }
 
             // Watcher agent setup
@@ -24,6 +24,9 @@ pub enum EthereumError {
     /// Transaction was not executed successfully
     #[error("Transaction was not executed successfully {0:?}")]
     TxNotExecuted(H256),
+    /// Dispatch tx receipt has no matching `Dispatch` event
+    #[error("No matching Dispatch event in transaction {0:?}")]
+    MissingDispatchEvent(H256),
     /// Any other error
     #[error("{0}")]
     CustomError(#[from] Box<dyn StdError + Send + Sync>),
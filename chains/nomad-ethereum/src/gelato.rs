@@ -1,4 +1,4 @@
-use crate::EthereumSigners;
+use crate::{utils, EthereumSigners};
 use ethers::{
     prelude::{Address, Bytes, H256, U64},
     providers::Middleware,
@@ -9,7 +9,7 @@ use gelato_sdk::{
     rpc::{CheckOrDate, RelayResponse, TaskState},
     FeeToken, ForwardRequestBuilder, GelatoClient,
 };
-use nomad_core::TxOutcome;
+use nomad_core::{TxOutcome, TxSubmitterKind};
 use std::{error::Error as StdError, sync::Arc};
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
@@ -36,6 +36,14 @@ pub enum GelatoError {
         /// Status
         check_info: Option<CheckOrDate>,
     },
+    /// Relayed tx reverted
+    #[error("Gelato relayed tx reverted. Id: {task_id}. Txid: {txid:?}.")]
+    RevertedTxError {
+        /// Task id
+        task_id: H256,
+        /// Txid
+        txid: H256,
+    },
     /// Custom error
     #[error("{0}")]
     CustomError(#[from] Box<dyn StdError + Send + Sync>),
@@ -139,10 +147,11 @@ where
             .await
     }
 
-    /// Poll task id and return tx hash of transaction if successful, error if
-    /// otherwise.
+    /// Poll task id and return the outcome of the relayed transaction, read
+    /// from its receipt, if successful. Error if otherwise.
     pub fn poll_task_id(&self, task_id: H256) -> JoinHandle<Result<TxOutcome, GelatoError>> {
         let gelato = self.gelato();
+        let eth_client = self.eth_client.clone();
 
         tokio::spawn(async move {
             loop {
@@ -164,8 +173,25 @@ where
                     );
 
                     let txid = execution.transaction_hash;
-
-                    return Ok(TxOutcome { txid });
+                    let receipt = eth_client
+                        .get_transaction_receipt(txid)
+                        .await
+                        .map_err(|e| GelatoError::CustomError(e.into()))?;
+
+                    // The provider may not have seen the tx yet
+                    if let Some(receipt) = receipt {
+                        let mut outcome = utils::transaction_receipt_to_tx_outcome(
+                            &receipt,
+                            Some(TxSubmitterKind::Gelato),
+                        );
+                        // The receipt fee is the relayer's gas cost. The
+                        // sponsor is charged in the fee token instead
+                        outcome.fee = None;
+                        if !outcome.is_success() {
+                            return Err(GelatoError::RevertedTxError { task_id, txid });
+                        }
+                        return Ok(outcome);
+                    }
                 }
 
                 if status.task_state == TaskState::CheckPending {
@@ -4,19 +4,25 @@
 use async_trait::async_trait;
 use color_eyre::Result;
 use ethers::{
+    abi::RawLog,
+    contract::EthEvent,
     core::types::{Signature, H256, U256},
     providers::Middleware,
 };
 use futures_util::future::join_all;
 use nomad_core::{
-    Common, CommonIndexer, ContractLocator, DoubleUpdate, Home, HomeIndexer, Message,
-    RawCommittedMessage, SignedUpdate, SignedUpdateWithMeta, State, TxOutcome, Update, UpdateMeta,
+    CommittedMessage, Common, CommonIndexer, ContractLocator, DoubleUpdate, Home, HomeIndexer,
+    Message, MessageMeta, RawCommittedMessage, RawCommittedMessageWithMeta, SignatureScheme,
+    SignedUpdate, SignedUpdateWithMeta, State, TxOutcome, Update, UpdateMeta,
 };
 use nomad_xyz_configuration::HomeGasLimits;
 use std::{convert::TryFrom, sync::Arc};
 use tracing::instrument;
 
-use crate::{bindings::home::Home as EthereumHomeInternal, utils, EthereumError, TxSubmitter};
+use crate::{
+    bindings::home::{DispatchFilter, Home as EthereumHomeInternal},
+    utils, EthereumError, TxSubmitter,
+};
 
 impl<M> std::fmt::Display for EthereumHomeInternal<M>
 where
@@ -129,7 +135,11 @@ where
                     .map(|b| b.timestamp.as_u64());
 
                 SignedUpdateWithMeta {
-                    signed_update: SignedUpdate { update, signature },
+                    signed_update: SignedUpdate {
+                        update,
+                        signature,
+                        scheme: SignatureScheme::Secp256k1,
+                    },
                     metadata: UpdateMeta {
                         block_number,
                         timestamp,
@@ -152,25 +162,47 @@ where
         &self,
         from: u32,
         to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error> {
         let mut events = self
             .contract
             .dispatch_filter()
             .from_block(from)
             .to_block(to)
-            .query()
+            .query_with_meta()
             .await?;
 
-        events.sort_by(|a, b| a.leaf_index.cmp(&b.leaf_index));
+        events.sort_by(|a, b| a.0.leaf_index.cmp(&b.0.leaf_index));
 
-        Ok(events
-            .into_iter()
-            .map(|f| RawCommittedMessage {
-                leaf_index: f.leaf_index.as_u32(),
-                committed_root: f.committed_root.into(),
-                message: f.message.to_vec(),
+        let message_futs: Vec<_> = events
+            .iter()
+            .map(|event| async {
+                let block_number = event.1.block_number.as_u64();
+                let timestamp = self
+                    .provider
+                    .get_block(block_number)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|b| b.timestamp.as_u64());
+
+                RawCommittedMessageWithMeta {
+                    raw_message: RawCommittedMessage {
+                        leaf_index: event.0.leaf_index.as_u32(),
+                        committed_root: event.0.committed_root.into(),
+                        message: event.0.message.to_vec(),
+                    },
+                    metadata: MessageMeta {
+                        block_number,
+                        block_hash: event.1.block_hash,
+                        tx_hash: Some(event.1.transaction_hash),
+                        log_index: event.1.log_index.as_u64(),
+                        timestamp,
+                    },
+                }
             })
-            .collect())
+            .collect();
+
+        Ok(join_all(message_futs).await)
     }
 }
 
@@ -259,7 +291,7 @@ where
             .get_transaction_receipt(txid)
             .await
             .map_err(|e| EthereumError::MiddlewareError(e.into()))?
-            .map(utils::try_transaction_receipt_to_tx_outcome)
+            .map(|receipt| utils::try_transaction_receipt_to_tx_outcome(receipt, None))
             .transpose()
     }
 
@@ -355,6 +387,43 @@ where
             .await
     }
 
+    #[tracing::instrument(err, skip(self))]
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, <Self as Common>::Error> {
+        let txid = self.dispatch(message).await?.txid;
+        let receipt = self
+            .contract
+            .client()
+            .get_transaction_receipt(txid)
+            .await
+            .map_err(|e| EthereumError::MiddlewareError(e.into()))?
+            .ok_or(EthereumError::DroppedError(txid))?;
+
+        let committed = receipt
+            .logs
+            .into_iter()
+            .filter(|log| log.address == self.contract.address())
+            .filter_map(|log| DispatchFilter::decode_log(&RawLog::from(log)).ok())
+            .map(|event| RawCommittedMessage {
+                leaf_index: event.leaf_index.as_u32(),
+                committed_root: event.committed_root.into(),
+                message: event.message.to_vec(),
+            })
+            .filter_map(|raw| CommittedMessage::try_from(raw).ok())
+            .find(|committed| committed.message.is_dispatch_of(message))
+            .ok_or(EthereumError::MissingDispatchEvent(txid))?;
+
+        tracing::info!(
+            txid = ?txid,
+            leaf_index = committed.leaf_index,
+            nonce = committed.message.nonce,
+            "Dispatched message committed."
+        );
+        Ok(committed)
+    }
+
     async fn queue_length(&self) -> Result<U256, <Self as Common>::Error> {
         Ok(self.contract.queue_length().call().await?)
     }
@@ -51,10 +51,15 @@ macro_rules! report_tx {
 
         tracing::info!(
             tx_hash = ?tx_hash,
+            block_number = ?result.block_number,
+            gas_used = ?result.gas_used,
             "Confirmed transaction",
         );
 
-        crate::utils::try_transaction_receipt_to_tx_outcome(result)
+        crate::utils::try_transaction_receipt_to_tx_outcome(
+            result,
+            Some(nomad_core::TxSubmitterKind::Local),
+        )
     }};
 }
 
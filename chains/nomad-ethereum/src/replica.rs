@@ -7,8 +7,8 @@ use ethers::core::types::{Signature, H256, U256};
 use futures_util::future::join_all;
 use nomad_core::{
     accumulator::NomadProof, Common, CommonIndexer, ContractLocator, DoubleUpdate, Encode,
-    MessageStatus, NomadMessage, Replica, SignedUpdate, SignedUpdateWithMeta, State, TxOutcome,
-    Update, UpdateMeta,
+    MessageStatus, NomadMessage, Replica, SignatureScheme, SignedUpdate, SignedUpdateWithMeta,
+    State, TxOutcome, Update, UpdateMeta,
 };
 use nomad_xyz_configuration::ReplicaGasLimits;
 use std::{convert::TryFrom, sync::Arc};
@@ -119,7 +119,11 @@ where
                     .map(|b| b.timestamp.as_u64());
 
                 SignedUpdateWithMeta {
-                    signed_update: SignedUpdate { update, signature },
+                    signed_update: SignedUpdate {
+                        update,
+                        signature,
+                        scheme: SignatureScheme::Secp256k1,
+                    },
                     metadata: UpdateMeta {
                         block_number,
                         timestamp,
@@ -217,7 +221,7 @@ where
             .get_transaction_receipt(txid)
             .await
             .map_err(|e| EthereumError::MiddlewareError(e.into()))?
-            .map(utils::try_transaction_receipt_to_tx_outcome)
+            .map(|receipt| utils::try_transaction_receipt_to_tx_outcome(receipt, None))
             .transpose()
     }
 
@@ -262,9 +266,7 @@ where
     #[tracing::instrument(err)]
     async fn double_update(&self, _: &DoubleUpdate) -> Result<TxOutcome, Self::Error> {
         tracing::warn!("double-update submission has been deprecated");
-        Ok(TxOutcome {
-            txid: Default::default(),
-        })
+        Ok(TxOutcome::new(Default::default()))
     }
 }
 
@@ -351,3 +353,25 @@ where
         Ok(self.contract.acceptable_root(root.into()).call().await?)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ethers::abi::AbiEncode;
+    use nomad_core::accumulator::{Merkle, NomadTree};
+
+    use super::*;
+    use crate::bindings::replica::ProveCall;
+
+    #[test]
+    fn prove_calldata_matches_bindings() {
+        let tree: NomadTree = (0..7).map(H256::from_low_u64_be).collect();
+        let proof = tree.prove(5).unwrap();
+
+        let call = ProveCall {
+            leaf: proof.leaf.into(),
+            proof: proof.path.map(Into::into),
+            index: proof.index.into(),
+        };
+        assert_eq!(call.encode(), proof.to_prove_calldata());
+    }
+}
@@ -63,6 +63,7 @@ impl FromSignerConf for EthereumSigners {
                 let signer = AwsSigner::new(kms_client, id, 0).await?;
                 Ok(Self::Aws(signer))
             }
+            SignerConf::Substrate { .. } => bail!("Substrate signer"),
             SignerConf::Node => bail!("Node signer"),
         }
     }
@@ -1,18 +1,45 @@
 use ethers::prelude::TransactionReceipt;
-use nomad_core::TxOutcome;
+use nomad_core::{TxOutcome, TxStatus, TxSubmitterKind};
 
 use crate::EthereumError;
 
+/// Convert ethers `TransactionReceipt` into `TxOutcome`, recording inclusion
+/// and cost data. The fee is `gas_used * effective_gas_price`, and is `None`
+/// if the provider does not return the effective gas price.
+pub fn transaction_receipt_to_tx_outcome(
+    receipt: &TransactionReceipt,
+    submitter: Option<TxSubmitterKind>,
+) -> TxOutcome {
+    let status = if receipt.status.map_or(false, |status| status.low_u32() == 1) {
+        TxStatus::Success
+    } else {
+        TxStatus::Reverted
+    };
+
+    TxOutcome {
+        txid: receipt.transaction_hash,
+        block_number: receipt.block_number.map(|number| number.as_u64()),
+        block_hash: receipt.block_hash,
+        status,
+        gas_used: receipt.gas_used,
+        fee: receipt
+            .gas_used
+            .zip(receipt.effective_gas_price)
+            .map(|(gas_used, price)| gas_used * price),
+        submitter,
+    }
+}
+
 /// Try to convert ethers `TransactionReceipt` into `TxOutcome`. We use this
 /// function instead of `From<TransactionReceipt> for TxOutcome` because
-/// TxOutcome belongs to `nomad-core`.
+/// TxOutcome belongs to `nomad-core`. Reverted transactions are errors.
 pub fn try_transaction_receipt_to_tx_outcome(
     receipt: TransactionReceipt,
+    submitter: Option<TxSubmitterKind>,
 ) -> Result<TxOutcome, EthereumError> {
-    if receipt.status.unwrap().low_u32() == 1 {
-        Ok(TxOutcome {
-            txid: receipt.transaction_hash,
-        })
+    let outcome = transaction_receipt_to_tx_outcome(&receipt, submitter);
+    if outcome.is_success() {
+        Ok(outcome)
     } else {
         Err(EthereumError::TxNotExecuted(receipt.transaction_hash))
     }
@@ -20,7 +47,7 @@ pub fn try_transaction_receipt_to_tx_outcome(
 
 #[cfg(test)]
 mod test {
-    use ethers::prelude::{TransactionReceipt, U64};
+    use ethers::prelude::{TransactionReceipt, H256, U256, U64};
 
     use super::*;
 
@@ -31,7 +58,7 @@ mod test {
             ..Default::default()
         };
         let tx_outcome: Result<TxOutcome, EthereumError> =
-            try_transaction_receipt_to_tx_outcome(receipt);
+            try_transaction_receipt_to_tx_outcome(receipt, None);
         assert!(
             tx_outcome.is_err(),
             "Turning failed transaction receipt into errored tx outcome not succeeded"
@@ -39,13 +66,33 @@ mod test {
 
         let receipt = TransactionReceipt {
             status: Some(U64::from(1)),
+            block_number: Some(U64::from(100)),
+            block_hash: Some(H256::repeat_byte(1)),
+            gas_used: Some(U256::from(50_000)),
+            effective_gas_price: Some(U256::from(3)),
             ..Default::default()
         };
         let tx_outcome: Result<TxOutcome, EthereumError> =
-            try_transaction_receipt_to_tx_outcome(receipt);
+            try_transaction_receipt_to_tx_outcome(receipt, Some(TxSubmitterKind::Local));
         assert!(
             tx_outcome.is_ok(),
             "Turning succeeded transaction receipt into successful tx outcome not succeeded"
         );
+
+        let tx_outcome = tx_outcome.unwrap();
+        assert_eq!(tx_outcome.block_number, Some(100));
+        assert_eq!(tx_outcome.block_hash, Some(H256::repeat_byte(1)));
+        assert_eq!(tx_outcome.gas_used, Some(U256::from(50_000)));
+        assert_eq!(tx_outcome.fee, Some(U256::from(150_000)));
+        assert_eq!(tx_outcome.submitter, Some(TxSubmitterKind::Local));
+
+        let receipt = TransactionReceipt {
+            status: Some(U64::from(1)),
+            gas_used: Some(U256::from(50_000)),
+            ..Default::default()
+        };
+        let tx_outcome = transaction_receipt_to_tx_outcome(&receipt, None);
+        assert!(tx_outcome.is_success());
+        assert_eq!(tx_outcome.fee, None);
     }
 }
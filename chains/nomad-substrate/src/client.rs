@@ -1,8 +1,11 @@
 use crate::configs::avail::avail::nomad_home as home;
 use crate::SubstrateError;
 use color_eyre::Result;
-use ethers_core::types::Signature;
-use nomad_core::{RawCommittedMessage, SignedUpdate, SignedUpdateWithMeta, Update, UpdateMeta};
+use ethers_core::types::{Signature, H256};
+use nomad_core::{
+    MessageMeta, RawCommittedMessage, RawCommittedMessageWithMeta, SignatureScheme, SignedUpdate,
+    SignedUpdateWithMeta, Update, UpdateMeta,
+};
 use std::convert::TryInto;
 use subxt::ext::sp_runtime::traits::Header;
 use subxt::{
@@ -43,6 +46,22 @@ where
             .map_err(|_| SubstrateError::CustomError("Couldn't convert block number to u32".into()))
     }
 
+    /// Get the number of the block with hash `block_hash`, if known
+    pub async fn get_block_number_by_hash(
+        &self,
+        block_hash: T::Hash,
+    ) -> Result<Option<u32>, SubstrateError> {
+        let header = match self.rpc().header(Some(block_hash)).await? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let u32_header = (*header.number()).try_into();
+
+        u32_header
+            .map(Some)
+            .map_err(|_| SubstrateError::CustomError("Couldn't convert block number to u32".into()))
+    }
+
     /// Fetch value from storage with built-in timelag
     pub async fn storage_fetch(
         &self,
@@ -101,6 +120,8 @@ where
                             new_root: ev.new_root,
                         },
                         signature,
+                        // The home pallet only accepts secp256k1 signatures
+                        scheme: SignatureScheme::Secp256k1,
                     },
                     metadata: UpdateMeta {
                         block_number: block_number as u64,
@@ -111,11 +132,12 @@ where
             .collect())
     }
 
-    /// Fetch ordered signed updates from the specific `block_number`
+    /// Fetch ordered messages and their metadata from the specific
+    /// `block_number`
     pub async fn fetch_sorted_messages_for_block(
         &self,
         block_number: u32,
-    ) -> Result<Vec<RawCommittedMessage>, SubstrateError> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, SubstrateError> {
         // Get hash for block number
         let hash = self
             .rpc()
@@ -123,26 +145,37 @@ where
             .await?
             .unwrap();
 
-        // Get dispatch events from block
-        let dispatch_events_res: Result<Vec<_>, _> = self
-            .events()
-            .at(Some(hash))
-            .await?
-            .find::<home::events::Dispatch>() // TODO: remove dependency on avail metadata
-            .into_iter()
-            .collect();
-
-        let dispatch_events = dispatch_events_res?;
+        // Get dispatch events and their indices from block
+        let events = self.events().at(Some(hash)).await?;
+        let mut dispatch_events = vec![];
+        for event in events.iter() {
+            let event = event?;
+            // TODO: remove dependency on avail metadata
+            if let Some(dispatch) = event.as_event::<home::events::Dispatch>()? {
+                dispatch_events.push((event.index(), dispatch));
+            }
+        }
 
         // TODO: sort events
 
-        // Map dispatches into raw committed messages
+        // Map dispatches into raw committed messages with meta. Extrinsic
+        // hashes and timestamps are not exposed alongside events
+        let block_hash = H256::from_slice(hash.as_ref());
         Ok(dispatch_events
             .into_iter()
-            .map(|ev| RawCommittedMessage {
-                leaf_index: ev.leaf_index,
-                committed_root: ev.committed_root,
-                message: ev.message,
+            .map(|(index, ev)| RawCommittedMessageWithMeta {
+                raw_message: RawCommittedMessage {
+                    leaf_index: ev.leaf_index,
+                    committed_root: ev.committed_root,
+                    message: ev.message,
+                },
+                metadata: MessageMeta {
+                    block_number: block_number as u64,
+                    block_hash,
+                    tx_hash: None,
+                    log_index: index as u64,
+                    timestamp: None,
+                },
             })
             .collect())
     }
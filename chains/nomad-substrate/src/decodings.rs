@@ -83,3 +83,20 @@ impl From<NomadLightMerkleWrapper> for accumulator::NomadLightMerkle {
         accumulator::NomadLightMerkle::new(branch, wrapper.count)
     }
 }
+
+/// `TransactionPayment::TransactionFeePaid`. Only emitted by runtimes on
+/// polkadot-v0.9.23 or later, so absent from the bundled metadata
+#[derive(Debug, Clone, codec::Decode)]
+pub(crate) struct TransactionFeePaid {
+    /// Account charged
+    pub who: subxt::ext::sp_runtime::AccountId32,
+    /// Fee charged, including the tip
+    pub actual_fee: u128,
+    /// Tip
+    pub tip: u128,
+}
+
+impl subxt::events::StaticEvent for TransactionFeePaid {
+    const PALLET: &'static str = "TransactionPayment";
+    const EVENT: &'static str = "TransactionFeePaid";
+}
@@ -8,6 +8,9 @@ pub enum SubstrateError {
     /// A transaction was not executed successfully
     #[error("Transaction was not executed successfully {0:?}")]
     TxNotExecuted(H256),
+    /// Dispatch tx has no matching `Dispatch` event
+    #[error("No matching Dispatch event in transaction {0:?}")]
+    MissingDispatchEvent(H256),
     /// Substrate provider error
     #[error("{0}")]
     ProviderError(#[from] SubxtError),
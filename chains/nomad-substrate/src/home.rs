@@ -1,3 +1,4 @@
+use crate::configs::avail::avail::nomad_home as home;
 use crate::decodings::{NomadBase, NomadLightMerkleWrapper, NomadState};
 use crate::{report_tx, utils, NomadOnlineClient, SubstrateError, SubstrateSigner};
 use async_trait::async_trait;
@@ -6,8 +7,9 @@ use ethers_core::types::{H160, H256, U256};
 use futures::{stream::FuturesOrdered, StreamExt};
 use nomad_core::{
     accumulator::{Merkle, NomadLightMerkle},
-    Common, CommonIndexer, DoubleUpdate, Home, HomeIndexer, Message, RawCommittedMessage,
-    SignedUpdate, SignedUpdateWithMeta, State, TxOutcome, Update,
+    CommittedMessage, Common, CommonIndexer, DoubleUpdate, Home, HomeIndexer, Message,
+    RawCommittedMessage, RawCommittedMessageWithMeta, SignedUpdate, SignedUpdateWithMeta, State,
+    TxOutcome, Update,
 };
 use std::{convert::TryInto, sync::Arc};
 use subxt::ext::scale_value::{self, Primitive, Value};
@@ -106,14 +108,14 @@ where
         &self,
         from: u32,
         to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error> {
         let mut futs = FuturesOrdered::new();
         for block_number in from..to {
             futs.push(self.0.fetch_sorted_messages_for_block(block_number))
         }
 
-        // Flatten all Future<Output = Result<Vec<RawCommittedMessage>>> into
-        // single Vec<RawCommittedMessage>
+        // Flatten all Future<Output = Result<Vec<RawCommittedMessageWithMeta>>>
+        // into single Vec<RawCommittedMessageWithMeta>
         Ok(futs
             .collect::<Vec<_>>()
             .await
@@ -303,24 +305,45 @@ where
 
     #[tracing::instrument(err, skip(self))]
     async fn dispatch(&self, message: &Message) -> Result<TxOutcome, <Self as Common>::Error> {
-        let Message {
-            destination,
-            recipient,
-            body,
-        } = message;
+        let tx_payload = dispatch_payload(message);
 
-        let destination_value = Value::u128(*destination as u128);
-        let recipient_value = Value::primitive(Primitive::U256((*recipient).into()));
-        let body_value = Value::from_bytes(body);
+        info!(message = ?message, "Dispatching message to chain.");
+        report_tx!(DISPATCH_CALL_NAME, self.api, self.signer, tx_payload)
+    }
 
-        let tx_payload = subxt::dynamic::tx(
-            HOME_PALLET_NAME,
-            DISPATCH_CALL_NAME,
-            vec![destination_value, recipient_value, body_value],
-        );
+    #[tracing::instrument(err, skip(self))]
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, <Self as Common>::Error> {
+        let tx_payload = dispatch_payload(message);
 
         info!(message = ?message, "Dispatching message to chain.");
-        report_tx!(DISPATCH_CALL_NAME, self.api, self.signer, tx_payload)
+        let (outcome, events) =
+            report_tx!(@events DISPATCH_CALL_NAME, self.api, self.signer, tx_payload);
+
+        let dispatch_events: Vec<_> = events
+            .find::<home::events::Dispatch>()
+            .collect::<Result<_, _>>()?;
+
+        let committed = dispatch_events
+            .into_iter()
+            .map(|ev| RawCommittedMessage {
+                leaf_index: ev.leaf_index,
+                committed_root: ev.committed_root,
+                message: ev.message,
+            })
+            .filter_map(|raw| CommittedMessage::try_from(raw).ok())
+            .find(|committed| committed.message.is_dispatch_of(message))
+            .ok_or(SubstrateError::MissingDispatchEvent(outcome.txid))?;
+
+        info!(
+            txid = ?outcome.txid,
+            leaf_index = committed.leaf_index,
+            nonce = committed.message.nonce,
+            "Dispatched message committed."
+        );
+        Ok(committed)
     }
 
     async fn queue_length(&self) -> Result<U256, <Self as Common>::Error> {
@@ -375,3 +398,22 @@ where
         })
     }
 }
+
+/// Format a message into a `dispatch` call
+fn dispatch_payload(message: &Message) -> impl subxt::tx::TxPayload {
+    let Message {
+        destination,
+        recipient,
+        body,
+    } = message;
+
+    let destination_value = Value::u128(*destination as u128);
+    let recipient_value = Value::primitive(Primitive::U256((*recipient).into()));
+    let body_value = Value::from_bytes(body);
+
+    subxt::dynamic::tx(
+        HOME_PALLET_NAME,
+        DISPATCH_CALL_NAME,
+        vec![destination_value, recipient_value, body_value],
+    )
+}
@@ -1,7 +1,13 @@
-/// Dispatches an extrinsic, waits for inclusion, and logs details
+/// Dispatches an extrinsic, waits for inclusion, and logs details. The
+/// `@events` form also returns the events of the successful tx.
 #[macro_export]
 macro_rules! report_tx {
     ($method:expr, $client:expr, $signer:expr, $tx:expr) => {{
+        let (outcome, _) = report_tx!(@events $method, $client, $signer, $tx);
+        Ok(outcome)
+    }};
+
+    (@events $method:expr, $client:expr, $signer:expr, $tx:expr) => {{
         let pending_tx = $client
             .tx()
             .sign_and_submit_then_watch_default(&$tx, $signer.as_ref())
@@ -36,8 +42,27 @@ macro_rules! report_tx {
             $method,
         );
 
-        Ok(TxOutcome { txid: successful_tx.extrinsic_hash().into() })
-    }}
+        // Inclusion data is best-effort, as the tx has already succeeded.
+        // Runtimes that do not emit `TransactionFeePaid` report no fee
+        let (gas_used, fee) = utils::tx_weight_and_fee(&successful_tx);
+        let block_number = $client
+            .get_block_number_by_hash(successful_tx.block_hash())
+            .await
+            .ok()
+            .flatten();
+
+        let outcome = TxOutcome {
+            txid: successful_tx.extrinsic_hash().into(),
+            block_number: block_number.map(Into::into),
+            block_hash: Some(successful_tx.block_hash().into()),
+            status: nomad_core::TxStatus::Success,
+            gas_used,
+            fee,
+            submitter: Some(nomad_core::TxSubmitterKind::Local),
+        };
+
+        (outcome, successful_tx)
+    }};
 }
 
 /// Generate function that creates boxed non_signing object (i.e. an indexer)
@@ -56,6 +56,7 @@ where
                 Ok(Self::Local(pair_signer))
             }
             SignerConf::Aws { .. } => bail!("No AWS signer support"),
+            SignerConf::Substrate { .. } => bail!("Attestation keys do not sign transactions"),
             SignerConf::Node => bail!("No node signer support"),
         }
     }
@@ -1,5 +1,7 @@
+use crate::configs::avail::avail::system::events::ExtrinsicSuccess;
+use crate::decodings::TransactionFeePaid;
 use crate::SubstrateError;
-use ethers_core::types::H256;
+use ethers_core::types::{H256, U256};
 use nomad_core::SignedUpdate;
 use subxt::{
     client::OnlineClientT,
@@ -29,9 +31,28 @@ where
     })
 }
 
+/// Weight and fee of a successful tx, read from its `System::ExtrinsicSuccess`
+/// and `TransactionPayment::TransactionFeePaid` events. Either is `None` if
+/// the runtime does not emit the event or it does not decode
+pub fn tx_weight_and_fee<T: Config>(events: &TxEvents<T>) -> (Option<U256>, Option<U256>) {
+    let weight = events
+        .find_first::<ExtrinsicSuccess>()
+        .ok()
+        .flatten()
+        .map(|success| success.0.weight.into());
+    let fee = events
+        .find_first::<TransactionFeePaid>()
+        .ok()
+        .flatten()
+        .map(|paid| paid.actual_fee.into());
+    (weight, fee)
+}
+
 /// Format signed update into scale value format
 pub fn format_signed_update_value(signed_update: &SignedUpdate) -> Value {
-    let SignedUpdate { update, signature } = signed_update;
+    let SignedUpdate {
+        update, signature, ..
+    } = signed_update;
 
     let r_bytes = signature.r.0;
     let s_bytes = signature.s.0;
@@ -1,5 +1,7 @@
 //! Agent configuration types
 
+use nomad_types::{AddressFormat, NomadIdentifier, SS58_GENERIC_PREFIX};
+
 /// Rpc Styles
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -21,6 +23,22 @@ impl std::fmt::Display for RpcStyles {
     }
 }
 
+impl RpcStyles {
+    /// The format identifiers on chains of this style are displayed in.
+    /// Substrate chains use their `ss58_prefix`, or the generic prefix
+    pub fn address_format(&self, ss58_prefix: Option<u16>) -> AddressFormat {
+        match self {
+            RpcStyles::Ethereum => AddressFormat::Ethereum,
+            RpcStyles::Substrate => AddressFormat::Ss58(ss58_prefix.unwrap_or(SS58_GENERIC_PREFIX)),
+        }
+    }
+
+    /// Display `id` as an address on a chain of this style
+    pub fn display_identifier(&self, id: &NomadIdentifier, ss58_prefix: Option<u16>) -> String {
+        id.display_as(self.address_format(ss58_prefix))
+    }
+}
+
 impl Default for RpcStyles {
     fn default() -> Self {
         RpcStyles::Ethereum
@@ -94,6 +112,7 @@ impl Default for LogConfig {
 
 #[cfg(test)]
 mod test {
+    use nomad_types::{NomadIdentifier, SS58_GENERIC_PREFIX};
     use serde_json::json;
 
     use super::RpcStyles;
@@ -105,4 +124,24 @@ mod test {
         let val = json! { "ethereum" };
         assert_eq!(val, serialized);
     }
+
+    #[test]
+    fn it_displays_identifiers_with_the_network_prefix() {
+        let alice_ss58 = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let (alice, _) = NomadIdentifier::from_ss58(alice_ss58).unwrap();
+
+        assert_eq!(
+            RpcStyles::Substrate.display_identifier(&alice, None),
+            alice.to_ss58(SS58_GENERIC_PREFIX)
+        );
+        assert_eq!(
+            RpcStyles::Substrate.display_identifier(&alice, Some(0)),
+            alice.to_ss58(0)
+        );
+        assert_ne!(alice.to_ss58(0), alice_ss58);
+        assert_eq!(
+            RpcStyles::Ethereum.display_identifier(&alice, Some(0)),
+            format!("0x{}", alice)
+        );
+    }
 }
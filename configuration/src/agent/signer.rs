@@ -4,6 +4,37 @@ use std::str::FromStr;
 use crate::full_prefix;
 use nomad_types::HexString;
 
+/// Signature scheme of a local Substrate key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubstrateKeyScheme {
+    /// Schnorrkel
+    Sr25519,
+    /// Ed25519
+    Ed25519,
+}
+
+impl Display for SubstrateKeyScheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubstrateKeyScheme::Sr25519 => write!(f, "sr25519"),
+            SubstrateKeyScheme::Ed25519 => write!(f, "ed25519"),
+        }
+    }
+}
+
+impl FromStr for SubstrateKeyScheme {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sr25519" => Ok(SubstrateKeyScheme::Sr25519),
+            "ed25519" => Ok(SubstrateKeyScheme::Ed25519),
+            _ => eyre::bail!("Unknown substrate key scheme: {}", s),
+        }
+    }
+}
+
 /// Ethereum signer configurations.
 ///
 /// This config item specifies valid Ethereum signers.
@@ -16,7 +47,10 @@ use nomad_types::HexString;
 ///    key. If this configuration is used, the AWS region and credentials must
 ///    be supplied when running the program. Typically these are inserted by
 ///    env var, aws config, or instance roles.
-/// 3. Anything else is treated as an instruction to request the RPC node sign
+/// 3. Deserialize the value as an object containing a `scheme` of
+///    `sr25519` or `ed25519` and a 32-byte hex `key`. This is treated as the
+///    secret seed of a local Substrate attestation key.
+/// 4. Anything else is treated as an instruction to request the RPC node sign
 ///    transactions and messages via the `eth_sign` family of RPC requests. If
 ///    this mode is used, the RPC mode must be unlocked, and have a key.
 ///
@@ -28,6 +62,8 @@ use nomad_types::HexString;
 /// "0x1234123412341234123412341234123412341234123412341234123412341234"
 /// // Aws
 /// { "id": "5485edfa-d7c2-11ec-9d64-0242ac120002" }
+/// // Substrate
+/// { "scheme": "sr25519", "key": "0x1234123412341234123412341234123412341234123412341234123412341234" }
 /// // Node signer
 /// null
 /// "asdjf"
@@ -50,6 +86,14 @@ pub enum SignerConf {
         /// See full rusoto documentation [here](https://docs.rs/rusoto_kms/0.47.0/rusoto_kms/struct.GetPublicKeyRequest.html#structfield.key_id)
         id: String,
     },
+    /// A local sr25519 or ed25519 key. Substrate homes may use these for
+    /// attestations
+    Substrate {
+        /// The signature scheme of the key
+        scheme: SubstrateKeyScheme,
+        /// Hex string of the secret seed, with or without 0x prefix
+        key: HexString<64>,
+    },
     /// Assume node will sign on RPC calls
     Node,
 }
@@ -66,6 +110,7 @@ impl Display for SignerConf {
         match self {
             HexKey(_) => write!(f, "SignerConf: HexKey(...)"),
             Aws { id: _ } => write!(f, "SignerConf: Aws {{...}}"),
+            Substrate { scheme, key: _ } => write!(f, "SignerConf: Substrate({}, ...)", scheme),
             Node => write!(f, "SignerConf: Node"),
         }
     }
@@ -108,7 +153,12 @@ impl SignerConf {
         }
 
         if let Ok(signer_key) = std::env::var(&format!("{}_KEY", full_prefix)) {
-            return Some(SignerConf::HexKey(HexString::from_str(&signer_key).ok()?));
+            let key = HexString::from_str(&signer_key).ok()?;
+            if let Ok(scheme) = std::env::var(&format!("{}_SCHEME", full_prefix)) {
+                let scheme = scheme.parse().ok()?;
+                return Some(SignerConf::Substrate { scheme, key });
+            }
+            return Some(SignerConf::HexKey(key));
         }
 
         None
@@ -121,6 +171,7 @@ impl SignerConf {
             SignerConf::Aws { id } => {
                 eyre::ensure!(!id.is_empty(), "ID for {} aws signer key empty!", network);
             }
+            SignerConf::Substrate { .. } => (), // length check implicit in hexkey type
             SignerConf::Node => (),
         };
 
@@ -132,7 +183,7 @@ impl SignerConf {
 mod test {
     use serde_json::{json, Value};
 
-    use super::SignerConf;
+    use super::{SignerConf, SubstrateKeyScheme};
 
     #[test]
     fn it_deserializes_hexkey_signer_confs() {
@@ -156,6 +207,29 @@ mod test {
         assert_eq!(signer_conf, SignerConf::Aws { id: "".to_owned() });
     }
 
+    #[test]
+    fn it_deserializes_substrate_signer_confs() {
+        let k = "0x3232323232323232323232323232323232323232323232323232323232323232";
+        let value = json!({
+            "scheme": "ed25519",
+            "key": k,
+        });
+        let signer_conf: SignerConf = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            signer_conf,
+            SignerConf::Substrate {
+                scheme: SubstrateKeyScheme::Ed25519,
+                key: k.parse().unwrap(),
+            }
+        );
+
+        let value = json!({
+            "scheme": "bls",
+            "key": k,
+        });
+        assert!(serde_json::from_value::<SignerConf>(value).is_err());
+    }
+
     #[test]
     fn it_does_not_display_or_debug_secrets() {
         let value = json! { "0xBADBADBAD0000000000000000000000000000000000000000000000000000000" };
@@ -174,6 +248,22 @@ mod test {
         assert_eq!("SignerConf: Aws {...}", format!("{:?}", signer_conf));
         assert!(!format!("{:?}", signer_conf).contains("BADBADBAD"));
 
+        let value = json!({
+            "scheme": "sr25519",
+            "key": "0xBADBADBAD0000000000000000000000000000000000000000000000000000000",
+        });
+        let signer_conf: SignerConf = serde_json::from_value(value).unwrap();
+
+        assert_eq!(
+            "SignerConf: Substrate(sr25519, ...)",
+            format!("{}", signer_conf)
+        );
+        assert_eq!(
+            "SignerConf: Substrate(sr25519, ...)",
+            format!("{:?}", signer_conf)
+        );
+        assert!(!format!("{:?}", signer_conf).contains("BADBADBAD"));
+
         let value = Value::Null;
         let signer_conf: SignerConf = serde_json::from_value(value).unwrap();
 
@@ -343,6 +343,23 @@ impl NomadConfig {
             .and_then(|contracts| contracts.replica_of(&home_network))
     }
 
+    /// Display `id` as an address on `network`, in the address format of
+    /// its rpc style. Networks without agent config are treated as Ethereum
+    pub fn display_identifier(&self, network: NameOrDomain, id: &NomadIdentifier) -> String {
+        let network = self.resolve_domain(network);
+        let rpc_style = network
+            .as_ref()
+            .and_then(|network| self.agent.get(network))
+            .map(|agent| agent.rpc_style)
+            .unwrap_or_default();
+        let ss58_prefix = network
+            .as_ref()
+            .and_then(|network| self.protocol.networks.get(network))
+            .and_then(|domain| domain.specs.ss58_prefix);
+
+        rpc_style.display_identifier(id, ss58_prefix)
+    }
+
     /// Get a reference to the nomad config's protocol configuration.
     pub fn protocol(&self) -> &NetworkInfo {
         &self.protocol
@@ -431,4 +448,32 @@ mod tests {
             .unwrap();
         println!("{}", yaml);
     }
+
+    #[test]
+    fn it_displays_identifiers_by_network() {
+        let mut config = crate::builtin::get_builtin("test").unwrap().clone();
+        let (alice, _) =
+            NomadIdentifier::from_ss58("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap();
+
+        assert_eq!(
+            config.display_identifier("ethereum".to_owned().into(), &alice),
+            format!("0x{}", alice)
+        );
+        assert_eq!(
+            config.display_identifier("avail".to_owned().into(), &alice),
+            alice.to_ss58(nomad_types::SS58_GENERIC_PREFIX)
+        );
+
+        config
+            .protocol
+            .networks
+            .get_mut("avail")
+            .unwrap()
+            .specs
+            .ss58_prefix = Some(0);
+        assert_eq!(
+            config.display_identifier("avail".to_owned().into(), &alice),
+            alice.to_ss58(0)
+        );
+    }
 }
@@ -2,7 +2,8 @@
 
 use crate::bridge::BridgeConfiguration;
 use nomad_types::{
-    deser_nomad_u32, deser_nomad_u64, deser_nomad_u8, NameOrDomain, NomadIdentifier, NomadLocator,
+    deser_nomad_u32, deser_nomad_u64, deser_nomad_u8, deser_ss58_prefix, NameOrDomain,
+    NomadIdentifier, NomadLocator,
 };
 use std::collections::{HashMap, HashSet};
 
@@ -58,6 +59,14 @@ pub struct NetworkSpecs {
     /// Number of blocks to include in a page while indexing
     #[serde(deserialize_with = "deser_nomad_u32")]
     pub index_page_size: u32,
+    /// SS58 address prefix of a substrate network. The generic prefix if
+    /// unset
+    #[serde(
+        default,
+        deserialize_with = "deser_ss58_prefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ss58_prefix: Option<u16>,
 }
 
 /// Specifier for deploy-time custom bridge tokens
@@ -120,6 +120,7 @@ export interface NetworkSpecs {
   confirmations: number | string;
   blockExplorer: string;
   indexPageSize: number;
+  ss58Prefix?: number;
 }
 
 export interface CustomTokenSpecifier {
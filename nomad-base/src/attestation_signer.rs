@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use color_eyre::Result;
+use ethers::core::types::{Signature, H256};
+use nomad_core::{Attester, FromSignerConf, NomadIdentifier, SignatureScheme, SubstrateAttester};
+use nomad_ethereum::{EthereumSigners, EthereumSignersError};
+use nomad_xyz_configuration::agent::SignerConf;
+
+/// Attestation signer. Ethereum signers attest with secp256k1, and Substrate
+/// homes may use local sr25519 or ed25519 keys.
+#[derive(Debug, Clone)]
+pub enum AttestationSigner {
+    /// secp256k1 signer, local or AWS
+    Ethereum(EthereumSigners),
+    /// Local sr25519 or ed25519 signer
+    Substrate(SubstrateAttester),
+}
+
+impl From<EthereumSigners> for AttestationSigner {
+    fn from(signer: EthereumSigners) -> Self {
+        AttestationSigner::Ethereum(signer)
+    }
+}
+
+impl From<SubstrateAttester> for AttestationSigner {
+    fn from(attester: SubstrateAttester) -> Self {
+        AttestationSigner::Substrate(attester)
+    }
+}
+
+#[async_trait]
+impl FromSignerConf for AttestationSigner {
+    async fn try_from_signer_conf(conf: &SignerConf) -> Result<Self> {
+        match conf {
+            SignerConf::Substrate { .. } => {
+                Ok(SubstrateAttester::try_from_signer_conf(conf).await?.into())
+            }
+            _ => Ok(EthereumSigners::try_from_signer_conf(conf).await?.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl Attester for AttestationSigner {
+    type Error = EthereumSignersError;
+
+    fn scheme(&self) -> SignatureScheme {
+        match self {
+            AttestationSigner::Ethereum(signer) => signer.scheme(),
+            AttestationSigner::Substrate(attester) => attester.scheme(),
+        }
+    }
+
+    fn identifier(&self) -> NomadIdentifier {
+        match self {
+            AttestationSigner::Ethereum(signer) => signer.identifier(),
+            AttestationSigner::Substrate(attester) => attester.identifier(),
+        }
+    }
+
+    async fn sign_attestation(&self, signing_hash: H256) -> Result<Signature, Self::Error> {
+        match self {
+            AttestationSigner::Ethereum(signer) => signer.sign_attestation(signing_hash).await,
+            AttestationSigner::Substrate(attester) => {
+                Ok(attester.sign_attestation(signing_hash).await?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nomad_core::Update;
+    use nomad_xyz_configuration::agent::SubstrateKeyScheme;
+
+    #[tokio::test]
+    async fn it_signs_with_each_signer_conf() {
+        let key: nomad_types::HexString<64> =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+        let confs = [
+            (SignerConf::HexKey(key.clone()), SignatureScheme::Secp256k1),
+            (
+                SignerConf::Substrate {
+                    scheme: SubstrateKeyScheme::Sr25519,
+                    key: key.clone(),
+                },
+                SignatureScheme::Sr25519,
+            ),
+            (
+                SignerConf::Substrate {
+                    scheme: SubstrateKeyScheme::Ed25519,
+                    key,
+                },
+                SignatureScheme::Ed25519,
+            ),
+        ];
+
+        for (conf, scheme) in confs {
+            let signer = AttestationSigner::try_from_signer_conf(&conf)
+                .await
+                .unwrap();
+            assert_eq!(signer.scheme(), scheme);
+
+            let signed = Update {
+                home_domain: 1000,
+                previous_root: H256::repeat_byte(1),
+                new_root: H256::repeat_byte(2),
+            }
+            .sign_with(&signer)
+            .await
+            .unwrap();
+            assert_eq!(signed.scheme, scheme);
+            signed.verify(signer.identifier()).unwrap();
+        }
+    }
+}
@@ -11,7 +11,7 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 mod metrics;
-mod schema;
+pub(crate) mod schema;
 
 pub use metrics::ContractSyncMetrics;
 use schema::{CommonContractSyncDB, HomeContractSyncDB};
@@ -173,8 +173,10 @@ where
                         continue;
                     }
 
-                    // Store updates
-                    db.store_updates_and_meta(&sorted_updates)?;
+                    // Store updates and move forward next height atomically
+                    let mut batch = db.batch();
+                    db.stage_update_latest_block_end(&mut batch, to);
+                    db.write_updates_and_meta(batch, &sorted_updates)?;
 
                     // Report latencies from emit to store if caught up
                     if to == tip {
@@ -204,8 +206,6 @@ where
                     // Report amount of updates stored into db
                     stored_updates.add(sorted_updates.len().try_into()?);
 
-                    // Move forward next height
-                    db.store_update_latest_block_end(to)?;
                     from = to;
                 }
             }
@@ -280,6 +280,19 @@ where
                 info!(from = from, "[Messages]: resuming indexer from {}", from);
 
                 loop {
+                    // Re-index from an earlier height if leaves were
+                    // invalidated (e.g. by ProverSync after a reorg)
+                    if let Some(rewound) = db
+                        .retrieve_message_latest_block_end()
+                        .filter(|height| *height < from)
+                    {
+                        info!(
+                            from = rewound,
+                            "[Messages]: rewinding indexer to {}", rewound
+                        );
+                        from = rewound;
+                    }
+
                     indexed_height.set(from as i64);
 
                     let tip = indexer.get_block_number().await?;
@@ -317,14 +330,14 @@ where
                         continue;
                     }
 
-                    // Store messages
-                    db.store_messages(&sorted_messages)?;
+                    // Store messages and move forward next height atomically
+                    let mut batch = db.batch();
+                    db.stage_message_latest_block_end(&mut batch, to);
+                    db.write_messages(batch, &sorted_messages)?;
 
                     // Report amount of messages stored into db
                     stored_messages.add(sorted_messages.len().try_into()?);
 
-                    // Move forward next height
-                    db.store_message_latest_block_end(to)?;
                     from = to;
                 }
             }
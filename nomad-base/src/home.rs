@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use color_eyre::eyre::Result;
 use ethers::core::types::{H256, U256};
 use nomad_core::{
-    db::DbError, Common, CommonEvents, DoubleUpdate, Home, HomeEvents, Message,
+    db::DbError, CommittedMessage, Common, CommonEvents, DoubleUpdate, Home, HomeEvents, Message,
     RawCommittedMessage, SignedUpdate, State, TxOutcome, Update,
 };
 use nomad_ethereum::EthereumHome;
@@ -73,6 +73,13 @@ impl Home for CachingHome {
         self.home.dispatch(message).await
     }
 
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, ChainCommunicationError> {
+        self.home.dispatch_committed(message).await
+    }
+
     async fn queue_length(&self) -> Result<U256, ChainCommunicationError> {
         self.home.queue_length().await
     }
@@ -327,6 +334,18 @@ impl Home for HomeVariants {
         }
     }
 
+    #[instrument(level = "trace", err)]
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, ChainCommunicationError> {
+        match self {
+            HomeVariants::Ethereum(home) => Ok(home.dispatch_committed(message).await?),
+            HomeVariants::Substrate(home) => Ok(home.dispatch_committed(message).await?),
+            HomeVariants::Mock(mock_home) => Ok(mock_home.dispatch_committed(message).await?),
+        }
+    }
+
     #[instrument(level = "trace", err)]
     async fn queue_length(&self) -> Result<U256, ChainCommunicationError> {
         match self {
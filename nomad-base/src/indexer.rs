@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use color_eyre::Result;
-use nomad_core::{CommonIndexer, HomeIndexer, RawCommittedMessage, SignedUpdateWithMeta};
+use nomad_core::{CommonIndexer, HomeIndexer, RawCommittedMessageWithMeta, SignedUpdateWithMeta};
 use nomad_test::mocks::MockIndexer;
 use std::{ops::Deref, sync::Arc};
 
@@ -144,7 +144,7 @@ impl HomeIndexer for HomeIndexers {
         &self,
         from: u32,
         to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error> {
         self.deref().fetch_sorted_messages(from, to).await
     }
 }
@@ -197,7 +197,7 @@ impl HomeIndexer for HomeIndexerVariants {
         &self,
         from: u32,
         to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error> {
         match self {
             HomeIndexerVariants::Ethereum(indexer) => {
                 Ok(indexer.fetch_sorted_messages(from, to).await?)
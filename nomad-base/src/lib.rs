@@ -27,6 +27,10 @@ pub use macros::*;
 mod nomad_db;
 pub use nomad_db::*;
 
+/// Disk-backed merkle tree
+mod persistent_tree;
+pub use persistent_tree::*;
+
 /// Base errors
 mod error;
 pub use error::*;
@@ -54,8 +58,9 @@ pub use indexer::*;
 mod submitter;
 pub use submitter::*;
 
-/// Re-export signer trait for attestation signer.
-pub use ethers::signers::Signer;
+/// Attestation signer
+mod attestation_signer;
+pub use attestation_signer::*;
 
-/// Attestation signer. Always uses Ethereum-style signing.
-pub type AttestationSigner = nomad_ethereum::EthereumSigners;
+/// Re-export attester trait for attestation signer.
+pub use nomad_core::Attester;
@@ -1,29 +1,34 @@
+use crate::contract_sync::schema::HomeContractSyncDB;
 use color_eyre::Result;
 use ethers::core::types::H256;
-use nomad_core::db::{DbError, TypedDB, DB};
+use nomad_core::db::{DbError, TypedBatch, TypedDB, DB};
 use nomad_core::{
-    accumulator::NomadProof, utils, CommittedMessage, Decode, NomadMessage, RawCommittedMessage,
-    SignedUpdate, SignedUpdateWithMeta, UpdateMeta,
+    accumulator::NomadProof, utils, CommittedMessage, Decode, MessageMeta, NomadMessage,
+    RawCommittedMessage, RawCommittedMessageWithMeta, SignedUpdate, SignedUpdateWithMeta,
+    UpdateMeta,
 };
 use tokio::time::sleep;
 use tracing::{debug, info};
 
 use std::future::Future;
+use std::ops::{Bound, RangeBounds};
 use std::time::Duration;
 
-use nomad_core::db::iterator::PrefixIterator;
-
 const LEAF_IDX: &str = "leaf_index_";
 const LEAF: &str = "leaf_";
 const PREV_ROOT: &str = "update_prev_root_";
 const PROOF: &str = "proof_";
 const MESSAGE: &str = "message_";
+const MESSAGE_META: &str = "message_metadata_";
 const UPDATE: &str = "update_";
 const UPDATE_META: &str = "update_metadata_";
 const LATEST_ROOT: &str = "update_latest_root_";
 const LATEST_LEAF_INDEX: &str = "latest_known_leaf_index_";
 const UPDATER_PRODUCED_UPDATE: &str = "updater_produced_update_";
 const PROVER_LATEST_COMMITTED: &str = "prover_latest_committed_";
+const PROVER_TREE_SIZE: &str = "prover_tree_size_";
+const PROVER_NODE: &str = "prover_node_";
+const PROVER_NODES_WRITTEN: &str = "prover_nodes_written_";
 const PROCESSOR_ATTEMPTED: &str = "processor_attempted_";
 
 /// DB handle for storing data tied to a specific home.
@@ -65,17 +70,39 @@ impl NomadDB {
         Ok(no_updates && no_messages)
     }
 
-    /// Store list of messages
-    pub fn store_messages(&self, messages: &[RawCommittedMessage]) -> Result<()> {
-        for message in messages {
-            self.store_latest_message(message)?;
+    /// Store list of messages and their metadata
+    pub fn store_messages(&self, messages: &[RawCommittedMessageWithMeta]) -> Result<()> {
+        self.write_messages(self.batch(), messages)
+    }
+
+    /// Add list of messages and their metadata to `batch`, and atomically
+    /// commit it. Other writes in `batch` are committed with the messages
+    pub fn write_messages(
+        &self,
+        mut batch: TypedBatch,
+        messages: &[RawCommittedMessageWithMeta],
+    ) -> Result<()> {
+        let mut latest_leaf_index = self.retrieve_latest_leaf_index()?;
+        for message_with_meta in messages {
+            Self::stage_latest_message(
+                &mut batch,
+                &mut latest_leaf_index,
+                &message_with_meta.raw_message,
+            )?;
+            Self::stage_message_metadata(&mut batch, message_with_meta);
+        }
+        self.write(batch)?;
 
-            let committed_message: CommittedMessage = message.clone().try_into()?;
+        for message_with_meta in messages {
+            let committed_message: CommittedMessage =
+                message_with_meta.raw_message.clone().try_into()?;
             info!(
                 leaf_index = &committed_message.leaf_index,
                 origin = &committed_message.message.origin,
                 destination = &committed_message.message.destination,
                 nonce = &committed_message.message.nonce,
+                block_number = message_with_meta.metadata.block_number,
+                tx_hash = ?message_with_meta.metadata.tx_hash,
                 "Stored new message in db.",
             );
         }
@@ -83,6 +110,36 @@ impl NomadDB {
         Ok(())
     }
 
+    /// Store message metadata (by message's leaf)
+    ///
+    /// Keys --> Values:
+    /// - `leaf` --> `message_metadata`
+    pub fn store_message_metadata(
+        &self,
+        message_with_meta: &RawCommittedMessageWithMeta,
+    ) -> Result<(), DbError> {
+        let mut batch = self.batch();
+        Self::stage_message_metadata(&mut batch, message_with_meta);
+        self.write(batch)
+    }
+
+    fn stage_message_metadata(
+        batch: &mut TypedBatch,
+        message_with_meta: &RawCommittedMessageWithMeta,
+    ) {
+        let leaf = message_with_meta.raw_message.leaf();
+        let metadata = message_with_meta.metadata;
+
+        debug!(leaf = ?leaf, metadata = ?metadata, "storing message metadata in DB");
+
+        batch.store_keyed_encodable(MESSAGE_META, &leaf, &metadata)
+    }
+
+    /// Retrieve message metadata (by message's leaf)
+    pub fn retrieve_message_metadata(&self, leaf: H256) -> Result<Option<MessageMeta>, DbError> {
+        self.retrieve_keyed_decodable(MESSAGE_META, &leaf)
+    }
+
     /// Store a raw committed message
     ///
     /// Keys --> Values:
@@ -90,6 +147,15 @@ impl NomadDB {
     /// - `leaf_index` --> `leaf`
     /// - `leaf` --> `message`
     pub fn store_raw_committed_message(&self, message: &RawCommittedMessage) -> Result<()> {
+        let mut batch = self.batch();
+        Self::stage_raw_committed_message(&mut batch, message)?;
+        Ok(self.write(batch)?)
+    }
+
+    fn stage_raw_committed_message(
+        batch: &mut TypedBatch,
+        message: &RawCommittedMessage,
+    ) -> Result<(), DbError> {
         let parsed = NomadMessage::read_from(&mut message.message.clone().as_slice())?;
 
         let destination_and_nonce = parsed.destination_and_nonce();
@@ -104,31 +170,42 @@ impl NomadDB {
             leaf_index = message.leaf_index,
             "storing raw committed message in db"
         );
-        self.store_leaf(message.leaf_index, destination_and_nonce, leaf)?;
-        self.store_keyed_encodable(MESSAGE, &leaf, message)?;
+        Self::stage_leaf(batch, message.leaf_index, destination_and_nonce, leaf);
+        batch.store_keyed_encodable(MESSAGE, &leaf, message);
         Ok(())
     }
 
     /// Store a raw committed message building off of the latest leaf index
     pub fn store_latest_message(&self, message: &RawCommittedMessage) -> Result<()> {
-        // If there is no latest root, or if this update is on the latest root
-        // update latest root
-        match self.retrieve_latest_leaf_index()? {
-            Some(idx) => {
-                if idx == message.leaf_index - 1 {
-                    self.update_latest_leaf_index(message.leaf_index)?;
-                } else {
-                    debug!(
-                        "Attempted to store message not building off latest leaf index. Latest leaf index: {}. Attempted leaf index: {}.",
-                        idx,
-                        message.leaf_index,
-                    )
-                }
+        let mut latest_leaf_index = self.retrieve_latest_leaf_index()?;
+        let mut batch = self.batch();
+        Self::stage_latest_message(&mut batch, &mut latest_leaf_index, message)?;
+        Ok(self.write(batch)?)
+    }
+
+    /// Stage a raw committed message, and the latest leaf index if it builds
+    /// off of `latest_leaf_index`. `latest_leaf_index` tracks the staged
+    /// value, so that later messages in the batch build off of it
+    fn stage_latest_message(
+        batch: &mut TypedBatch,
+        latest_leaf_index: &mut Option<u32>,
+        message: &RawCommittedMessage,
+    ) -> Result<(), DbError> {
+        // If there is no latest leaf index, or if this message is on the
+        // latest leaf index, update latest leaf index
+        match *latest_leaf_index {
+            Some(idx) if idx + 1 != message.leaf_index => debug!(
+                "Attempted to store message not building off latest leaf index. Latest leaf index: {}. Attempted leaf index: {}.",
+                idx,
+                message.leaf_index,
+            ),
+            _ => {
+                batch.store_encodable("", LATEST_LEAF_INDEX, &message.leaf_index);
+                *latest_leaf_index = Some(message.leaf_index);
             }
-            None => self.update_latest_leaf_index(message.leaf_index)?,
         }
 
-        self.store_raw_committed_message(message)
+        Self::stage_raw_committed_message(batch, message)
     }
 
     /// Store the latest known leaf_index
@@ -143,20 +220,15 @@ impl NomadDB {
         self.retrieve_decodable("", LATEST_LEAF_INDEX)
     }
 
-    /// Store the leaf keyed by leaf_index
-    fn store_leaf(
-        &self,
-        leaf_index: u32,
-        destination_and_nonce: u64,
-        leaf: H256,
-    ) -> Result<(), DbError> {
+    /// Stage the leaf keyed by leaf_index
+    fn stage_leaf(batch: &mut TypedBatch, leaf_index: u32, destination_and_nonce: u64, leaf: H256) {
         debug!(
             leaf_index,
             leaf = ?leaf,
             "storing leaf hash keyed by index and dest+nonce"
         );
-        self.store_keyed_encodable(LEAF, &destination_and_nonce, &leaf)?;
-        self.store_keyed_encodable(LEAF, &leaf_index, &leaf)
+        batch.store_keyed_encodable(LEAF, &destination_and_nonce, &leaf);
+        batch.store_keyed_encodable(LEAF_IDX, &leaf_index, &leaf)
     }
 
     /// Retrieve a raw committed message by its leaf hash
@@ -166,7 +238,7 @@ impl NomadDB {
 
     /// Retrieve the leaf hash keyed by leaf index
     pub fn leaf_by_leaf_index(&self, leaf_index: u32) -> Result<Option<H256>, DbError> {
-        self.retrieve_keyed_decodable(LEAF, &leaf_index)
+        self.retrieve_keyed_decodable(LEAF_IDX, &leaf_index)
     }
 
     /// Retrieve the leaf hash keyed by destination and nonce
@@ -200,12 +272,6 @@ impl NomadDB {
         }
     }
 
-    /// Store the latest committed
-    fn store_latest_root(&self, root: H256) -> Result<(), DbError> {
-        debug!(root = ?root, "storing new latest root in DB");
-        self.store_encodable("", LATEST_ROOT, &root)
-    }
-
     /// Retrieve the latest committed
     pub fn retrieve_latest_root(&self) -> Result<Option<H256>, DbError> {
         self.retrieve_decodable("", LATEST_ROOT)
@@ -213,10 +279,29 @@ impl NomadDB {
 
     /// Store list of sorted updates and their metadata
     pub fn store_updates_and_meta(&self, updates: &[SignedUpdateWithMeta]) -> Result<()> {
+        self.write_updates_and_meta(self.batch(), updates)
+    }
+
+    /// Add list of sorted updates and their metadata to `batch`, and
+    /// atomically commit it. Other writes in `batch` are committed with the
+    /// updates
+    pub fn write_updates_and_meta(
+        &self,
+        mut batch: TypedBatch,
+        updates: &[SignedUpdateWithMeta],
+    ) -> Result<()> {
+        let mut latest_root = self.retrieve_latest_root()?;
         for update_with_meta in updates {
-            self.store_latest_update(&update_with_meta.signed_update)?;
-            self.store_update_metadata(update_with_meta)?;
+            Self::stage_latest_update(
+                &mut batch,
+                &mut latest_root,
+                &update_with_meta.signed_update,
+            );
+            Self::stage_update_metadata(&mut batch, update_with_meta);
+        }
+        self.write(batch)?;
 
+        for update_with_meta in updates {
             info!(
                 block_number = update_with_meta.metadata.block_number,
                 timestamp = ?update_with_meta.metadata.timestamp,
@@ -237,12 +322,18 @@ impl NomadDB {
         &self,
         update_with_meta: &SignedUpdateWithMeta,
     ) -> Result<(), DbError> {
+        let mut batch = self.batch();
+        Self::stage_update_metadata(&mut batch, update_with_meta);
+        self.write(batch)
+    }
+
+    fn stage_update_metadata(batch: &mut TypedBatch, update_with_meta: &SignedUpdateWithMeta) {
         let new_root = update_with_meta.signed_update.update.new_root;
         let metadata = update_with_meta.metadata;
 
         debug!(new_root = ?new_root, metadata = ?metadata, "storing update metadata in DB");
 
-        self.store_keyed_encodable(UPDATE_META, &new_root, &metadata)
+        batch.store_keyed_encodable(UPDATE_META, &new_root, &metadata)
     }
 
     /// Retrieve update metadata (by update's new root)
@@ -257,6 +348,20 @@ impl NomadDB {
     /// - `new_root` --> `prev_root`
     /// - `prev_root` --> `update`
     pub fn store_latest_update(&self, update: &SignedUpdate) -> Result<(), DbError> {
+        let mut latest_root = self.retrieve_latest_root()?;
+        let mut batch = self.batch();
+        Self::stage_latest_update(&mut batch, &mut latest_root, update);
+        self.write(batch)
+    }
+
+    /// Stage a signed update, and the latest root if it builds off of
+    /// `latest_root`. `latest_root` tracks the staged value, so that later
+    /// updates in the batch build off of it
+    fn stage_latest_update(
+        batch: &mut TypedBatch,
+        latest_root: &mut Option<H256>,
+        update: &SignedUpdate,
+    ) {
         debug!(
             previous_root = ?update.update.previous_root,
             new_root = ?update.update.new_root,
@@ -265,21 +370,19 @@ impl NomadDB {
 
         // If there is no latest root, or if this update is on the latest root
         // update latest root
-        match self.retrieve_latest_root()? {
-            Some(root) => {
-                if root == update.update.previous_root {
-                    self.store_latest_root(update.update.new_root)?;
-                } else {
-                    debug!(
-                        "Attempted to store update not building off latest root: {:?}",
-                        update
-                    )
-                }
+        match *latest_root {
+            Some(root) if root != update.update.previous_root => debug!(
+                "Attempted to store update not building off latest root: {:?}",
+                update
+            ),
+            _ => {
+                debug!(root = ?update.update.new_root, "storing new latest root in DB");
+                batch.store_encodable("", LATEST_ROOT, &update.update.new_root);
+                *latest_root = Some(update.update.new_root);
             }
-            None => self.store_latest_root(update.update.new_root)?,
         }
 
-        self.store_update(update)
+        Self::stage_update(batch, update)
     }
 
     /// Store an update.
@@ -288,8 +391,14 @@ impl NomadDB {
     /// - `new_root` --> `prev_root`
     /// - `prev_root` --> `update`
     pub fn store_update(&self, update: &SignedUpdate) -> Result<(), DbError> {
-        self.store_keyed_encodable(UPDATE, &update.update.previous_root, update)?;
-        self.store_keyed_encodable(
+        let mut batch = self.batch();
+        Self::stage_update(&mut batch, update);
+        self.write(batch)
+    }
+
+    fn stage_update(batch: &mut TypedBatch, update: &SignedUpdate) {
+        batch.store_keyed_encodable(UPDATE, &update.update.previous_root, update);
+        batch.store_keyed_encodable(
             PREV_ROOT,
             &update.update.new_root,
             &update.update.previous_root,
@@ -314,9 +423,77 @@ impl NomadDB {
         }
     }
 
-    /// Iterate over all leaves
-    pub fn leaf_iterator(&self) -> PrefixIterator<H256> {
-        PrefixIterator::new(self.0.as_ref().prefix_iterator(LEAF_IDX), LEAF_IDX.as_ref())
+    /// Iterate over leaves and their leaf indices in `range`, in order
+    pub fn leaves_by_leaf_index<'a>(
+        &'a self,
+        range: impl RangeBounds<u32> + 'a,
+    ) -> impl Iterator<Item = Result<(u32, H256), DbError>> + 'a {
+        self.leaf_index_range(LEAF_IDX, range)
+    }
+
+    /// Iterate over all leaves, in leaf index order
+    pub fn leaf_iterator(&self) -> impl Iterator<Item = Result<H256, DbError>> + '_ {
+        self.leaves_by_leaf_index(..)
+            .map(|res| res.map(|(_, leaf)| leaf))
+    }
+
+    /// Iterate over messages and their leaf indices in `range`, in order
+    pub fn messages_by_leaf_index<'a>(
+        &'a self,
+        range: impl RangeBounds<u32> + 'a,
+    ) -> impl Iterator<Item = Result<(u32, RawCommittedMessage), DbError>> + 'a {
+        self.leaves_by_leaf_index(range).filter_map(move |res| {
+            let message = res.and_then(|(index, leaf)| {
+                Ok(self.message_by_leaf(leaf)?.map(|message| (index, message)))
+            });
+            message.transpose()
+        })
+    }
+
+    /// Iterate over proofs and their leaf indices in `range`, in order
+    pub fn proofs_by_leaf_index<'a>(
+        &'a self,
+        range: impl RangeBounds<u32> + 'a,
+    ) -> impl Iterator<Item = Result<(u32, NomadProof), DbError>> + 'a {
+        self.leaf_index_range(PROOF, range)
+    }
+
+    /// Iterate over the chain of updates starting at `previous_root`, with
+    /// each update's previous root
+    pub fn updates_from_root(
+        &self,
+        previous_root: H256,
+    ) -> impl Iterator<Item = Result<(H256, SignedUpdate), DbError>> + '_ {
+        let mut next_root = Some(previous_root);
+        std::iter::from_fn(move || {
+            let previous_root = next_root.take()?;
+            match self.update_by_previous_root(previous_root) {
+                Ok(Some(update)) => {
+                    next_root = Some(update.update.new_root);
+                    Some(Ok((previous_root, update)))
+                }
+                Ok(None) => None,
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
+    /// Iterate over values keyed by leaf index under `prefix`, in `range`
+    fn leaf_index_range<'a, V: Decode + 'a>(
+        &'a self,
+        prefix: &str,
+        range: impl RangeBounds<u32> + 'a,
+    ) -> impl Iterator<Item = Result<(u32, V), DbError>> + 'a {
+        let from = match range.start_bound() {
+            Bound::Included(start) => Some(*start),
+            Bound::Excluded(start) => Some(start.saturating_add(1)),
+            Bound::Unbounded => None,
+        };
+        self.keyed_iterator(prefix, from.as_ref())
+            .take_while(move |res| match res {
+                Ok((index, _)) => range.contains(index),
+                Err(_) => true,
+            })
     }
 
     /// Store a proof by its leaf index
@@ -347,6 +524,50 @@ impl NomadDB {
         }
     }
 
+    /// Invalidate the leaves at and after `leaf_index` (e.g. after a reorg),
+    /// and rewind the message indexer to the block of the first invalidated
+    /// leaf, so that the leaves are re-fetched from chain
+    ///
+    /// Keys --> Values:
+    /// - `leaf_index` --> deleted for each invalidated leaf
+    /// - `LATEST_LEAF_INDEX` --> `leaf_index - 1`
+    pub fn invalidate_leaves_from(&self, leaf_index: u32) -> Result<(), DbError> {
+        let leaves = self
+            .leaves_by_leaf_index(leaf_index..)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut batch = self.batch();
+        for (index, _) in leaves.iter() {
+            batch.delete_keyed(LEAF_IDX, index);
+        }
+        match leaf_index.checked_sub(1) {
+            Some(latest) => batch.store_encodable("", LATEST_LEAF_INDEX, &latest),
+            None => batch.delete("", LATEST_LEAF_INDEX),
+        }
+
+        // Re-index from the block of the first invalidated leaf
+        let first_block = leaves
+            .first()
+            .map(|(_, leaf)| self.retrieve_message_metadata(*leaf))
+            .transpose()?
+            .flatten()
+            .map(|metadata| metadata.block_number as u32);
+        if let (Some(block), Some(latest_block_end)) =
+            (first_block, self.retrieve_message_latest_block_end())
+        {
+            self.stage_message_latest_block_end(&mut batch, block.min(latest_block_end));
+        }
+
+        info!(
+            leaf_index,
+            count = leaves.len(),
+            block = ?first_block,
+            "Invalidated leaves from index {}",
+            leaf_index
+        );
+        self.write(batch)
+    }
+
     /// Store a pending update in the DB for potential submission.
     pub fn store_produced_update(
         &self,
@@ -375,6 +596,64 @@ impl NomadDB {
         self.retrieve_decodable("", PROVER_LATEST_COMMITTED)
     }
 
+    /// Store the number of leaves in the prover's tree when its root was
+    /// `root`
+    ///
+    /// Keys --> Values:
+    /// - `root` --> `tree_size`
+    pub fn store_tree_size_by_root(&self, root: H256, tree_size: u32) -> Result<(), DbError> {
+        self.store_keyed_encodable(PROVER_TREE_SIZE, &root, &tree_size)
+    }
+
+    /// Retrieve the number of leaves in the prover's tree when its root was
+    /// `root`
+    pub fn tree_size_by_root(&self, root: H256) -> Result<Option<u32>, DbError> {
+        self.retrieve_keyed_decodable(PROVER_TREE_SIZE, &root)
+    }
+
+    /// Store a node of the prover's tree by its layer and index within that
+    /// layer. Layer 0 holds the leaves
+    ///
+    /// Keys --> Values:
+    /// - `layer_and_index` --> `node`
+    pub fn store_prover_node(&self, layer: usize, index: usize, node: H256) -> Result<(), DbError> {
+        let mut batch = self.batch();
+        Self::stage_prover_node(&mut batch, layer, index, node);
+        self.write(batch)
+    }
+
+    /// Stage a node of the prover's tree, to be committed with the rest of
+    /// `batch`
+    pub fn stage_prover_node(batch: &mut TypedBatch, layer: usize, index: usize, node: H256) {
+        batch.store_keyed_encodable(PROVER_NODE, &layer_and_index(layer, index), &node)
+    }
+
+    /// Retrieve a node of the prover's tree by its layer and index within
+    /// that layer
+    pub fn prover_node(&self, layer: usize, index: usize) -> Result<Option<H256>, DbError> {
+        self.retrieve_keyed_decodable(PROVER_NODE, &layer_and_index(layer, index))
+    }
+
+    /// Store the number of leaves whose nodes have been written to the
+    /// prover's tree
+    pub fn store_prover_nodes_written(&self, count: u32) -> Result<(), DbError> {
+        let mut batch = self.batch();
+        Self::stage_prover_nodes_written(&mut batch, count);
+        self.write(batch)
+    }
+
+    /// Stage the number of leaves whose nodes have been written to the
+    /// prover's tree, to be committed with the nodes in `batch`
+    pub fn stage_prover_nodes_written(batch: &mut TypedBatch, count: u32) {
+        batch.store_encodable("", PROVER_NODES_WRITTEN, &count)
+    }
+
+    /// Retrieve the number of leaves whose nodes have been written to the
+    /// prover's tree
+    pub fn retrieve_prover_nodes_written(&self) -> Result<Option<u32>, DbError> {
+        self.retrieve_decodable("", PROVER_NODES_WRITTEN)
+    }
+
     /// Set a DB entry stating that the processor has previously attempted to
     /// process a message
     pub fn set_previously_attempted(&self, message: &CommittedMessage) -> Result<(), DbError> {
@@ -391,12 +670,36 @@ impl NomadDB {
     }
 }
 
+/// Key a tree node by its layer (high 32 bits) and index (low 32 bits)
+fn layer_and_index(layer: usize, index: usize) -> u64 {
+    ((layer as u64) << 32) | index as u64
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use ethers::types::H256;
-    use nomad_core::{accumulator::Proof, Encode, NomadMessage, RawCommittedMessage};
-    use nomad_test::test_utils::run_test_db;
+    use nomad_core::{accumulator::Proof, db::iterator::Page, Encode};
+    use nomad_test::test_utils::{run_test_db, run_test_rocks_db};
+
+    fn message_with_meta(leaf_index: u32) -> RawCommittedMessageWithMeta {
+        let m = NomadMessage {
+            origin: 10,
+            sender: H256::from_low_u64_be(4),
+            nonce: leaf_index,
+            destination: 12,
+            recipient: H256::from_low_u64_be(5),
+            body: vec![1, 2, 3],
+        };
+        RawCommittedMessageWithMeta {
+            raw_message: RawCommittedMessage {
+                leaf_index,
+                committed_root: H256::from_low_u64_be(3),
+                message: m.to_vec(),
+            },
+            metadata: Default::default(),
+        }
+    }
 
     #[tokio::test]
     async fn db_stores_and_retrieves_messages() {
@@ -441,16 +744,106 @@ mod test {
     }
 
     #[tokio::test]
-    async fn db_stores_and_retrieves_proofs() {
+    async fn db_stores_and_retrieves_message_metadata() {
         run_test_db(|db| async move {
             let home_name = "home_1".to_owned();
             let db = NomadDB::new(home_name, db);
 
-            let proof = Proof {
-                leaf: H256::from_low_u64_be(15),
-                index: 32,
-                path: Default::default(),
+            let message_with_meta = RawCommittedMessageWithMeta {
+                metadata: MessageMeta {
+                    block_number: 20,
+                    block_hash: H256::from_low_u64_be(6),
+                    tx_hash: Some(H256::from_low_u64_be(7)),
+                    log_index: 2,
+                    timestamp: None,
+                },
+                ..message_with_meta(0)
             };
+
+            db.store_messages(&[message_with_meta.clone()]).unwrap();
+
+            let by_index = db.message_by_leaf_index(0).unwrap().unwrap();
+            assert_eq!(by_index, message_with_meta.raw_message);
+
+            let metadata = db
+                .retrieve_message_metadata(message_with_meta.raw_message.leaf())
+                .unwrap()
+                .unwrap();
+            assert_eq!(metadata, message_with_meta.metadata);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_writes_messages_and_staged_writes_atomically() {
+        run_test_rocks_db(|db| async move {
+            let home_name = "home_1".to_owned();
+            let db = NomadDB::new(home_name, db);
+
+            let messages: Vec<_> = (0..3).map(message_with_meta).collect();
+
+            let mut batch = db.batch();
+            batch.store_encodable("", "block_end", &50u32);
+            assert_eq!(batch.len(), 1);
+
+            // Nothing is written until the batch is committed
+            assert_eq!(db.retrieve_decodable::<u32>("", "block_end").unwrap(), None);
+
+            db.write_messages(batch, &messages).unwrap();
+
+            assert_eq!(db.retrieve_latest_leaf_index().unwrap(), Some(2));
+            assert_eq!(
+                db.retrieve_decodable::<u32>("", "block_end").unwrap(),
+                Some(50)
+            );
+            for message in messages.iter() {
+                let by_index = db
+                    .message_by_leaf_index(message.raw_message.leaf_index)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(by_index, message.raw_message);
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_iterates_and_pages_messages_by_leaf_index() {
+        run_test_db(|db| async move {
+            let home_name = "home_1".to_owned();
+            let db = NomadDB::new(home_name, db);
+
+            let messages: Vec<_> = (0..5).map(message_with_meta).collect();
+            db.store_messages(&messages).unwrap();
+
+            let leaves: Vec<_> = db.leaf_iterator().map(Result::unwrap).collect();
+            assert_eq!(leaves.len(), 5);
+            assert_eq!(leaves[3], messages[3].raw_message.leaf());
+
+            let indices: Vec<_> = db
+                .messages_by_leaf_index(1..=3)
+                .map(|res| res.unwrap().0)
+                .collect();
+            assert_eq!(indices, vec![1, 2, 3]);
+
+            let first = Page::collect(db.messages_by_leaf_index(..), 2).unwrap();
+            assert_eq!(first.items[0], messages[0].raw_message);
+            assert_eq!(first.next, Some(2));
+
+            let last = Page::collect(db.messages_by_leaf_index(4..), 2).unwrap();
+            assert_eq!(last.items, vec![messages[4].raw_message.clone()]);
+            assert_eq!(last.next, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_stores_and_retrieves_proofs() {
+        run_test_db(|db| async move {
+            let home_name = "home_1".to_owned();
+            let db = NomadDB::new(home_name, db);
+
+            let proof = Proof::new(H256::from_low_u64_be(15), 32, Default::default());
             db.store_proof(13, &proof).unwrap();
 
             let by_index = db.proof_by_leaf_index(13).unwrap().unwrap();
@@ -458,4 +851,37 @@ mod test {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn db_invalidates_leaves_and_rewinds_message_indexer() {
+        run_test_db(|db| async move {
+            let home_name = "home_1".to_owned();
+            let db = NomadDB::new(home_name, db);
+
+            let messages: Vec<_> = (0..4)
+                .map(|leaf_index| RawCommittedMessageWithMeta {
+                    metadata: MessageMeta {
+                        block_number: 20 + leaf_index as u64,
+                        ..Default::default()
+                    },
+                    ..message_with_meta(leaf_index)
+                })
+                .collect();
+            db.store_messages(&messages).unwrap();
+            db.store_message_latest_block_end(50).unwrap();
+
+            db.invalidate_leaves_from(2).unwrap();
+
+            assert!(db.leaf_by_leaf_index(1).unwrap().is_some());
+            assert_eq!(db.leaf_by_leaf_index(2).unwrap(), None);
+            assert_eq!(db.leaf_by_leaf_index(3).unwrap(), None);
+            assert_eq!(db.retrieve_latest_leaf_index().unwrap(), Some(1));
+            assert_eq!(db.retrieve_message_latest_block_end(), Some(22));
+
+            // Re-fetched leaves build off the latest leaf index again
+            db.store_messages(&messages[2..]).unwrap();
+            assert_eq!(db.retrieve_latest_leaf_index().unwrap(), Some(3));
+        })
+        .await;
+    }
 }
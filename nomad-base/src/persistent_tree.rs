@@ -0,0 +1,450 @@
+use ethers::core::types::H256;
+use nomad_core::{
+    accumulator::{
+        full::build_layers, hash_concat, IngestionError, Keccak, Merkle, NomadLightMerkle,
+        NomadProof, ProvingError, TREE_DEPTH, ZERO_HASHES,
+    },
+    db::DbError,
+};
+
+use crate::NomadDB;
+
+/// Number of nodes committed per batch when building the tree in bulk
+const BUILD_BATCH_SIZE: usize = 10_000;
+
+/// PersistentTree errors
+#[derive(Debug, thiserror::Error)]
+pub enum PersistentTreeError {
+    /// A node required to resume the tree or build a proof is not in the db
+    #[error("Tree node at layer {layer}, index {index} not found in db")]
+    MissingNode {
+        /// The layer of the missing node
+        layer: usize,
+        /// The index of the missing node within its layer
+        index: usize,
+    },
+    /// Attempted to resume the tree at a size whose nodes were never written
+    #[error("Cannot resume tree with {requested} leaves. Nodes are written for {written} leaves")]
+    CountTooHigh {
+        /// The tree size requested
+        requested: usize,
+        /// The number of leaves whose nodes are in the db
+        written: usize,
+    },
+    /// Error ingesting a leaf
+    #[error(transparent)]
+    IngestionError(#[from] IngestionError),
+    /// Error producing a proof
+    #[error(transparent)]
+    ProvingError(#[from] ProvingError),
+    /// DB Error
+    #[error("{0}")]
+    DbError(#[from] DbError),
+}
+
+/// A merkle tree whose nodes are persisted in a `NomadDB`.
+///
+/// Ingesting a leaf writes every node on its path to the root, so proofs are
+/// built by reading `TREE_DEPTH` nodes from disk rather than by holding the
+/// full tree in memory. Only the leading-edge branch is kept in memory, and
+/// it is reloaded from disk in O(depth) when the tree is resumed.
+#[derive(Debug, Clone)]
+pub struct PersistentTree {
+    db: NomadDB,
+    frontier: NomadLightMerkle,
+}
+
+impl PersistentTree {
+    /// Resume the tree with `count` leaves from the nodes in `db`.
+    ///
+    /// `count` may be lower than the number of leaves written to disk (e.g.
+    /// if leaves were ingested but never committed). Nodes for any later
+    /// leaves are discarded.
+    pub fn open(db: NomadDB, count: usize) -> Result<Self, PersistentTreeError> {
+        let written = db.retrieve_prover_nodes_written()?.unwrap_or_default() as usize;
+        let mut tree = Self {
+            db,
+            frontier: Default::default(),
+        };
+        tree.rewind(count, written)?;
+        Ok(tree)
+    }
+
+    /// Load the tree with every leaf whose nodes are in `db`, without writing
+    /// to it. Suitable for read-only dbs, e.g. to produce historical proofs
+    pub fn load(db: NomadDB) -> Result<Self, PersistentTreeError> {
+        let written = db.retrieve_prover_nodes_written()?.unwrap_or_default() as usize;
+        let mut tree = Self {
+            db,
+            frontier: Default::default(),
+        };
+        tree.load_frontier(written)?;
+        Ok(tree)
+    }
+
+    /// Build the tree from `leaves`, replacing any nodes in `db`.
+    ///
+    /// Each layer of the tree is hashed at once, so this is much faster than
+    /// ingesting a large set of leaves one at a time. With the accumulator's
+    /// `rayon` feature, layers are hashed in parallel.
+    pub fn build(db: NomadDB, leaves: &[H256]) -> Result<Self, PersistentTreeError> {
+        // Nodes are committed in chunks. Until the last chunk, no leaves are
+        // marked as written, so a partial build is never resumed from
+        let mut batch = db.batch();
+        NomadDB::stage_prover_nodes_written(&mut batch, 0);
+        for (layer, nodes) in build_layers::<Keccak>(leaves, TREE_DEPTH)
+            .into_iter()
+            .enumerate()
+        {
+            for (index, node) in nodes.into_iter().enumerate() {
+                NomadDB::stage_prover_node(&mut batch, layer, index, node);
+                if batch.len() >= BUILD_BATCH_SIZE {
+                    db.write(std::mem::replace(&mut batch, db.batch()))?;
+                }
+            }
+        }
+        NomadDB::stage_prover_nodes_written(&mut batch, leaves.len() as u32);
+        db.write(batch)?;
+        Self::open(db, leaves.len())
+    }
+
+    /// Roll the tree back to contain only its first `count` leaves, e.g.
+    /// after a reorg. Nodes for any later leaves are discarded. Returns the
+    /// new root
+    pub fn rollback(&mut self, count: usize) -> Result<H256, PersistentTreeError> {
+        self.rewind(count, self.count())?;
+        Ok(self.root())
+    }
+
+    /// Truncate the nodes on disk to the first `count` of `written` leaves,
+    /// and reload the leading-edge branch
+    fn rewind(&mut self, count: usize, written: usize) -> Result<(), PersistentTreeError> {
+        if count > written {
+            return Err(PersistentTreeError::CountTooHigh {
+                requested: count,
+                written,
+            });
+        }
+
+        self.truncate(count)?;
+        self.load_frontier(count)
+    }
+
+    /// Reload the leading-edge branch of the tree with `count` leaves. Its
+    /// nodes are complete subtrees, so are unaffected by any later leaves
+    fn load_frontier(&mut self, count: usize) -> Result<(), PersistentTreeError> {
+        let mut branch = [H256::zero(); TREE_DEPTH];
+        for (layer, node) in branch.iter_mut().enumerate() {
+            let index = count >> layer;
+            *node = if index & 1 == 1 {
+                self.node(layer, index - 1)?
+            } else {
+                ZERO_HASHES[layer]
+            };
+        }
+        self.frontier = NomadLightMerkle::new(branch, count);
+
+        Ok(())
+    }
+
+    /// Recompute the ancestors of the last of `count` leaves, discarding any
+    /// contribution from later leaves. Ancestors of later leaves that do not
+    /// cover the first `count` leaves are never read, so are left in place
+    /// to be overwritten.
+    fn truncate(&self, count: usize) -> Result<(), PersistentTreeError> {
+        let mut batch = self.db.batch();
+        if count > 0 {
+            let mut index = count - 1;
+            let mut node = self.node(0, index)?;
+            for (layer, zero) in ZERO_HASHES.iter().enumerate().take(TREE_DEPTH) {
+                node = if index & 1 == 1 {
+                    hash_concat(self.node(layer, index - 1)?, node)
+                } else {
+                    hash_concat(node, zero)
+                };
+                index >>= 1;
+                NomadDB::stage_prover_node(&mut batch, layer + 1, index, node);
+            }
+        }
+        NomadDB::stage_prover_nodes_written(&mut batch, count as u32);
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Retrieve a node from the db, erroring if it is missing
+    fn node(&self, layer: usize, index: usize) -> Result<H256, PersistentTreeError> {
+        self.db
+            .prover_node(layer, index)?
+            .ok_or(PersistentTreeError::MissingNode { layer, index })
+    }
+
+    /// The number of leaves in the tree
+    pub fn count(&self) -> usize {
+        self.frontier.count()
+    }
+
+    /// Calculate the root of the tree
+    pub fn root(&self) -> H256 {
+        self.frontier.root()
+    }
+
+    /// Push a leaf to the tree, writing the nodes on its path to the db.
+    /// Returns the new root
+    pub fn ingest(&mut self, leaf: H256) -> Result<H256, PersistentTreeError> {
+        let mut index = self.count();
+        let branch = *self.frontier.branch();
+
+        let mut frontier = self.frontier;
+        let root = frontier.ingest(leaf)?;
+
+        // The leaf, its path and the new count are committed together
+        let mut batch = self.db.batch();
+        let mut node = leaf;
+        NomadDB::stage_prover_node(&mut batch, 0, index, node);
+        for (layer, zero) in ZERO_HASHES.iter().enumerate().take(TREE_DEPTH) {
+            node = if index & 1 == 1 {
+                hash_concat(branch[layer], node)
+            } else {
+                hash_concat(node, zero)
+            };
+            index >>= 1;
+            NomadDB::stage_prover_node(&mut batch, layer + 1, index, node);
+        }
+        debug_assert_eq!(node, root);
+
+        NomadDB::stage_prover_nodes_written(&mut batch, frontier.count() as u32);
+        self.db.write(batch)?;
+        self.frontier = frontier;
+        Ok(root)
+    }
+
+    /// Return the leaf at `index` and a Merkle proof of its inclusion,
+    /// reading the nodes on its path from the db.
+    ///
+    /// The Merkle proof is in "bottom-up" order, starting with a leaf node
+    /// and moving up the tree. Its length will be exactly equal to `depth`.
+    pub fn prove(&self, index: usize) -> Result<NomadProof, PersistentTreeError> {
+        if index > u32::MAX as usize {
+            return Err(ProvingError::IndexTooHigh(index).into());
+        }
+
+        let count = self.count();
+        if index >= count {
+            return Err(ProvingError::ZeroProof { index, count }.into());
+        }
+
+        let leaf = self.node(0, index)?;
+        let mut path = [H256::zero(); TREE_DEPTH];
+        for (layer, sibling) in path.iter_mut().enumerate() {
+            let sibling_index = (index >> layer) ^ 1;
+            // Subtrees entirely to the right of the last leaf are empty
+            *sibling = if sibling_index << layer >= count {
+                ZERO_HASHES[layer]
+            } else {
+                self.node(layer, sibling_index)?
+            };
+        }
+
+        Ok(NomadProof::new(leaf, index, path))
+    }
+
+    /// Return the leaf at `index` and a Merkle proof of its inclusion in the
+    /// tree as it was when it contained only its first `count` leaves.
+    ///
+    /// Stored nodes reflect every leaf ingested since, so nodes covering
+    /// leaf `count` are recomputed from their children as they were then.
+    pub fn prove_at(&self, index: usize, count: usize) -> Result<NomadProof, PersistentTreeError> {
+        if count > self.count() {
+            return Err(ProvingError::CountTooHigh {
+                requested: count,
+                count: self.count(),
+            }
+            .into());
+        }
+        if index >= count {
+            return Err(ProvingError::ZeroProof { index, count }.into());
+        }
+
+        let leaf = self.node(0, index)?;
+        let mut path = [H256::zero(); TREE_DEPTH];
+        for (layer, sibling) in path.iter_mut().enumerate() {
+            *sibling = self.node_at(layer, (index >> layer) ^ 1, count)?;
+        }
+
+        Ok(NomadProof::new(leaf, index, path))
+    }
+
+    /// Retrieve a node as it was when the tree contained only its first
+    /// `count` leaves
+    fn node_at(
+        &self,
+        layer: usize,
+        index: usize,
+        count: usize,
+    ) -> Result<H256, PersistentTreeError> {
+        let first = index << layer;
+        let last = ((index + 1) << layer) - 1;
+
+        if first >= count {
+            // Subtree was entirely empty
+            Ok(ZERO_HASHES[layer])
+        } else if last < count {
+            // Subtree was complete, and has not changed since
+            self.node(layer, index)
+        } else {
+            Ok(hash_concat(
+                self.node_at(layer - 1, index * 2, count)?,
+                self.node_at(layer - 1, index * 2 + 1, count)?,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nomad_core::accumulator::{MerkleProof, NomadTree};
+    use nomad_test::test_utils::run_test_db;
+
+    fn leaves(n: u64) -> Vec<H256> {
+        (0..n).map(H256::from_low_u64_be).collect()
+    }
+
+    #[tokio::test]
+    async fn it_matches_the_in_memory_tree() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+            let mut tree = PersistentTree::open(db, 0).unwrap();
+            let mut expected = NomadTree::default();
+            assert_eq!(tree.root(), expected.root());
+
+            for leaf in leaves(37) {
+                assert_eq!(tree.ingest(leaf).unwrap(), expected.ingest(leaf).unwrap());
+                for i in 0..tree.count() {
+                    assert_eq!(tree.prove(i).unwrap(), expected.prove(i).unwrap());
+                }
+            }
+
+            assert!(matches!(
+                tree.prove(37),
+                Err(PersistentTreeError::ProvingError(
+                    ProvingError::ZeroProof { .. }
+                ))
+            ));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn it_builds_from_leaves() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+            let expected: NomadTree = leaves(37).as_slice().into();
+
+            let mut tree = PersistentTree::open(db.clone(), 0).unwrap();
+            leaves(40).into_iter().for_each(|leaf| {
+                tree.ingest(leaf).unwrap();
+            });
+
+            // Building replaces the nodes of the earlier tree
+            let mut tree = PersistentTree::build(db.clone(), &leaves(37)).unwrap();
+            assert_eq!(tree.count(), 37);
+            assert_eq!(tree.root(), expected.root());
+            for i in 0..37 {
+                assert_eq!(tree.prove(i).unwrap(), expected.prove(i).unwrap());
+            }
+            assert_eq!(
+                PersistentTree::open(db, 37).unwrap().root(),
+                expected.root()
+            );
+
+            let expected: NomadTree = leaves(38).as_slice().into();
+            tree.ingest(H256::from_low_u64_be(37)).unwrap();
+            assert_eq!(tree.root(), expected.root());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn it_proves_against_earlier_sizes() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+            let expected: NomadTree = leaves(37).as_slice().into();
+
+            let mut tree = PersistentTree::open(db.clone(), 0).unwrap();
+            leaves(37).into_iter().for_each(|leaf| {
+                tree.ingest(leaf).unwrap();
+            });
+
+            let loaded = PersistentTree::load(db).unwrap();
+            assert_eq!(loaded.root(), expected.root());
+            for count in 1..=37 {
+                for i in 0..count {
+                    let proof = loaded.prove_at(i, count).unwrap();
+                    assert_eq!(proof, expected.prove_at(i, count).unwrap());
+                    assert_eq!(proof.root(), expected.root_at(count).unwrap());
+                }
+            }
+
+            assert!(matches!(
+                loaded.prove_at(3, 38),
+                Err(PersistentTreeError::ProvingError(
+                    ProvingError::CountTooHigh { .. }
+                ))
+            ));
+            assert!(matches!(
+                loaded.prove_at(20, 20),
+                Err(PersistentTreeError::ProvingError(
+                    ProvingError::ZeroProof { .. }
+                ))
+            ));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn it_resumes_from_disk() {
+        run_test_db(|db| async move {
+            let db = NomadDB::new("home_1", db);
+            let mut tree = PersistentTree::open(db.clone(), 0).unwrap();
+            leaves(21).into_iter().for_each(|leaf| {
+                tree.ingest(leaf).unwrap();
+            });
+            let root = tree.root();
+
+            let resumed = PersistentTree::open(db.clone(), 21).unwrap();
+            assert_eq!(resumed.root(), root);
+            assert!(matches!(
+                PersistentTree::open(db.clone(), 22),
+                Err(PersistentTreeError::CountTooHigh { .. })
+            ));
+
+            // Resuming at an earlier size discards the later leaves
+            let mut rewound = PersistentTree::open(db.clone(), 17).unwrap();
+            assert_eq!(
+                rewound.rollback(13).unwrap(),
+                NomadTree::from(leaves(13).as_slice()).root()
+            );
+            let expected: NomadTree = leaves(13).as_slice().into();
+            assert_eq!(rewound.root(), expected.root());
+            for i in 0..13 {
+                assert_eq!(rewound.prove(i).unwrap(), expected.prove(i).unwrap());
+            }
+
+            // and the tree can be rebuilt on a different history
+            let mut forked = leaves(13);
+            forked.push(H256::repeat_byte(1));
+            let expected: NomadTree = forked.as_slice().into();
+            rewound.ingest(H256::repeat_byte(1)).unwrap();
+            assert_eq!(rewound.root(), expected.root());
+            for i in 0..14 {
+                assert_eq!(rewound.prove(i).unwrap(), expected.prove(i).unwrap());
+            }
+            assert_eq!(
+                PersistentTree::open(db, 14).unwrap().root(),
+                expected.root()
+            );
+        })
+        .await;
+    }
+}
@@ -1,10 +1,10 @@
 use color_eyre::Result;
 use nomad_core::ContractLocator;
 use nomad_ethereum::{make_conn_manager, make_replica};
-use nomad_types::NomadIdentifier;
+use nomad_types::{deser_ss58_prefix, NomadIdentifier};
 use nomad_xyz_configuration::{
-    core::CoreDeploymentInfo, AgentSecrets, ChainConf, ConnectionManagerGasLimits, HomeGasLimits,
-    NomadConfig, ReplicaGasLimits, TxSubmitterConf,
+    agent::RpcStyles, core::CoreDeploymentInfo, AgentSecrets, ChainConf,
+    ConnectionManagerGasLimits, HomeGasLimits, NomadConfig, ReplicaGasLimits, TxSubmitterConf,
 };
 use serde::Deserialize;
 
@@ -59,6 +59,9 @@ pub struct ChainSetup {
     pub finality: u8,
     /// Network specific block time in seconds
     pub block_time: u64,
+    /// SS58 address prefix, for substrate chains
+    #[serde(default, deserialize_with = "deser_ss58_prefix")]
+    pub ss58_prefix: Option<u16>,
     /// The chain connection details
     #[serde(flatten)]
     pub chain: ChainConf,
@@ -88,6 +91,7 @@ impl ChainSetup {
         let domain_number = domain.domain;
         let finality = domain.specs.finalization_blocks;
         let block_time = domain.specs.block_time;
+        let ss58_prefix = domain.specs.ss58_prefix;
         let core = config.core().get(&resident_network).expect("!core");
         let (address, page_settings) = match core {
             CoreDeploymentInfo::Ethereum(core) => {
@@ -132,11 +136,25 @@ impl ChainSetup {
             page_settings,
             finality,
             block_time,
+            ss58_prefix,
             chain,
             disabled: None,
         }
     }
 
+    /// The rpc style of the chain
+    pub fn rpc_style(&self) -> RpcStyles {
+        match self.chain {
+            ChainConf::Ethereum(_) => RpcStyles::Ethereum,
+            ChainConf::Substrate(_) => RpcStyles::Substrate,
+        }
+    }
+
+    /// Display `id` as an address on this chain
+    pub fn display_identifier(&self, id: &NomadIdentifier) -> String {
+        self.rpc_style().display_identifier(id, self.ss58_prefix)
+    }
+
     /// Try to convert the chain setting into a Home contract
     pub async fn try_into_home(
         &self,
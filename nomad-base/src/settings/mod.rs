@@ -539,6 +539,7 @@ impl Settings {
             self.home.finality,
             config_home_domain.specs.finalization_blocks
         );
+        assert_eq!(self.home.ss58_prefix, config_home_domain.specs.ss58_prefix);
 
         let config_home_core = config.core().get(home_network).unwrap();
         match config_home_core {
@@ -572,6 +573,10 @@ impl Settings {
                 replica_setup.finality,
                 config_replica_domain.specs.finalization_blocks
             );
+            assert_eq!(
+                replica_setup.ss58_prefix,
+                config_replica_domain.specs.ss58_prefix
+            );
 
             let config_replica_core = config.core().get(remote_network).unwrap();
             match config_replica_core {
@@ -1,4 +1,5 @@
-use nomad_base::{AttestationSigner, Signer};
+use ethers::core::types::H256;
+use nomad_base::{AttestationSigner, Attester};
 use nomad_core::FromSignerConf;
 use nomad_xyz_configuration::agent::SignerConf;
 
@@ -8,7 +9,6 @@ async fn signer_auths() {
         let signer = AttestationSigner::try_from_signer_conf(&signer_conf)
             .await
             .unwrap();
-        let message = "hello world";
-        signer.sign_message(message).await.unwrap();
+        signer.sign_attestation(H256::repeat_byte(1)).await.unwrap();
     }
 }
@@ -6,6 +6,8 @@ fn main() {
     {
         output_signed_updates();
         output_signed_failure_notifications();
+        output_typed_updates();
+        output_typed_failure_notifications();
         output_message_and_leaf();
     }
 }
@@ -0,0 +1,65 @@
+use std::{collections::BTreeMap, sync::RwLock};
+
+use super::{BatchOp, KvIterator, KvStore};
+use crate::db::Result;
+
+/// A `KvStore` held in memory, for tests and simulations. Nothing is
+/// persisted when it is dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStore(RwLock<BTreeMap<Vec<u8>, Vec<u8>>>);
+
+impl MemoryStore {
+    /// Instantiate an empty store
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl KvStore for MemoryStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.read().expect("poisoned").get(key).cloned())
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.0
+            .write()
+            .expect("poisoned")
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn write(&self, ops: Vec<BatchOp>) -> Result<()> {
+        let mut map = self.0.write().expect("poisoned");
+        for op in ops {
+            match op {
+                BatchOp::Put(key, value) => map.insert(key, value),
+                BatchOp::Delete(key) => map.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn iter_from<'a>(&'a self, prefix: &[u8], from: &[u8]) -> KvIterator<'a> {
+        // Snapshot the matching range, so the lock isn't held while iterating
+        let pairs: Vec<_> = self
+            .0
+            .read()
+            .expect("poisoned")
+            .range(from.to_vec()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| Ok((k.clone(), v.clone())))
+            .collect();
+        Box::new(pairs.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::backend::test_suite::check_store;
+
+    #[test]
+    fn it_passes_the_backend_suite() {
+        check_store(&MemoryStore::new());
+    }
+}
@@ -0,0 +1,133 @@
+use super::Result;
+
+/// RocksDB storage
+mod rocks;
+pub use rocks::*;
+
+/// In-memory storage
+mod memory;
+pub use memory::*;
+
+/// Sled storage
+#[cfg(feature = "sled")]
+mod sled;
+#[cfg(feature = "sled")]
+pub use self::sled::*;
+
+/// A raw key-value pair
+pub type KvPair = (Vec<u8>, Vec<u8>);
+
+/// A raw write in a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    /// Store the value at the key
+    Put(Vec<u8>, Vec<u8>),
+    /// Delete the key
+    Delete(Vec<u8>),
+}
+
+/// An iterator over the raw key-value pairs under a prefix
+pub type KvIterator<'a> = Box<dyn Iterator<Item = Result<KvPair>> + 'a>;
+
+/// A key-value storage backend. `DB` adds prefixing and encoding on top of
+/// this, so implementations only deal in raw bytes.
+pub trait KvStore: Send + Sync + std::fmt::Debug {
+    /// Retrieve the value at `key`
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Store `value` at `key`
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Atomically apply every op in `ops`, in order
+    fn write(&self, ops: Vec<BatchOp>) -> Result<()>;
+
+    /// Iterate over every pair whose key starts with `prefix` and is at
+    /// least `from`, in ascending key order
+    fn iter_from<'a>(&'a self, prefix: &[u8], from: &[u8]) -> KvIterator<'a>;
+
+    /// Iterate over every pair whose key starts with `prefix`, in ascending
+    /// key order
+    fn prefix_iter<'a>(&'a self, prefix: &[u8]) -> KvIterator<'a> {
+        self.iter_from(prefix, prefix)
+    }
+
+    /// Catch up with writes made by another process. A no-op for backends
+    /// that are not following a primary
+    fn catch_up(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Checks shared by every `KvStore` implementation, so each backend is held
+/// to the same behavior
+#[cfg(test)]
+pub(crate) mod test_suite {
+    use super::*;
+
+    /// Run every check against an empty `store`
+    pub(crate) fn check_store(store: &dyn KvStore) {
+        it_gets_and_puts(store);
+        it_applies_batches_in_order(store);
+        it_iterates_in_order_under_prefix(store);
+    }
+
+    fn it_gets_and_puts(store: &dyn KvStore) {
+        assert_eq!(store.get(b"key").unwrap(), None);
+        store.put(b"key", b"a").unwrap();
+        assert_eq!(store.get(b"key").unwrap(), Some(b"a".to_vec()));
+        store.put(b"key", b"b").unwrap();
+        assert_eq!(store.get(b"key").unwrap(), Some(b"b".to_vec()));
+    }
+
+    fn it_applies_batches_in_order(store: &dyn KvStore) {
+        store
+            .write(vec![
+                BatchOp::Put(b"batch_0".to_vec(), b"a".to_vec()),
+                BatchOp::Delete(b"batch_0".to_vec()),
+                BatchOp::Put(b"batch_1".to_vec(), b"b".to_vec()),
+                BatchOp::Put(b"batch_1".to_vec(), b"c".to_vec()),
+                BatchOp::Delete(b"batch_2".to_vec()),
+            ])
+            .unwrap();
+
+        assert_eq!(store.get(b"batch_0").unwrap(), None);
+        assert_eq!(store.get(b"batch_1").unwrap(), Some(b"c".to_vec()));
+        assert_eq!(store.get(b"batch_2").unwrap(), None);
+    }
+
+    fn it_iterates_in_order_under_prefix(store: &dyn KvStore) {
+        store.put(b"leaf_1", b"a").unwrap();
+        store
+            .write(vec![
+                BatchOp::Put(b"leaf_0".to_vec(), b"b".to_vec()),
+                BatchOp::Put(b"leaf_index_0".to_vec(), b"c".to_vec()),
+                BatchOp::Put(b"leag".to_vec(), b"d".to_vec()),
+                BatchOp::Put(b"lea".to_vec(), b"e".to_vec()),
+                BatchOp::Put(b"le".to_vec(), b"f".to_vec()),
+                BatchOp::Delete(b"le".to_vec()),
+            ])
+            .unwrap();
+
+        let keys: Vec<_> = store
+            .prefix_iter(b"leaf_")
+            .map(|res| res.unwrap().0)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                b"leaf_0".to_vec(),
+                b"leaf_1".to_vec(),
+                b"leaf_index_0".to_vec()
+            ]
+        );
+        let keys: Vec<_> = store
+            .iter_from(b"leaf_", b"leaf_1")
+            .map(|res| res.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![b"leaf_1".to_vec(), b"leaf_index_0".to_vec()]);
+        assert_eq!(store.prefix_iter(b"missing_").count(), 0);
+
+        assert_eq!(store.get(b"lea").unwrap(), Some(b"e".to_vec()));
+        assert_eq!(store.get(b"le").unwrap(), None);
+    }
+}
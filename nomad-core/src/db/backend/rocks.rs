@@ -0,0 +1,111 @@
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB as Rocks};
+use std::path::Path;
+
+use super::{BatchOp, KvIterator, KvStore};
+use crate::db::Result;
+
+/// How a `RocksStore` is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RocksMode {
+    /// Exclusive read-write handle. Only one process may hold it
+    ReadWrite,
+    /// Read-only view of the DB as it was when opened. Can be opened
+    /// alongside a live read-write handle
+    ReadOnly,
+    /// Read-only handle that follows a live read-write handle via
+    /// `KvStore::catch_up`
+    Secondary,
+}
+
+/// A `KvStore` backed by RocksDB
+#[derive(Debug)]
+pub struct RocksStore {
+    rocks: Rocks,
+    mode: RocksMode,
+}
+
+impl From<Rocks> for RocksStore {
+    fn from(rocks: Rocks) -> Self {
+        Self {
+            rocks,
+            mode: RocksMode::ReadWrite,
+        }
+    }
+}
+
+impl RocksStore {
+    /// Open a read-write handle at `path`, creating the DB if missing
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+
+        Ok(Rocks::open(&opts, path)?.into())
+    }
+
+    /// Open a read-only handle at `path`
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            rocks: Rocks::open_for_read_only(&Options::default(), path, false)?,
+            mode: RocksMode::ReadOnly,
+        })
+    }
+
+    /// Open a secondary handle on the primary DB at `path`. RocksDB keeps
+    /// the secondary's own logs at `secondary_path`
+    pub fn open_secondary(
+        path: impl AsRef<Path>,
+        secondary_path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let mut opts = Options::default();
+        // Required for secondary instances
+        opts.set_max_open_files(-1);
+
+        Ok(Self {
+            rocks: Rocks::open_as_secondary(&opts, path.as_ref(), secondary_path.as_ref())?,
+            mode: RocksMode::Secondary,
+        })
+    }
+
+    /// How this handle was opened
+    pub fn mode(&self) -> RocksMode {
+        self.mode
+    }
+}
+
+impl KvStore for RocksStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.rocks.get(key)?)
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        Ok(self.rocks.put(key, value)?)
+    }
+
+    fn write(&self, ops: Vec<BatchOp>) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for op in ops {
+            match op {
+                BatchOp::Put(key, value) => batch.put(key, value),
+                BatchOp::Delete(key) => batch.delete(key),
+            }
+        }
+        Ok(self.rocks.write(batch)?)
+    }
+
+    fn iter_from<'a>(&'a self, prefix: &[u8], from: &[u8]) -> KvIterator<'a> {
+        let owned_prefix = prefix.to_vec();
+        Box::new(
+            self.rocks
+                .iterator(IteratorMode::From(from, Direction::Forward))
+                .take_while(move |(k, _)| k.starts_with(&owned_prefix))
+                .map(|(k, v)| Ok((k.into_vec(), v.into_vec()))),
+        )
+    }
+
+    fn catch_up(&self) -> Result<()> {
+        match self.mode {
+            RocksMode::Secondary => Ok(self.rocks.try_catch_up_with_primary()?),
+            _ => Ok(()),
+        }
+    }
+}
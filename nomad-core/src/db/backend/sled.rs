@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use super::{BatchOp, KvIterator, KvStore};
+use crate::db::Result;
+
+/// A `KvStore` backed by sled, a pure-Rust embedded DB
+#[derive(Debug)]
+pub struct SledStore(sled::Db);
+
+impl From<sled::Db> for SledStore {
+    fn from(db: sled::Db) -> Self {
+        Self(db)
+    }
+}
+
+impl SledStore {
+    /// Open the DB at `path`, creating it if missing
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(sled::open(path)?.into())
+    }
+}
+
+impl KvStore for SledStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.0.insert(key, value)?;
+        Ok(())
+    }
+
+    fn write(&self, ops: Vec<BatchOp>) -> Result<()> {
+        let mut batch = sled::Batch::default();
+        for op in ops {
+            match op {
+                BatchOp::Put(key, value) => batch.insert(key, value),
+                BatchOp::Delete(key) => batch.remove(key),
+            }
+        }
+        Ok(self.0.apply_batch(batch)?)
+    }
+
+    fn iter_from<'a>(&'a self, prefix: &[u8], from: &[u8]) -> KvIterator<'a> {
+        let owned_prefix = prefix.to_vec();
+        Box::new(
+            self.0
+                .range(from..)
+                .take_while(move |res| match res {
+                    Ok((k, _)) => k.starts_with(&owned_prefix),
+                    Err(_) => true,
+                })
+                .map(|res| {
+                    res.map(|(k, v)| (k.to_vec(), v.to_vec()))
+                        .map_err(Into::into)
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::{backend::test_suite::check_store, CURRENT_SCHEMA_VERSION, DB};
+
+    fn temporary() -> SledStore {
+        sled::Config::new().temporary(true).open().unwrap().into()
+    }
+
+    #[test]
+    fn it_passes_the_backend_suite() {
+        check_store(&temporary());
+    }
+
+    #[test]
+    fn it_migrates_while_iterating() {
+        // Sled iterators are not snapshots, so the migration's own writes
+        // may be visible to the iterator it is reading from
+        let db = DB::new(temporary());
+        for i in 0..10u32 {
+            db.store_keyed_encodable("home_leaf_", &i, &i).unwrap();
+        }
+
+        db.migrate().unwrap();
+        assert_eq!(db.schema_version().unwrap(), Some(CURRENT_SCHEMA_VERSION));
+        for i in 0..10u32 {
+            let moved: Option<u32> = db.retrieve_keyed_decodable("home_leaf_", &i).unwrap();
+            assert_eq!(moved, None);
+            let moved: Option<u32> = db.retrieve_keyed_decodable("home_leaf_index_", &i).unwrap();
+            assert_eq!(moved, Some(i));
+        }
+    }
+}
@@ -1,17 +1,18 @@
 use crate::{Decode, Encode};
-use rocksdb::DBIterator;
 use std::marker::PhantomData;
 
+use super::{backend::KvIterator, DbError};
+
 /// An iterator over a prefix that deserializes values
 pub struct PrefixIterator<'a, V> {
-    iter: DBIterator<'a>,
+    iter: KvIterator<'a>,
     prefix: &'a [u8],
     _phantom: PhantomData<*const V>,
 }
 
 impl<'a, V> PrefixIterator<'a, V> {
     /// Return new prefix iterator
-    pub fn new(iter: DBIterator<'a>, prefix: &'a [u8]) -> Self {
+    pub fn new(iter: KvIterator<'a>, prefix: &'a [u8]) -> Self {
         Self {
             iter,
             prefix,
@@ -24,13 +25,110 @@ impl<'a, V> Iterator for PrefixIterator<'a, V>
 where
     V: Encode + Decode,
 {
-    type Item = V;
+    type Item = Result<V, DbError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let prefix = self.prefix;
         self.iter
-            .find(|(k, _)| k.strip_prefix(prefix).is_some())
-            .map(|(_, v)| v.to_vec())
-            .map(|v| V::read_from(&mut v.as_slice()).expect("!corrupt"))
+            .by_ref()
+            .find(|res| match res {
+                Ok((k, _)) => k.starts_with(prefix),
+                Err(_) => true,
+            })
+            .map(|res| Ok(V::read_from(&mut res?.1.as_slice())?))
+    }
+}
+
+/// An iterator over a prefix that deserializes keys and values, in key
+/// order. Keys are decoded from the remainder after the prefix, so every key
+/// under the prefix must be a `K`
+pub struct KeyedIterator<'a, K, V> {
+    iter: KvIterator<'a>,
+    prefix_len: usize,
+    _phantom: PhantomData<*const (K, V)>,
+}
+
+impl<'a, K, V> KeyedIterator<'a, K, V> {
+    /// Return new keyed iterator over `iter`, whose keys all start with a
+    /// prefix of length `prefix_len`
+    pub fn new(iter: KvIterator<'a>, prefix_len: usize) -> Self {
+        Self {
+            iter,
+            prefix_len,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for KeyedIterator<'a, K, V>
+where
+    K: Decode,
+    V: Decode,
+{
+    type Item = Result<(K, V), DbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prefix_len = self.prefix_len;
+        self.iter.next().map(|res| {
+            let (k, v) = res?;
+            let key = K::read_from(&mut &k[prefix_len..])?;
+            let value = V::read_from(&mut v.as_slice())?;
+            Ok((key, value))
+        })
+    }
+}
+
+/// A page of items read from an iterator of `(cursor, item)` pairs, and the
+/// cursor of the first item of the next page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<C, T> {
+    /// Items in this page
+    pub items: Vec<T>,
+    /// Cursor to resume from. `None` if this is the last page
+    pub next: Option<C>,
+}
+
+impl<C, T> Page<C, T> {
+    /// Read up to `limit` items from `iter`. Reads one more item, to find
+    /// the next page's cursor. Stops at the first error
+    pub fn collect<I>(iter: I, limit: usize) -> Result<Self, DbError>
+    where
+        I: IntoIterator<Item = Result<(C, T), DbError>>,
+    {
+        let mut items = Vec::with_capacity(limit);
+        for res in iter {
+            let (cursor, item) = res?;
+            if items.len() == limit {
+                return Ok(Self {
+                    items,
+                    next: Some(cursor),
+                });
+            }
+            items.push(item);
+        }
+
+        Ok(Self { items, next: None })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_pages_with_cursors() {
+        let pairs = || (0u32..5).map(|i| Ok((i, i * 10)));
+
+        let first = Page::collect(pairs(), 2).unwrap();
+        assert_eq!(first.items, vec![0, 10]);
+        assert_eq!(first.next, Some(2));
+
+        let last = Page::collect(pairs().skip(4), 2).unwrap();
+        assert_eq!(last.items, vec![40]);
+        assert_eq!(last.next, None);
+
+        let exact = Page::collect(pairs(), 5).unwrap();
+        assert_eq!(exact.items.len(), 5);
+        assert_eq!(exact.next, None);
     }
 }
@@ -0,0 +1,429 @@
+use tracing::info;
+
+use super::{DbBatch, DbError, Result, DB};
+use crate::SignatureScheme;
+
+/// Unprefixed key holding the db's schema version. Entity keys are always
+/// prefixed with `<entity>_`, so this can't collide with them
+const SCHEMA_VERSION: &str = "nomad_schema_version";
+
+/// Schema version written by this version of the agents. Must equal the
+/// version of the last migration in `MIGRATIONS`
+pub const CURRENT_SCHEMA_VERSION: u32 = 3;
+
+/// Most writes a migration should stage into one batch
+pub const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// Commits a chunk of a migration's writes
+pub type CommitBatch<'a> = dyn FnMut(DbBatch) -> Result<()> + 'a;
+
+/// A step from schema version `version - 1` to `version`
+#[derive(Debug)]
+pub struct Migration {
+    /// Schema version after this migration
+    pub version: u32,
+    /// What the migration changes
+    pub description: &'static str,
+    /// Stage the migration's writes, passing each chunk of at most
+    /// `MIGRATION_BATCH_SIZE` writes to the commit fn. The version bump is
+    /// committed with the last chunk, so a migration interrupted part way is
+    /// re-run from the start, and must skip writes it has already made
+    pub stage: fn(&DB, &mut CommitBatch) -> Result<()>,
+}
+
+/// Every migration, in order. Dbs written before schema versioning are at
+/// version 0
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "mark unversioned key layout as version 1",
+        stage: |_, _| Ok(()),
+    },
+    Migration {
+        version: 2,
+        description: "move leaves keyed by leaf index to their own prefix",
+        stage: separate_leaf_indices,
+    },
+    Migration {
+        version: 3,
+        description: "tag stored signed updates with their signature scheme",
+        stage: tag_signed_updates,
+    },
+];
+
+/// `NomadDB` stored leaves keyed by both leaf index and destination + nonce
+/// under `<entity>_leaf_`, so leaf indices couldn't be iterated in order.
+/// Move those keyed by leaf index to `<entity>_leaf_index_`. Moved leaves are
+/// deleted from `<entity>_leaf_`, so re-running only moves the rest.
+fn separate_leaf_indices(db: &DB, commit: &mut CommitBatch) -> Result<()> {
+    let mut batch = DbBatch::new();
+    for res in db.prefix_iterator("") {
+        let (key, value) = res?;
+        if key.len() < 4 {
+            continue;
+        }
+
+        // Leaf indices are 4 bytes, destination + nonce keys are 8
+        let (head, leaf_index) = key.split_at(key.len() - 4);
+        if let Some(entity) = head.strip_suffix(b"_leaf_") {
+            batch.put([entity, b"_leaf_index_", leaf_index].concat(), value);
+            batch.delete(key);
+        }
+
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            commit(std::mem::take(&mut batch))?;
+        }
+    }
+    commit(batch)
+}
+
+/// Length of a `SignedUpdate` encoded before it carried a scheme tag: 68
+/// bytes update + 65 bytes signature
+const UNTAGGED_SIGNED_UPDATE_LEN: usize = 133;
+
+/// `SignedUpdate`s were encoded without a signature scheme tag, and could
+/// only be secp256k1. Append the secp256k1 tag to those stored under
+/// `<entity>_update_` and `<entity>_updater_produced_update_`, keyed by
+/// previous root. Tagged updates are longer, so re-running skips them.
+fn tag_signed_updates(db: &DB, commit: &mut CommitBatch) -> Result<()> {
+    let mut batch = DbBatch::new();
+    for res in db.prefix_iterator("") {
+        let (key, value) = res?;
+        if key.len() < 32 || value.len() != UNTAGGED_SIGNED_UPDATE_LEN {
+            continue;
+        }
+
+        let (head, _previous_root) = key.split_at(key.len() - 32);
+        if head.ends_with(b"_update_") {
+            let mut tagged = value;
+            tagged.push(SignatureScheme::Secp256k1 as u8);
+            batch.put(key, tagged);
+        }
+
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            commit(std::mem::take(&mut batch))?;
+        }
+    }
+    commit(batch)
+}
+
+/// A migration that was (or, in a dry run, would be) applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationOutcome {
+    /// Schema version after the migration
+    pub version: u32,
+    /// What the migration changes
+    pub description: &'static str,
+    /// Number of writes in the migration's batches
+    pub writes: usize,
+    /// Number of batches the writes are committed in
+    pub batches: usize,
+}
+
+impl std::fmt::Display for MigrationOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "v{}: {} ({} writes in {} batches)",
+            self.version, self.description, self.writes, self.batches
+        )
+    }
+}
+
+impl DB {
+    /// Retrieve the stored schema version. `None` if the db has never been
+    /// stamped with one
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        self.retrieve_decodable("", SCHEMA_VERSION)
+    }
+
+    /// Check that the db is at `CURRENT_SCHEMA_VERSION`, for dbs opened
+    /// without migrating them (e.g. read-only)
+    pub fn check_schema_version(&self) -> Result<()> {
+        match self.starting_version()? {
+            CURRENT_SCHEMA_VERSION => Ok(()),
+            version => Err(DbError::OutdatedSchemaVersion(
+                version,
+                CURRENT_SCHEMA_VERSION,
+            )),
+        }
+    }
+
+    /// Version the db is migrated from. Unversioned dbs are at version 0,
+    /// unless they are empty, in which case there is nothing to migrate
+    fn starting_version(&self) -> Result<u32> {
+        if let Some(version) = self.schema_version()? {
+            if version > CURRENT_SCHEMA_VERSION {
+                return Err(DbError::UnsupportedSchemaVersion(
+                    version,
+                    CURRENT_SCHEMA_VERSION,
+                ));
+            }
+            return Ok(version);
+        }
+
+        match self.prefix_iterator("").next().transpose()? {
+            Some(_) => Ok(0),
+            None => Ok(CURRENT_SCHEMA_VERSION),
+        }
+    }
+
+    /// Migrations not yet applied to the db, in order
+    fn pending_migrations(&self) -> Result<impl Iterator<Item = &'static Migration>> {
+        let from = self.starting_version()?;
+        Ok(MIGRATIONS.iter().filter(move |m| m.version > from))
+    }
+
+    /// Stage `migration` and the version bump, committing each chunk unless
+    /// this is a dry run. The version bump is added to the last chunk
+    fn run_migration(&self, migration: &Migration, dry_run: bool) -> Result<MigrationOutcome> {
+        let mut outcome = MigrationOutcome {
+            version: migration.version,
+            description: migration.description,
+            writes: 0,
+            batches: 0,
+        };
+
+        // Each chunk is held back until the next arrives, so the last can be
+        // committed together with the version bump
+        let mut pending: Option<DbBatch> = None;
+        let mut commit = |batch: DbBatch| -> Result<()> {
+            if let Some(previous) = pending.replace(batch) {
+                outcome.writes += previous.len();
+                outcome.batches += 1;
+                if !dry_run {
+                    self.write(previous)?;
+                }
+            }
+            Ok(())
+        };
+        (migration.stage)(self, &mut commit)?;
+
+        let mut last = pending.unwrap_or_default();
+        last.store_encodable("", SCHEMA_VERSION, &migration.version);
+        outcome.writes += last.len();
+        outcome.batches += 1;
+        if !dry_run {
+            self.write(last)?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// List the migrations `DB::migrate` would apply, and their writes,
+    /// without applying them. Each migration is staged against the current
+    /// db, so it doesn't see the writes of the pending migrations before it
+    pub fn plan_migrations(&self) -> Result<Vec<MigrationOutcome>> {
+        self.pending_migrations()?
+            .map(|migration| self.run_migration(migration, true))
+            .collect()
+    }
+
+    /// Apply pending migrations in order, leaving the db at
+    /// `CURRENT_SCHEMA_VERSION`. Each migration is committed in batches of
+    /// at most `MIGRATION_BATCH_SIZE` writes, with the version bump in the
+    /// last
+    pub fn migrate(&self) -> Result<Vec<MigrationOutcome>> {
+        // Fresh dbs have nothing to migrate, and are stamped directly
+        let unversioned = self.schema_version()?.is_none();
+        if unversioned && self.starting_version()? == CURRENT_SCHEMA_VERSION {
+            self.store_encodable("", SCHEMA_VERSION, &CURRENT_SCHEMA_VERSION)?;
+            return Ok(vec![]);
+        }
+
+        let mut outcomes = vec![];
+        for migration in self.pending_migrations()? {
+            let outcome = self.run_migration(migration, false)?;
+            info!(
+                version = outcome.version,
+                writes = outcome.writes,
+                batches = outcome.batches,
+                "Applied db migration: {}",
+                outcome.description
+            );
+            outcomes.push(outcome);
+        }
+
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Encode, SignedUpdate, Update};
+    use ethers::core::types::{Signature, H256};
+
+    #[test]
+    fn migrations_are_ordered_and_end_at_current_version() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as u32 + 1);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map(|m| m.version),
+            Some(CURRENT_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn it_stamps_fresh_dbs() {
+        let db = DB::in_memory();
+        assert!(db.plan_migrations().unwrap().is_empty());
+
+        assert!(db.migrate().unwrap().is_empty());
+        assert_eq!(db.schema_version().unwrap(), Some(CURRENT_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn it_migrates_unversioned_dbs() {
+        let db = DB::in_memory();
+        db.store_encodable("home_", "leaf_", &1u32).unwrap();
+
+        let planned = db.plan_migrations().unwrap();
+        assert_eq!(planned.len(), MIGRATIONS.len());
+        assert_eq!(db.schema_version().unwrap(), None);
+
+        assert_eq!(db.migrate().unwrap(), planned);
+        assert_eq!(db.schema_version().unwrap(), Some(CURRENT_SCHEMA_VERSION));
+        assert!(db.migrate().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_separates_leaf_indices() {
+        let db = DB::in_memory();
+        db.store_encodable("", SCHEMA_VERSION, &1u32).unwrap();
+        db.store_keyed_encodable("home_leaf_", &7u32, &H256::repeat_byte(1))
+            .unwrap();
+        db.store_keyed_encodable("home_leaf_", &(5u64 << 32 | 7), &H256::repeat_byte(2))
+            .unwrap();
+
+        let applied = db.migrate().unwrap();
+        assert_eq!(applied.len(), 2);
+        // One put and one delete, plus the version bump
+        assert_eq!(applied[0].writes, 3);
+
+        let by_index: Option<H256> = db.retrieve_keyed_decodable("home_leaf_", &7u32).unwrap();
+        assert_eq!(by_index, None);
+        let by_index: Option<H256> = db
+            .retrieve_keyed_decodable("home_leaf_index_", &7u32)
+            .unwrap();
+        assert_eq!(by_index, Some(H256::repeat_byte(1)));
+        let by_nonce: Option<H256> = db
+            .retrieve_keyed_decodable("home_leaf_", &(5u64 << 32 | 7))
+            .unwrap();
+        assert_eq!(by_nonce, Some(H256::repeat_byte(2)));
+    }
+
+    #[test]
+    fn it_migrates_in_bounded_batches() {
+        let db = DB::in_memory();
+        db.store_encodable("", SCHEMA_VERSION, &1u32).unwrap();
+        // One put and one delete per leaf
+        let leaves = MIGRATION_BATCH_SIZE as u32 / 2 + 1;
+        for i in 0..leaves {
+            db.store_keyed_encodable("home_leaf_", &i, &H256::from_low_u64_be(i as u64))
+                .unwrap();
+        }
+
+        let planned = db.plan_migrations().unwrap();
+        assert_eq!(planned[0].batches, 2);
+        assert_eq!(planned[0].writes, 2 * leaves as usize + 1);
+        assert_eq!(db.schema_version().unwrap(), Some(1));
+
+        assert_eq!(db.migrate().unwrap(), planned);
+        assert_eq!(db.schema_version().unwrap(), Some(CURRENT_SCHEMA_VERSION));
+        for i in 0..leaves {
+            let leaf: Option<H256> = db.retrieve_keyed_decodable("home_leaf_index_", &i).unwrap();
+            assert_eq!(leaf, Some(H256::from_low_u64_be(i as u64)));
+        }
+    }
+
+    #[test]
+    fn it_tags_signed_updates() {
+        let db = DB::in_memory();
+        db.store_encodable("", SCHEMA_VERSION, &2u32).unwrap();
+
+        let signed = SignedUpdate {
+            update: Update {
+                home_domain: 1000,
+                previous_root: H256::repeat_byte(1),
+                new_root: H256::repeat_byte(2),
+            },
+            signature: Signature {
+                r: 1.into(),
+                s: 2.into(),
+                v: 27,
+            },
+            scheme: SignatureScheme::Secp256k1,
+        };
+        let mut untagged = signed.to_vec();
+        untagged.pop();
+        let mut batch = DbBatch::new();
+        for prefix in ["home_update_", "home_updater_produced_update_"] {
+            batch.put(
+                [prefix.as_bytes(), H256::repeat_byte(1).as_bytes()].concat(),
+                &untagged,
+            );
+        }
+        db.write(batch).unwrap();
+        // Other values keyed by root are left alone
+        db.store_keyed_encodable(
+            "home_update_prev_root_",
+            &H256::repeat_byte(2),
+            &H256::repeat_byte(1),
+        )
+        .unwrap();
+
+        let applied = db.migrate().unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].writes, 3);
+
+        for prefix in ["home_update_", "home_updater_produced_update_"] {
+            let migrated: Option<SignedUpdate> = db
+                .retrieve_keyed_decodable(prefix, &H256::repeat_byte(1))
+                .unwrap();
+            assert_eq!(migrated, Some(signed.clone()));
+        }
+        let previous_root: Option<H256> = db
+            .retrieve_keyed_decodable("home_update_prev_root_", &H256::repeat_byte(2))
+            .unwrap();
+        assert_eq!(previous_root, Some(H256::repeat_byte(1)));
+    }
+
+    #[test]
+    fn it_rejects_newer_schema_versions() {
+        let db = DB::in_memory();
+        db.store_encodable("", SCHEMA_VERSION, &(CURRENT_SCHEMA_VERSION + 1))
+            .unwrap();
+
+        assert!(matches!(
+            db.migrate(),
+            Err(DbError::UnsupportedSchemaVersion(_, CURRENT_SCHEMA_VERSION))
+        ));
+        assert!(matches!(
+            db.check_schema_version(),
+            Err(DbError::UnsupportedSchemaVersion(_, CURRENT_SCHEMA_VERSION))
+        ));
+    }
+
+    #[test]
+    fn it_checks_schema_versions_without_migrating() {
+        let db = DB::in_memory();
+        db.check_schema_version().unwrap();
+
+        db.store_encodable("home_", "leaf_", &1u32).unwrap();
+        assert!(matches!(
+            db.check_schema_version(),
+            Err(DbError::OutdatedSchemaVersion(0, CURRENT_SCHEMA_VERSION))
+        ));
+
+        db.store_encodable("", SCHEMA_VERSION, &2u32).unwrap();
+        assert!(matches!(
+            db.check_schema_version(),
+            Err(DbError::OutdatedSchemaVersion(2, CURRENT_SCHEMA_VERSION))
+        ));
+
+        db.migrate().unwrap();
+        db.check_schema_version().unwrap();
+    }
+}
@@ -1,11 +1,21 @@
 use color_eyre::eyre::WrapErr;
-use rocksdb::{DBIterator, Options, DB as Rocks};
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tracing::info;
 
-/// Shared functionality surrounding use of rocksdb
+/// Iterators over db prefixes
 pub mod iterator;
 
+/// Key-value storage backends
+pub mod backend;
+use backend::{BatchOp, KvIterator, KvStore, MemoryStore, RocksStore};
+
+/// Schema versioning and migrations
+mod migrations;
+pub use migrations::*;
+
 /// Type-specific db operations
 mod typed_db;
 pub use typed_db::*;
@@ -14,11 +24,11 @@ use crate::{Decode, Encode, NomadError};
 
 #[derive(Debug, Clone)]
 /// A KV Store
-pub struct DB(Arc<Rocks>);
+pub struct DB(Arc<dyn KvStore>);
 
-impl From<Rocks> for DB {
-    fn from(rocks: Rocks) -> Self {
-        Self(Arc::new(rocks))
+impl From<rocksdb::DB> for DB {
+    fn from(rocks: rocksdb::DB) -> Self {
+        Self::new(RocksStore::from(rocks))
     }
 }
 
@@ -28,20 +38,136 @@ pub enum DbError {
     /// Rocks DB Error
     #[error("{0}")]
     RockError(#[from] rocksdb::Error),
+    /// Sled Error
+    #[cfg(feature = "sled")]
+    #[error("{0}")]
+    SledError(#[from] sled::Error),
     /// Nomad Error
     #[error("{0}")]
     NomadError(#[from] NomadError),
+    /// DB was written by a newer version of the agents
+    #[error("DB schema version {0} is newer than supported version {1}")]
+    UnsupportedSchemaVersion(u32, u32),
+    /// DB was opened without migrating it, and has pending migrations
+    #[error("DB schema version {0} is older than current version {1}. Run `nomad-cli db migrate`")]
+    OutdatedSchemaVersion(u32, u32),
 }
 
 type Result<T> = std::result::Result<T, DbError>;
 
+fn prefixed_key(prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Vec<u8> {
+    let mut buf = vec![];
+    buf.extend(prefix.as_ref());
+    buf.extend(key.as_ref());
+    buf
+}
+
+/// A set of writes, committed atomically by `DB::write`
+#[derive(Debug, Default)]
+pub struct DbBatch(Vec<BatchOp>);
+
+impl DbBatch {
+    /// Instantiate an empty batch
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of writes in the batch
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// True if the batch has no writes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Prefix a key and add a write to the batch
+    fn prefix_store(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) {
+        self.put(prefixed_key(prefix, key), value)
+    }
+
+    /// Add a write of `value` at the raw, unprefixed `key`
+    pub fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        self.0
+            .push(BatchOp::Put(key.as_ref().to_vec(), value.as_ref().to_vec()))
+    }
+
+    /// Add a delete of the raw, unprefixed `key`
+    pub fn delete(&mut self, key: impl AsRef<[u8]>) {
+        self.0.push(BatchOp::Delete(key.as_ref().to_vec()))
+    }
+
+    /// Store any encodeable
+    pub fn store_encodable<V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+        value: &V,
+    ) {
+        self.prefix_store(prefix, key, value.to_vec())
+    }
+
+    /// Store any encodeable
+    pub fn store_keyed_encodable<K: Encode, V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: &K,
+        value: &V,
+    ) {
+        self.store_encodable(prefix, key.to_vec(), value)
+    }
+
+    /// Delete the value stored under `prefix` and `key`
+    pub fn delete_prefixed(&mut self, prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) {
+        self.delete(prefixed_key(prefix, key))
+    }
+
+    /// Delete the value stored under `prefix` and encodable `key`
+    pub fn delete_keyed<K: Encode>(&mut self, prefix: impl AsRef<[u8]>, key: &K) {
+        self.delete_prefixed(prefix, key.to_vec())
+    }
+}
+
 impl DB {
-    /// Opens db at `db_path` and creates if missing
-    #[tracing::instrument(err)]
-    pub fn from_path(db_path: &str) -> color_eyre::Result<DB> {
+    /// Instantiate a DB on top of `store`
+    pub fn new(store: impl KvStore + 'static) -> Self {
+        Self(Arc::new(store))
+    }
+
+    /// Instantiate an empty in-memory DB
+    pub fn in_memory() -> Self {
+        Self::new(MemoryStore::new())
+    }
+
+    /// Resolve `db_path` relative to the working directory
+    fn resolve_path(db_path: &str) -> color_eyre::Result<PathBuf> {
         // Canonicalize ensures existence, so we have to do that, then extend
         let mut path = Path::new(".").canonicalize()?;
         path.extend(&[db_path]);
+        Ok(path)
+    }
+
+    /// Opens db at `db_path`, creating it if missing, and applies pending
+    /// schema migrations
+    #[tracing::instrument(err)]
+    pub fn from_path(db_path: &str) -> color_eyre::Result<DB> {
+        let db = Self::from_path_unmigrated(db_path)?;
+        db.migrate()
+            .wrap_err(format!("Failed to migrate db at {}", db_path))?;
+        Ok(db)
+    }
+
+    /// Opens db at `db_path` and creates if missing, without applying
+    /// schema migrations
+    #[tracing::instrument(err)]
+    pub fn from_path_unmigrated(db_path: &str) -> color_eyre::Result<DB> {
+        let path = Self::resolve_path(db_path)?;
 
         match path.is_dir() {
             true => info!(
@@ -51,25 +177,98 @@ impl DB {
             false => info!("Creating db at {path}", path = path.to_str().unwrap()),
         }
 
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
-
-        Rocks::open(&opts, &path)
+        RocksStore::open(&path)
             .wrap_err(format!(
                 "Failed to open db path {}, canonicalized as {:?}",
                 db_path, path
             ))
-            .map(Into::into)
+            .map(Self::new)
+    }
+
+    /// Opens existing db at `db_path` read-only. Can be used while an agent
+    /// holds the db open, but does not see the agent's later writes. Errors
+    /// if the db is not at the current schema version
+    #[tracing::instrument(err)]
+    pub fn from_path_read_only(db_path: &str) -> color_eyre::Result<DB> {
+        let db = Self::from_path_read_only_unmigrated(db_path)?;
+        db.check_schema_version()
+            .wrap_err(format!("Failed to open db at {}", db_path))?;
+        Ok(db)
+    }
+
+    /// Opens existing db at `db_path` read-only, at whatever schema version
+    /// it is at
+    #[tracing::instrument(err)]
+    pub fn from_path_read_only_unmigrated(db_path: &str) -> color_eyre::Result<DB> {
+        let path = Self::resolve_path(db_path)?;
+        info!(
+            "Opening existing db at {path} read-only",
+            path = path.to_str().unwrap()
+        );
+
+        RocksStore::open_read_only(&path)
+            .wrap_err(format!(
+                "Failed to open db path {} read-only, canonicalized as {:?}",
+                db_path, path
+            ))
+            .map(Self::new)
+    }
+
+    /// Opens existing db at `db_path` as a secondary, which follows the
+    /// agent holding the db open via `DB::catch_up`. The secondary keeps its
+    /// own logs at `secondary_path`. Errors if the db is not at the current
+    /// schema version
+    #[tracing::instrument(err)]
+    pub fn from_path_secondary(db_path: &str, secondary_path: &str) -> color_eyre::Result<DB> {
+        let path = Self::resolve_path(db_path)?;
+        info!(
+            "Opening existing db at {path} as secondary",
+            path = path.to_str().unwrap()
+        );
+
+        let db = RocksStore::open_secondary(&path, secondary_path)
+            .wrap_err(format!(
+                "Failed to open db path {} as secondary, canonicalized as {:?}",
+                db_path, path
+            ))
+            .map(Self::new)?;
+        db.check_schema_version()
+            .wrap_err(format!("Failed to open db at {}", db_path))?;
+        Ok(db)
+    }
+
+    /// Opens sled db at `db_path`, creating it if missing, and applies
+    /// pending schema migrations
+    #[cfg(feature = "sled")]
+    #[tracing::instrument(err)]
+    pub fn from_sled_path(db_path: &str) -> color_eyre::Result<DB> {
+        let path = Self::resolve_path(db_path)?;
+        info!("Opening sled db at {path}", path = path.to_str().unwrap());
+
+        let db = backend::SledStore::open(&path)
+            .wrap_err(format!(
+                "Failed to open sled db path {}, canonicalized as {:?}",
+                db_path, path
+            ))
+            .map(Self::new)?;
+        db.migrate()
+            .wrap_err(format!("Failed to migrate db at {}", db_path))?;
+        Ok(db)
+    }
+
+    /// Catch up with writes made by the primary, if this is a secondary db
+    pub fn catch_up(&self) -> Result<()> {
+        self.0.catch_up()
     }
 
     /// Store a value in the DB
     fn _store(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
-        Ok(self.0.put(key, value)?)
+        self.0.put(key.as_ref(), value.as_ref())
     }
 
     /// Retrieve a value from the DB
     fn _retrieve(&self, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>> {
-        Ok(self.0.get(key)?)
+        self.0.get(key.as_ref())
     }
 
     /// Prefix a key and store in the DB
@@ -79,10 +278,7 @@ impl DB {
         key: impl AsRef<[u8]>,
         value: impl AsRef<[u8]>,
     ) -> Result<()> {
-        let mut buf = vec![];
-        buf.extend(prefix.as_ref());
-        buf.extend(key.as_ref());
-        self._store(buf, value)
+        self._store(prefixed_key(prefix, key), value)
     }
 
     /// Prefix the key and retrieve
@@ -91,10 +287,12 @@ impl DB {
         prefix: impl AsRef<[u8]>,
         key: impl AsRef<[u8]>,
     ) -> Result<Option<Vec<u8>>> {
-        let mut buf = vec![];
-        buf.extend(prefix.as_ref());
-        buf.extend(key.as_ref());
-        self._retrieve(buf)
+        self._retrieve(prefixed_key(prefix, key))
+    }
+
+    /// Atomically commit every write in `batch`
+    pub fn write(&self, batch: DbBatch) -> Result<()> {
+        self.0.write(batch.0)
     }
 
     /// Store any encodeable
@@ -139,7 +337,17 @@ impl DB {
     }
 
     /// Get prefix db iterator for `prefix`
-    pub fn prefix_iterator(&self, prefix: impl AsRef<[u8]>) -> DBIterator {
-        self.0.prefix_iterator(prefix)
+    pub fn prefix_iterator(&self, prefix: impl AsRef<[u8]>) -> KvIterator<'_> {
+        self.0.prefix_iter(prefix.as_ref())
+    }
+
+    /// Get db iterator for `prefix`, starting at the key `prefix` + `from`
+    pub fn prefix_iterator_from(
+        &self,
+        prefix: impl AsRef<[u8]>,
+        from: impl AsRef<[u8]>,
+    ) -> KvIterator<'_> {
+        let prefix = prefix.as_ref();
+        self.0.iter_from(prefix, &prefixed_key(prefix, from))
     }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    db::{DbError, DB},
+    db::{iterator::KeyedIterator, DbBatch, DbError, DB},
     Decode, Encode,
 };
 use color_eyre::Result;
@@ -19,6 +19,67 @@ impl AsRef<DB> for TypedDB {
     }
 }
 
+fn full_prefix(entity: &str, prefix: impl AsRef<[u8]>) -> Vec<u8> {
+    let mut full_prefix = vec![];
+    full_prefix.extend(entity.as_bytes());
+    full_prefix.extend("_".as_bytes());
+    full_prefix.extend(prefix.as_ref());
+    full_prefix
+}
+
+/// A set of writes to a `TypedDB`, committed atomically by `TypedDB::write`
+#[derive(Debug)]
+pub struct TypedBatch {
+    entity: String,
+    batch: DbBatch,
+}
+
+impl TypedBatch {
+    /// Number of writes in the batch
+    pub fn len(&self) -> usize {
+        self.batch.len()
+    }
+
+    /// True if the batch has no writes
+    pub fn is_empty(&self) -> bool {
+        self.batch.is_empty()
+    }
+
+    /// Store encodable value
+    pub fn store_encodable<V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+        value: &V,
+    ) {
+        self.batch
+            .store_encodable(full_prefix(&self.entity, prefix), key, value)
+    }
+
+    /// Store encodable kv pair
+    pub fn store_keyed_encodable<K: Encode, V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: &K,
+        value: &V,
+    ) {
+        self.batch
+            .store_keyed_encodable(full_prefix(&self.entity, prefix), key, value)
+    }
+
+    /// Delete value
+    pub fn delete(&mut self, prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) {
+        self.batch
+            .delete_prefixed(full_prefix(&self.entity, prefix), key)
+    }
+
+    /// Delete value given encodable key
+    pub fn delete_keyed<K: Encode>(&mut self, prefix: impl AsRef<[u8]>, key: &K) {
+        self.batch
+            .delete_keyed(full_prefix(&self.entity, prefix), key)
+    }
+}
+
 impl TypedDB {
     /// Instantiate new `TypedDB`
     pub fn new(entity: String, db: DB) -> Self {
@@ -26,11 +87,20 @@ impl TypedDB {
     }
 
     fn full_prefix(&self, prefix: impl AsRef<[u8]>) -> Vec<u8> {
-        let mut full_prefix = vec![];
-        full_prefix.extend(self.entity.as_ref() as &[u8]);
-        full_prefix.extend("_".as_bytes());
-        full_prefix.extend(prefix.as_ref());
-        full_prefix
+        full_prefix(&self.entity, prefix)
+    }
+
+    /// Start a batch of writes to this entity
+    pub fn batch(&self) -> TypedBatch {
+        TypedBatch {
+            entity: self.entity.clone(),
+            batch: DbBatch::new(),
+        }
+    }
+
+    /// Atomically commit every write in `batch`
+    pub fn write(&self, batch: TypedBatch) -> Result<(), DbError> {
+        self.db.write(batch.batch)
     }
 
     /// Store encodable value
@@ -73,4 +143,19 @@ impl TypedDB {
         self.db
             .retrieve_keyed_decodable(self.full_prefix(prefix), key)
     }
+
+    /// Iterate over the decoded kv pairs under `prefix`, in key order,
+    /// starting at `from` if given
+    pub fn keyed_iterator<K: Encode + Decode, V: Decode>(
+        &self,
+        prefix: impl AsRef<[u8]>,
+        from: Option<&K>,
+    ) -> KeyedIterator<'_, K, V> {
+        let prefix = self.full_prefix(prefix);
+        let iter = match from {
+            Some(from) => self.db.prefix_iterator_from(&prefix, from.to_vec()),
+            None => self.db.prefix_iterator(&prefix),
+        };
+        KeyedIterator::new(iter, prefix.len())
+    }
 }
@@ -8,8 +8,13 @@
 #![forbid(unsafe_code)]
 #![forbid(where_clauses_object_safety)]
 
+// Lets `nomad_derive` expansions name `nomad_core` from inside this crate
+extern crate self as nomad_core;
+
 pub use accumulator;
 
+pub use nomad_derive::{Decode, Encode};
+
 /// AWS global state and init
 pub mod aws;
 
@@ -84,4 +89,34 @@ pub enum NomadError {
     /// IO error from Read/Write usage
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    /// Message body is not a BridgeRouter message of a known type
+    #[error("Unknown BridgeRouter message type: {0}")]
+    UnknownBridgeMessageType(u8),
+    /// Message body continues after a complete message
+    #[error("Message body has {0} unexpected trailing bytes")]
+    TrailingBytes(usize),
+    /// Message body is not a GovernanceRouter message of a known type
+    #[error("Unknown GovernanceRouter message type: {0}")]
+    UnknownGovernanceMessageType(u8),
+    /// A GovernanceRouter batch has more calls than can be encoded
+    #[error("Governance batch has {0} calls, more than the maximum of 255")]
+    TooManyCalls(usize),
+    /// Signer address cannot be recovered from a signature of this scheme
+    #[error("Cannot recover signer of a {0:?} signature")]
+    UnrecoverableSignature(SignatureScheme),
+    /// Encoded attestation has a signature scheme tag of no known scheme
+    #[error("Unknown signature scheme tag: {0}")]
+    UnknownSignatureScheme(u8),
+    /// Signature does not have the form of its tagged scheme
+    #[error("Signature with v {1} is not a {0:?} signature")]
+    MismatchedSignatureScheme(SignatureScheme, u64),
+    /// Signature was not made by the expected signer
+    #[error("Invalid {0:?} signature for signer {1:?}")]
+    InvalidSignature(SignatureScheme, NomadIdentifier),
+    /// An encoded `Option` has a tag other than 0 or 1
+    #[error("Invalid option tag: {0}")]
+    InvalidOptionTag(u8),
+    /// Attester secret could not be parsed
+    #[error("Invalid attester secret: {0}")]
+    InvalidSecret(String),
 }
@@ -5,11 +5,12 @@ use crate::{
     },
     test_utils::find_test_fixtures,
     utils::{destination_and_nonce, home_domain_hash},
-    FailureNotification, NomadMessage, Update,
+    FailureNotification, NomadDomain, NomadMessage, Update,
 };
 use ethers::{
     core::types::{H160, H256},
     signers::Signer,
+    types::transaction::eip712::Eip712,
 };
 use hex::FromHex;
 
@@ -239,4 +240,127 @@ pub mod output_functions {
             .unwrap()
             .block_on(t)
     }
+
+    /// Outputs EIP-712 signed update test cases in /vector/typedUpdate.json
+    pub fn output_typed_updates() {
+        let t = async {
+            let signer: ethers::signers::LocalWallet =
+                "1111111111111111111111111111111111111111111111111111111111111111"
+                    .parse()
+                    .unwrap();
+
+            let domain = NomadDomain::new(
+                1,
+                H160::from_str("0x3333333333333333333333333333333333333333").unwrap(),
+            );
+
+            let mut test_cases: Vec<Value> = Vec::new();
+
+            // test suite
+            for i in 1..=3 {
+                let update = Update {
+                    home_domain: 1000,
+                    new_root: H256::repeat_byte(i + 1),
+                    previous_root: H256::repeat_byte(i),
+                };
+                let signed_update = update
+                    .sign_typed_with(domain, &signer)
+                    .await
+                    .expect("!sign_typed_with");
+
+                test_cases.push(json!({
+                    "chainId": domain.chain_id,
+                    "verifyingContract": domain.verifying_contract,
+                    "domainSeparator": H256::from(domain.eip712_domain().separator()),
+                    "homeDomain": update.home_domain,
+                    "oldRoot": update.previous_root,
+                    "newRoot": update.new_root,
+                    "structHash": H256::from(update.typed(domain).struct_hash().unwrap()),
+                    "digest": update.eip712_hash(domain),
+                    "signature": signed_update.signature,
+                    "signer": signer.address(),
+                }))
+            }
+
+            let json = json!(test_cases).to_string();
+
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(find_test_fixtures("typedUpdate.json"))
+                .expect("Failed to open/create file");
+
+            file.write_all(json.as_bytes())
+                .expect("Failed to write to file");
+        };
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(t)
+    }
+
+    /// Outputs EIP-712 signed failure notification test cases in
+    /// /vector/typedFailure.json
+    pub fn output_typed_failure_notifications() {
+        let t = async {
+            let signer: ethers::signers::LocalWallet =
+                "1111111111111111111111111111111111111111111111111111111111111111"
+                    .parse()
+                    .unwrap();
+
+            let updater: ethers::signers::LocalWallet =
+                "2222222222222222222222222222222222222222222222222222222222222222"
+                    .parse()
+                    .unwrap();
+
+            let domain = NomadDomain::new(
+                1,
+                H160::from_str("0x3333333333333333333333333333333333333333").unwrap(),
+            );
+
+            // `home_domain` MUST BE 2000 to match home_domain domain of
+            // XAppConnectionManager test suite
+            let notification = FailureNotification {
+                home_domain: 2000,
+                updater: updater.address().into(),
+            };
+            let signed_failure = notification
+                .sign_typed_with(domain, &signer)
+                .await
+                .expect("!sign_typed_with");
+
+            let signed_json = json!({
+                "chainId": domain.chain_id,
+                "verifyingContract": domain.verifying_contract,
+                "domainSeparator": H256::from(domain.eip712_domain().separator()),
+                "domain": notification.home_domain,
+                "updater": notification.updater,
+                "structHash": H256::from(notification.typed(domain).struct_hash().unwrap()),
+                "digest": notification.eip712_hash(domain),
+                "signature": signed_failure.signature,
+                "signer": signer.address()
+            });
+
+            let json = json!(vec!(signed_json)).to_string();
+
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(find_test_fixtures("typedFailure.json"))
+                .expect("Failed to open/create file");
+
+            file.write_all(json.as_bytes())
+                .expect("Failed to write to file");
+        };
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(t)
+    }
 }
@@ -1,5 +1,6 @@
 use crate::NomadError;
 use ethers::prelude::{Signature, SignatureError, H256};
+use nomad_types::NomadIdentifier;
 use std::convert::TryFrom;
 
 /// Simple trait for types with a canonical encoding
@@ -73,6 +74,25 @@ impl Decode for H256 {
     }
 }
 
+impl Encode for NomadIdentifier {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        H256::write_to(self, writer)
+    }
+}
+
+impl Decode for NomadIdentifier {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+        Self: Sized,
+    {
+        Ok(H256::read_from(reader)?.into())
+    }
+}
+
 impl Encode for u32 {
     fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
     where
@@ -117,7 +137,7 @@ impl Decode for u64 {
     }
 }
 
-impl<const N: usize> Encode for accumulator::Proof<N> {
+impl<const N: usize, H: accumulator::Hasher> Encode for accumulator::Proof<N, H> {
     fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
     where
         W: std::io::Write,
@@ -131,7 +151,7 @@ impl<const N: usize> Encode for accumulator::Proof<N> {
     }
 }
 
-impl<const N: usize> Decode for accumulator::Proof<N> {
+impl<const N: usize, H: accumulator::Hasher> Decode for accumulator::Proof<N, H> {
     fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
     where
         R: std::io::Read,
@@ -149,7 +169,7 @@ impl<const N: usize> Decode for accumulator::Proof<N> {
 
         let index = u64::from_be_bytes(index_bytes) as usize;
 
-        Ok(Self { leaf, index, path })
+        Ok(Self::new(leaf, index, path))
     }
 }
 
@@ -178,3 +198,127 @@ impl Decode for bool {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Decode, Encode, FailureNotification, NomadMessage, RawCommittedMessage, Update, UpdateMeta,
+    };
+    use ethers::prelude::H160;
+
+    #[derive(Debug, PartialEq, Encode, Decode)]
+    struct Tagged {
+        index: u32,
+        root: Option<H256>,
+        rest: Vec<u8>,
+    }
+
+    fn roundtrip<T: Encode + Decode + PartialEq + std::fmt::Debug>(value: &T, expected: &str) {
+        let encoded = value.to_vec();
+        assert_eq!(hex::encode(&encoded), expected);
+        assert_eq!(value.write_to(&mut vec![]).unwrap(), encoded.len());
+        assert_eq!(&T::read_from(&mut encoded.as_slice()).unwrap(), value);
+    }
+
+    #[test]
+    fn it_encodes_derived_types_byte_identically() {
+        // the message of the `message.json` vector
+        let message = NomadMessage {
+            origin: 1000,
+            sender: H160::repeat_byte(0x11).into(),
+            nonce: 1,
+            destination: 2000,
+            recipient: H160::repeat_byte(0x22).into(),
+            body: vec![0x12, 0x34],
+        };
+        roundtrip(
+            &message,
+            "000003e8\
+             0000000000000000000000001111111111111111111111111111111111111111\
+             00000001\
+             000007d0\
+             0000000000000000000000002222222222222222222222222222222222222222\
+             1234",
+        );
+
+        let raw = RawCommittedMessage {
+            leaf_index: 3,
+            committed_root: H256::repeat_byte(0x33),
+            message: message.to_vec(),
+        };
+        roundtrip(
+            &raw,
+            &format!(
+                "00000003{}{}",
+                hex::encode(H256::repeat_byte(0x33)),
+                hex::encode(message.to_vec())
+            ),
+        );
+
+        let update = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        };
+        roundtrip(
+            &update,
+            &format!(
+                "000003e8{}{}",
+                hex::encode(H256::repeat_byte(1)),
+                hex::encode(H256::repeat_byte(2))
+            ),
+        );
+
+        let notification = FailureNotification {
+            home_domain: 1000,
+            updater: H160::repeat_byte(0x11).into(),
+        };
+        roundtrip(
+            &notification,
+            "000003e8\
+             0000000000000000000000001111111111111111111111111111111111111111",
+        );
+
+        // `None` timestamps are encoded as 0
+        roundtrip(
+            &UpdateMeta {
+                block_number: 5,
+                timestamp: None,
+            },
+            "00000000000000050000000000000000",
+        );
+        roundtrip(
+            &UpdateMeta {
+                block_number: 5,
+                timestamp: Some(7),
+            },
+            "00000000000000050000000000000007",
+        );
+    }
+
+    #[test]
+    fn it_tags_derived_options() {
+        roundtrip(
+            &Tagged {
+                index: 1,
+                root: None,
+                rest: vec![],
+            },
+            "0000000100",
+        );
+        roundtrip(
+            &Tagged {
+                index: 1,
+                root: Some(H256::repeat_byte(0xff)),
+                rest: vec![0xab],
+            },
+            &format!("0000000101{}ab", hex::encode(H256::repeat_byte(0xff))),
+        );
+
+        assert!(matches!(
+            Tagged::read_from(&mut [0u8, 0, 0, 1, 2].as_ref()),
+            Err(NomadError::InvalidOptionTag(2))
+        ));
+    }
+}
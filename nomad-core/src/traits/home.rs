@@ -10,16 +10,19 @@ use ethers::{
     core::types::{H256, U256},
     utils::keccak256,
 };
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 /// A Stamped message that has been committed at some leaf index
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
 pub struct RawCommittedMessage {
     /// The index at which the message is committed
     pub leaf_index: u32,
     /// The home's current root when the message was committed.
     pub committed_root: H256,
     /// The fully detailed message that was committed
+    #[serde(with = "crate::utils::hex_bytes")]
     pub message: Vec<u8>,
 }
 
@@ -33,44 +36,37 @@ impl RawCommittedMessage {
     }
 }
 
-impl Encode for RawCommittedMessage {
-    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
-    where
-        W: std::io::Write,
-    {
-        writer.write_all(&self.leaf_index.to_be_bytes())?;
-        writer.write_all(self.committed_root.as_ref())?;
-        writer.write_all(&self.message)?;
-        Ok(4 + 32 + self.message.len())
-    }
+/// Metadata stored about a committed message
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageMeta {
+    /// Block number
+    pub block_number: u64,
+    /// Block hash
+    pub block_hash: H256,
+    /// Hash of the dispatching transaction (optional because not every chain
+    /// exposes it alongside events)
+    pub tx_hash: Option<H256>,
+    /// Index of the dispatch event within its block
+    pub log_index: u64,
+    /// Timestamp seconds (optional because fetching timestamp is fallible)
+    pub timestamp: Option<u64>,
 }
 
-impl Decode for RawCommittedMessage {
-    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
-    where
-        R: std::io::Read,
-        Self: Sized,
-    {
-        let mut idx = [0u8; 4];
-        reader.read_exact(&mut idx)?;
-
-        let mut hash = [0u8; 32];
-        reader.read_exact(&mut hash)?;
-
-        let mut message = vec![];
-        reader.read_to_end(&mut message)?;
-
-        Ok(Self {
-            leaf_index: u32::from_be_bytes(idx),
-            committed_root: hash.into(),
-            message,
-        })
-    }
+/// A raw committed message with metadata
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawCommittedMessageWithMeta {
+    /// Raw committed message
+    pub raw_message: RawCommittedMessage,
+    /// Metadata
+    pub metadata: MessageMeta,
 }
 
 // ember: tracingify these across usage points
 /// A Stamped message that has been committed at some leaf index
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CommittedMessage {
     /// The index at which the message is committed
     pub leaf_index: u32,
@@ -123,6 +119,14 @@ pub trait Home: Common + Send + Sync + std::fmt::Debug {
     /// Dispatch a message.
     async fn dispatch(&self, message: &Message) -> Result<TxOutcome, <Self as Common>::Error>;
 
+    /// Dispatch a message, and return it as committed by the home, with its
+    /// nonce, leaf index and committed root. These are read from the
+    /// `Dispatch` event emitted by the dispatch tx.
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, <Self as Common>::Error>;
+
     /// Return length of queue.
     async fn queue_length(&self) -> Result<U256, <Self as Common>::Error>;
 
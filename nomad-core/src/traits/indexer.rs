@@ -11,7 +11,7 @@ use async_trait::async_trait;
 use color_eyre::Result;
 use std::error::Error as StdError;
 
-use crate::{RawCommittedMessage, SignedUpdateWithMeta};
+use crate::{RawCommittedMessageWithMeta, SignedUpdateWithMeta};
 
 /// Interface for Common contract indexer. Interface that allows for other
 /// entities to retrieve chain-specific data from a home or replica.
@@ -35,10 +35,11 @@ pub trait CommonIndexer: Send + Sync + std::fmt::Debug {
 /// entities to retrieve chain-specific data from a home.
 #[async_trait]
 pub trait HomeIndexer: CommonIndexer + Send + Sync + std::fmt::Debug {
-    /// Fetch list of messages between blocks `from` and `to`.
+    /// Fetch list of messages and their metadata between blocks `from` and
+    /// `to`.
     async fn fetch_sorted_messages(
         &self,
         _from: u32,
         _to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error>;
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error>;
 }
@@ -7,7 +7,8 @@ mod xapp;
 
 use async_trait::async_trait;
 use color_eyre::Result;
-use ethers::core::types::H256;
+use ethers::core::types::{H256, U256};
+use serde::{Deserialize, Serialize};
 use std::{error::Error as StdError, fmt::Display};
 
 use crate::{db::DbError, SignedUpdate};
@@ -46,12 +47,67 @@ impl Display for DoubleUpdate {
     }
 }
 
-/// The result of a transaction
-#[derive(Debug, Clone, Copy)]
+/// How a transaction was submitted to its chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TxSubmitterKind {
+    /// Signed and submitted by the agent
+    Local,
+    /// Relayed by the Gelato relay service
+    Gelato,
+}
+
+/// Whether an included transaction succeeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TxStatus {
+    /// The transaction succeeded
+    Success,
+    /// The transaction was included, but reverted
+    Reverted,
+}
+
+impl Default for TxStatus {
+    fn default() -> Self {
+        TxStatus::Success
+    }
+}
+
+/// The result of a transaction. Fields a chain or submitter does not expose
+/// are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TxOutcome {
     /// The txid
     pub txid: H256,
-    // TODO: more? What can be abstracted across all chains?
+    /// Number of the block including the transaction
+    pub block_number: Option<u64>,
+    /// Hash of the block including the transaction
+    pub block_hash: Option<H256>,
+    /// Success or revert
+    pub status: TxStatus,
+    /// Gas used by the transaction, or its weight on substrate chains
+    pub gas_used: Option<U256>,
+    /// Fee paid for inclusion, in the chain's native token
+    pub fee: Option<U256>,
+    /// How the transaction was submitted. `None` if the outcome was looked
+    /// up by txid
+    pub submitter: Option<TxSubmitterKind>,
+}
+
+impl TxOutcome {
+    /// Instantiate an outcome carrying only a txid
+    pub fn new(txid: H256) -> Self {
+        Self {
+            txid,
+            ..Default::default()
+        }
+    }
+
+    /// True if the transaction succeeded
+    pub fn is_success(&self) -> bool {
+        self.status == TxStatus::Success
+    }
 }
 
 /// Interface for attributes shared by Home and Replica
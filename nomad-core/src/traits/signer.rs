@@ -1,7 +1,12 @@
 use async_trait::async_trait;
-use color_eyre::Result;
-use ethers::prelude::{Signature, Signer};
-use nomad_xyz_configuration::agent::SignerConf;
+use color_eyre::{eyre::bail, Result};
+use ethers::prelude::{Signature, Signer, H256, U256};
+use nomad_types::NomadIdentifier;
+use nomad_xyz_configuration::agent::{SignerConf, SubstrateKeyScheme};
+use serde::{Deserialize, Serialize};
+use subxt::ext::sp_core::{ed25519, sr25519, Pair};
+
+use crate::{Decode, Encode, NomadError};
 
 /// Extension of ethers signer trait
 #[async_trait]
@@ -19,6 +24,230 @@ pub trait SignerExt: Signer {
 
 impl<T> SignerExt for T where T: Signer {}
 
+/// The scheme an attestation was signed with. Encoded attestations carry it
+/// as a tag byte after the signature. The tags must not be renumbered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum SignatureScheme {
+    /// Recoverable secp256k1 personal-sign signature
+    #[default]
+    Secp256k1 = 0,
+    /// Schnorrkel signature over the signing hash
+    Sr25519 = 1,
+    /// Ed25519 signature over the signing hash
+    Ed25519 = 2,
+}
+
+impl TryFrom<u8> for SignatureScheme {
+    type Error = NomadError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SignatureScheme::Secp256k1),
+            1 => Ok(SignatureScheme::Sr25519),
+            2 => Ok(SignatureScheme::Ed25519),
+            _ => Err(NomadError::UnknownSignatureScheme(value)),
+        }
+    }
+}
+
+impl Encode for SignatureScheme {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&[*self as u8])?;
+        Ok(1)
+    }
+}
+
+impl Decode for SignatureScheme {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+        tag[0].try_into()
+    }
+}
+
+impl SignatureScheme {
+    /// Check that `signature` has the form of a signature of this scheme.
+    ///
+    /// secp256k1 signatures carry a recovery id `v` of 27 or 28, as the
+    /// contracts require. sr25519 and ed25519 signatures have no recovery
+    /// id, so their `v` must be 0.
+    pub fn check(&self, signature: &Signature) -> Result<(), NomadError> {
+        let valid = match self {
+            SignatureScheme::Secp256k1 => signature.v == 27 || signature.v == 28,
+            SignatureScheme::Sr25519 | SignatureScheme::Ed25519 => signature.v == 0,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(NomadError::MismatchedSignatureScheme(*self, signature.v))
+        }
+    }
+
+    /// Wrap a 64-byte sr25519 or ed25519 signature in an ethers `Signature`
+    fn wrap(bytes: &[u8; 64]) -> Signature {
+        Signature {
+            r: U256::from_big_endian(&bytes[..32]),
+            s: U256::from_big_endian(&bytes[32..]),
+            v: 0,
+        }
+    }
+
+    /// Verify that `signature` over `signing_hash` was made by `signer`.
+    ///
+    /// secp256k1 signatures are checked against the EIP-191 prefixed hash,
+    /// and `signer` must be an Ethereum address. sr25519 and ed25519
+    /// signatures are checked against the raw hash, and `signer` is the
+    /// 32-byte public key.
+    pub(crate) fn verify(
+        &self,
+        signature: &Signature,
+        signing_hash: H256,
+        signer: NomadIdentifier,
+    ) -> Result<(), NomadError> {
+        let invalid = || NomadError::InvalidSignature(*self, signer);
+        let mut bytes = [0u8; 64];
+        signature.r.to_big_endian(&mut bytes[..32]);
+        signature.s.to_big_endian(&mut bytes[32..]);
+
+        let valid = match self {
+            SignatureScheme::Secp256k1 => {
+                let address = signer.as_ethereum_address().map_err(|_| invalid())?;
+                return Ok(signature.verify(ethers::utils::hash_message(signing_hash), address)?);
+            }
+            SignatureScheme::Sr25519 => sr25519::Pair::verify(
+                &sr25519::Signature::from_raw(bytes),
+                signing_hash,
+                &sr25519::Public::from_raw(signer.into()),
+            ),
+            SignatureScheme::Ed25519 => ed25519::Pair::verify(
+                &ed25519::Signature::from_raw(bytes),
+                signing_hash,
+                &ed25519::Public::from_raw(signer.into()),
+            ),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// A signer of attestations: updates and failure notifications.
+///
+/// Every ethers `Signer` is a secp256k1 attester. Substrate homes may use
+/// sr25519 or ed25519 keys via `SubstrateAttester`.
+#[async_trait]
+pub trait Attester: Send + Sync {
+    /// Error type
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// The scheme this attester signs with
+    fn scheme(&self) -> SignatureScheme;
+
+    /// The identity attestations are verified against. An Ethereum address
+    /// for secp256k1, and the public key otherwise
+    fn identifier(&self) -> NomadIdentifier;
+
+    /// Sign the signing hash of an attestation
+    async fn sign_attestation(&self, signing_hash: H256) -> Result<Signature, Self::Error>;
+}
+
+#[async_trait]
+impl<T> Attester for T
+where
+    T: Signer,
+{
+    type Error = <T as Signer>::Error;
+
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::Secp256k1
+    }
+
+    fn identifier(&self) -> NomadIdentifier {
+        self.address().into()
+    }
+
+    async fn sign_attestation(&self, signing_hash: H256) -> Result<Signature, Self::Error> {
+        self.sign_message_without_eip_155(signing_hash).await
+    }
+}
+
+/// Local sr25519 or ed25519 attester for Substrate homes
+#[derive(Clone)]
+pub enum SubstrateAttester {
+    /// sr25519 keypair
+    Sr25519(sr25519::Pair),
+    /// ed25519 keypair
+    Ed25519(ed25519::Pair),
+}
+
+impl std::fmt::Debug for SubstrateAttester {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SubstrateAttester {{ {:?}: {:?} }}",
+            self.scheme(),
+            self.identifier()
+        )
+    }
+}
+
+impl SubstrateAttester {
+    /// Instantiate an attester for `scheme` from a secret URI or 0x-prefixed
+    /// hex seed
+    pub fn from_string(scheme: SignatureScheme, secret: &str) -> Result<Self, NomadError> {
+        let invalid = |e| NomadError::InvalidSecret(format!("{:?}", e));
+        match scheme {
+            SignatureScheme::Secp256k1 => Err(NomadError::InvalidSecret(
+                "secp256k1 attesters are ethers signers".to_owned(),
+            )),
+            SignatureScheme::Sr25519 => Ok(SubstrateAttester::Sr25519(
+                sr25519::Pair::from_string(secret, None).map_err(invalid)?,
+            )),
+            SignatureScheme::Ed25519 => Ok(SubstrateAttester::Ed25519(
+                ed25519::Pair::from_string(secret, None).map_err(invalid)?,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Attester for SubstrateAttester {
+    type Error = std::convert::Infallible;
+
+    fn scheme(&self) -> SignatureScheme {
+        match self {
+            SubstrateAttester::Sr25519(_) => SignatureScheme::Sr25519,
+            SubstrateAttester::Ed25519(_) => SignatureScheme::Ed25519,
+        }
+    }
+
+    fn identifier(&self) -> NomadIdentifier {
+        match self {
+            SubstrateAttester::Sr25519(pair) => H256::from(pair.public().0).into(),
+            SubstrateAttester::Ed25519(pair) => H256::from(pair.public().0).into(),
+        }
+    }
+
+    async fn sign_attestation(&self, signing_hash: H256) -> Result<Signature, Self::Error> {
+        let bytes = match self {
+            SubstrateAttester::Sr25519(pair) => pair.sign(signing_hash.as_ref()).0,
+            SubstrateAttester::Ed25519(pair) => pair.sign(signing_hash.as_ref()).0,
+        };
+        Ok(SignatureScheme::wrap(&bytes))
+    }
+}
+
 /// Interface for instantiating a chain-specific signer from a `SignerConf`
 /// object.
 #[async_trait]
@@ -26,3 +255,152 @@ pub trait FromSignerConf: Sized {
     /// Instantiate `Self` from a `SignerConf` object
     async fn try_from_signer_conf(conf: &SignerConf) -> Result<Self>;
 }
+
+#[async_trait]
+impl FromSignerConf for SubstrateAttester {
+    async fn try_from_signer_conf(conf: &SignerConf) -> Result<Self> {
+        match conf {
+            SignerConf::Substrate { scheme, key } => {
+                let scheme = match scheme {
+                    SubstrateKeyScheme::Sr25519 => SignatureScheme::Sr25519,
+                    SubstrateKeyScheme::Ed25519 => SignatureScheme::Ed25519,
+                };
+                Ok(Self::from_string(scheme, &format!("0x{}", key.as_ref()))?)
+            }
+            _ => bail!("Not a substrate attestation signer: {}", conf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{FailureNotification, SignedFailureNotification, SignedUpdate, Update};
+    use ethers::signers::LocalWallet;
+
+    #[tokio::test]
+    async fn it_signs_and_verifies_with_each_scheme() {
+        let wallet: LocalWallet =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+        let seed = "0x2222222222222222222222222222222222222222222222222222222222222222";
+        let sr25519 = SubstrateAttester::from_string(SignatureScheme::Sr25519, seed).unwrap();
+        let ed25519 = SubstrateAttester::from_string(SignatureScheme::Ed25519, seed).unwrap();
+
+        let update = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        };
+        let notification = FailureNotification {
+            home_domain: 1000,
+            updater: wallet.address().into(),
+        };
+
+        let signed = update.sign_with(&wallet).await.unwrap();
+        assert_eq!(signed.scheme, SignatureScheme::Secp256k1);
+        assert_eq!(signed.recover().unwrap(), wallet.address());
+        signed.verify(wallet.identifier()).unwrap();
+
+        for attester in [sr25519, ed25519] {
+            let signed = update.sign_with(&attester).await.unwrap();
+            assert_eq!(signed.scheme, attester.scheme());
+            signed.verify(attester.identifier()).unwrap();
+            assert!(signed.verify(wallet.identifier()).is_err());
+            assert!(matches!(
+                signed.recover(),
+                Err(NomadError::UnrecoverableSignature(scheme)) if scheme == attester.scheme()
+            ));
+
+            // the scheme survives encoding
+            let encoded = signed.to_vec();
+            assert_eq!(encoded.last(), Some(&(attester.scheme() as u8)));
+            let decoded = SignedUpdate::read_from(&mut encoded.as_slice()).unwrap();
+            assert_eq!(decoded, signed);
+            let value = serde_json::to_value(&signed).unwrap();
+            let deserialized: SignedUpdate = serde_json::from_value(value).unwrap();
+            assert_eq!(deserialized, signed);
+
+            let moved = SignedUpdate {
+                update: Update {
+                    home_domain: 1001,
+                    ..update
+                },
+                ..signed
+            };
+            assert!(moved.verify(attester.identifier()).is_err());
+
+            let signed = notification.sign_with(&attester).await.unwrap();
+            signed.verify(attester.identifier()).unwrap();
+            let decoded =
+                SignedFailureNotification::read_from(&mut signed.to_vec().as_slice()).unwrap();
+            assert_eq!(decoded, signed);
+        }
+    }
+
+    #[tokio::test]
+    async fn it_instantiates_from_substrate_signer_confs() {
+        let seed = "0x2222222222222222222222222222222222222222222222222222222222222222";
+        let conf = SignerConf::Substrate {
+            scheme: SubstrateKeyScheme::Ed25519,
+            key: seed.parse().unwrap(),
+        };
+        let attester = SubstrateAttester::try_from_signer_conf(&conf)
+            .await
+            .unwrap();
+        let expected = SubstrateAttester::from_string(SignatureScheme::Ed25519, seed).unwrap();
+        assert_eq!(attester.scheme(), SignatureScheme::Ed25519);
+        assert_eq!(attester.identifier(), expected.identifier());
+
+        assert!(SubstrateAttester::try_from_signer_conf(&SignerConf::Node)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn it_rejects_unknown_and_mismatched_schemes() {
+        let wallet: LocalWallet =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+        let signed = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        }
+        .sign_with(&wallet)
+        .await
+        .unwrap();
+
+        let mut encoded = signed.to_vec();
+        *encoded.last_mut().unwrap() = 3;
+        assert!(matches!(
+            SignedUpdate::read_from(&mut encoded.as_slice()),
+            Err(NomadError::UnknownSignatureScheme(3))
+        ));
+
+        // a secp256k1 signature tagged as sr25519
+        *encoded.last_mut().unwrap() = SignatureScheme::Sr25519 as u8;
+        assert!(matches!(
+            SignedUpdate::read_from(&mut encoded.as_slice()),
+            Err(NomadError::MismatchedSignatureScheme(
+                SignatureScheme::Sr25519,
+                _
+            ))
+        ));
+
+        let mut value = serde_json::to_value(&signed).unwrap();
+        value["scheme"] = "sr25519".into();
+        assert!(serde_json::from_value::<SignedUpdate>(value.clone()).is_err());
+        value["scheme"] = "bls".into();
+        assert!(serde_json::from_value::<SignedUpdate>(value.clone()).is_err());
+
+        // serialized before schemes were tagged
+        value.as_object_mut().unwrap().remove("scheme");
+        assert_eq!(
+            serde_json::from_value::<SignedUpdate>(value).unwrap(),
+            signed
+        );
+    }
+}
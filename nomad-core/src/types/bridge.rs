@@ -0,0 +1,476 @@
+use ethers::{
+    types::{H256, U256},
+    utils::keccak256,
+};
+
+use crate::{Decode, Encode, NomadError};
+
+/// 4 bytes domain + 32 bytes id
+const TOKEN_ID_LEN: usize = 36;
+/// 1 byte type + 32 bytes recipient + 32 bytes amount + 32 bytes details hash
+const TRANSFER_LEN: usize = 97;
+/// 1 byte type + 32 bytes hook + 32 bytes amount + 32 bytes details hash + 32
+/// bytes sender, followed by the hook's extra data
+const TRANSFER_TO_HOOK_PREFIX_LEN: usize = 129;
+
+/// BridgeRouter message types. These mirror `BridgeMessage.Types` in the
+/// token bridge contracts, and must not be renumbered. `Transfer`,
+/// `DeprecatedFastTransfer` and `TransferToHook` are actions. The others
+/// type the views of a message that are not actions.
+///
+/// The `Details` and `RequestDetails` actions are not included, as the
+/// deployed contracts no longer send them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BridgeMessageType {
+    /// A token's identity
+    TokenId = 1,
+    /// A full message: a token and an action
+    Message = 2,
+    /// A transfer of tokens to a recipient
+    Transfer = 3,
+    /// A transfer pre-filled by a liquidity provider. No longer sent by the
+    /// BridgeRouter, but still in chain history. Laid out as a `Transfer`
+    DeprecatedFastTransfer = 4,
+    /// A transfer of tokens to a hook contract, which is called with the
+    /// transfer's extra data
+    TransferToHook = 5,
+    /// The extra data of a `TransferToHook`
+    ExtraData = 6,
+}
+
+impl TryFrom<u8> for BridgeMessageType {
+    type Error = NomadError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use BridgeMessageType::*;
+        match value {
+            1 => Ok(TokenId),
+            2 => Ok(Message),
+            3 => Ok(Transfer),
+            4 => Ok(DeprecatedFastTransfer),
+            5 => Ok(TransferToHook),
+            6 => Ok(ExtraData),
+            _ => Err(NomadError::UnknownBridgeMessageType(value)),
+        }
+    }
+}
+
+/// The hash of a token's details committed to by transfers, as computed by
+/// `BridgeMessage.getDetailsHash`
+pub fn details_hash(name: &str, symbol: &str, decimals: u8) -> H256 {
+    let mut buf = vec![];
+    let mut len = [0u8; 32];
+    U256::from(name.len()).to_big_endian(&mut len);
+    buf.extend(len);
+    buf.extend(name.as_bytes());
+    U256::from(symbol.len()).to_big_endian(&mut len);
+    buf.extend(len);
+    buf.extend(symbol.as_bytes());
+    buf.push(decimals);
+    keccak256(buf).into()
+}
+
+/// A token's identity: the domain of its home chain and its address there
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenId {
+    /// 4   Domain of the token's home chain
+    pub domain: u32,
+    /// 32  Address on the home chain, in home chain convention
+    pub id: H256,
+}
+
+impl Encode for TokenId {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&self.domain.to_be_bytes())?;
+        writer.write_all(self.id.as_ref())?;
+        Ok(TOKEN_ID_LEN)
+    }
+}
+
+impl Decode for TokenId {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        Ok(Self {
+            domain: u32::read_from(reader)?,
+            id: H256::read_from(reader)?,
+        })
+    }
+}
+
+impl std::fmt::Display for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:?}", self.domain, self.id)
+    }
+}
+
+/// The contents of a `Transfer` action
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    /// 32  Recipient, in destination chain convention
+    pub recipient: H256,
+    /// 32  Amount of tokens, in the token's smallest unit
+    pub amount: U256,
+    /// 32  Hash of the token's name, symbol and decimals
+    pub details_hash: H256,
+}
+
+impl Encode for Transfer {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let mut amount = [0u8; 32];
+        self.amount.to_big_endian(&mut amount);
+
+        writer.write_all(self.recipient.as_ref())?;
+        writer.write_all(&amount)?;
+        writer.write_all(self.details_hash.as_ref())?;
+        Ok(TRANSFER_LEN - 1)
+    }
+}
+
+impl Decode for Transfer {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let recipient = H256::read_from(reader)?;
+        let amount = U256::from_big_endian(H256::read_from(reader)?.as_ref());
+        let details_hash = H256::read_from(reader)?;
+        Ok(Self {
+            recipient,
+            amount,
+            details_hash,
+        })
+    }
+}
+
+/// The contents of a `TransferToHook` action
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransferToHook {
+    /// 32  Hook contract, which receives the tokens and is called with them
+    pub hook: H256,
+    /// 32  Amount of tokens, in the token's smallest unit
+    pub amount: U256,
+    /// 32  Hash of the token's name, symbol and decimals
+    pub details_hash: H256,
+    /// 32  Sender on the origin chain
+    pub sender: H256,
+    /// 0+  Data passed to the hook. Runs to the end of the message
+    pub extra_data: Vec<u8>,
+}
+
+impl Encode for TransferToHook {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let mut amount = [0u8; 32];
+        self.amount.to_big_endian(&mut amount);
+
+        writer.write_all(self.hook.as_ref())?;
+        writer.write_all(&amount)?;
+        writer.write_all(self.details_hash.as_ref())?;
+        writer.write_all(self.sender.as_ref())?;
+        writer.write_all(&self.extra_data)?;
+        Ok(TRANSFER_TO_HOOK_PREFIX_LEN - 1 + self.extra_data.len())
+    }
+}
+
+impl Decode for TransferToHook {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let hook = H256::read_from(reader)?;
+        let amount = U256::from_big_endian(H256::read_from(reader)?.as_ref());
+        let details_hash = H256::read_from(reader)?;
+        let sender = H256::read_from(reader)?;
+        let mut extra_data = vec![];
+        reader.read_to_end(&mut extra_data)?;
+        Ok(Self {
+            hook,
+            amount,
+            details_hash,
+            sender,
+            extra_data,
+        })
+    }
+}
+
+/// An action in a BridgeRouter message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeAction {
+    /// Transfer tokens to a recipient
+    Transfer(Transfer),
+    /// Transfer tokens to a recipient, pre-filled by a liquidity provider.
+    /// Only found in historical messages
+    FastTransfer(Transfer),
+    /// Transfer tokens to a hook contract, and call it
+    TransferToHook(TransferToHook),
+}
+
+impl BridgeAction {
+    /// The message type of this action
+    pub fn message_type(&self) -> BridgeMessageType {
+        match self {
+            BridgeAction::Transfer(_) => BridgeMessageType::Transfer,
+            BridgeAction::FastTransfer(_) => BridgeMessageType::DeprecatedFastTransfer,
+            BridgeAction::TransferToHook(_) => BridgeMessageType::TransferToHook,
+        }
+    }
+
+    /// The amount of tokens transferred
+    pub fn amount(&self) -> U256 {
+        match self {
+            BridgeAction::Transfer(transfer) | BridgeAction::FastTransfer(transfer) => {
+                transfer.amount
+            }
+            BridgeAction::TransferToHook(transfer) => transfer.amount,
+        }
+    }
+
+    /// The hash of the transferred token's details
+    pub fn details_hash(&self) -> H256 {
+        match self {
+            BridgeAction::Transfer(transfer) | BridgeAction::FastTransfer(transfer) => {
+                transfer.details_hash
+            }
+            BridgeAction::TransferToHook(transfer) => transfer.details_hash,
+        }
+    }
+
+    /// The recipient of the tokens: the hook of a `TransferToHook`
+    pub fn recipient(&self) -> H256 {
+        match self {
+            BridgeAction::Transfer(transfer) | BridgeAction::FastTransfer(transfer) => {
+                transfer.recipient
+            }
+            BridgeAction::TransferToHook(transfer) => transfer.hook,
+        }
+    }
+}
+
+impl Encode for BridgeAction {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&[self.message_type() as u8])?;
+        let len = match self {
+            BridgeAction::Transfer(transfer) | BridgeAction::FastTransfer(transfer) => {
+                transfer.write_to(writer)?
+            }
+            BridgeAction::TransferToHook(transfer) => transfer.write_to(writer)?,
+        };
+        Ok(1 + len)
+    }
+}
+
+impl Decode for BridgeAction {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let mut message_type = [0u8; 1];
+        reader.read_exact(&mut message_type)?;
+
+        match BridgeMessageType::try_from(message_type[0])? {
+            BridgeMessageType::Transfer => Ok(BridgeAction::Transfer(Transfer::read_from(reader)?)),
+            BridgeMessageType::DeprecatedFastTransfer => {
+                Ok(BridgeAction::FastTransfer(Transfer::read_from(reader)?))
+            }
+            BridgeMessageType::TransferToHook => Ok(BridgeAction::TransferToHook(
+                TransferToHook::read_from(reader)?,
+            )),
+            other => Err(NomadError::UnknownBridgeMessageType(other as u8)),
+        }
+    }
+}
+
+/// A BridgeRouter message: a token and an action to take on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeMessage {
+    /// 36  The token
+    pub token: TokenId,
+    /// 1+  The action
+    pub action: BridgeAction,
+}
+
+impl Encode for BridgeMessage {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let token_len = self.token.write_to(writer)?;
+        let action_len = self.action.write_to(writer)?;
+        Ok(token_len + action_len)
+    }
+}
+
+impl Decode for BridgeMessage {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        Ok(Self {
+            token: TokenId::read_from(reader)?,
+            action: BridgeAction::read_from(reader)?,
+        })
+    }
+}
+
+impl std::fmt::Display for BridgeMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} of {} token {} to {:?}",
+            self.action.message_type(),
+            self.action.amount(),
+            self.token,
+            self.action.recipient(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn token() -> TokenId {
+        TokenId {
+            domain: 6648936,
+            id: H256::repeat_byte(0x11),
+        }
+    }
+
+    #[test]
+    fn it_roundtrips_bridge_messages() {
+        let transfer = Transfer {
+            recipient: H256::repeat_byte(0x22),
+            amount: U256::exp10(18),
+            details_hash: H256::repeat_byte(0x33),
+        };
+        let hook = TransferToHook {
+            hook: H256::repeat_byte(0x44),
+            amount: U256::exp10(6),
+            details_hash: H256::repeat_byte(0x33),
+            sender: H256::repeat_byte(0x55),
+            extra_data: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+
+        let cases = [
+            (BridgeAction::Transfer(transfer), TRANSFER_LEN),
+            (BridgeAction::FastTransfer(transfer), TRANSFER_LEN),
+            (
+                BridgeAction::TransferToHook(hook.clone()),
+                TRANSFER_TO_HOOK_PREFIX_LEN + 4,
+            ),
+            (
+                BridgeAction::TransferToHook(TransferToHook {
+                    extra_data: vec![],
+                    ..hook
+                }),
+                TRANSFER_TO_HOOK_PREFIX_LEN,
+            ),
+        ];
+        for (action, len) in cases {
+            let message = BridgeMessage {
+                token: token(),
+                action,
+            };
+            let encoded = message.to_vec();
+            assert_eq!(encoded.len(), TOKEN_ID_LEN + len);
+            assert_eq!(&encoded[..4], &6648936u32.to_be_bytes());
+            assert_eq!(encoded[TOKEN_ID_LEN], message.action.message_type() as u8);
+            assert_eq!(
+                BridgeMessage::read_from(&mut encoded.as_slice()).unwrap(),
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn it_decodes_a_mainnet_transfer() {
+        // Layout of a mainnet transfer of 1 WETH from Ethereum (domain "eth"),
+        // committing to the details of Wrapped Ether
+        let body = hex::decode(concat!(
+            "00657468",
+            "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "03",
+            "000000000000000000000000000000000000000000000000000000000000beef",
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+            "69144a56ecb1b88cd5fea4f45c41f8bc298716dbc612b16010ccf8d7f01ba0a3",
+        ))
+        .unwrap();
+
+        let message = BridgeMessage::read_from(&mut body.as_slice()).unwrap();
+        assert_eq!(message.token.domain, 0x657468);
+        assert_eq!(
+            message.token.id,
+            "0x000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(message.action.message_type(), BridgeMessageType::Transfer);
+        assert_eq!(message.action.amount(), U256::exp10(18));
+        assert_eq!(message.action.recipient(), H256::from_low_u64_be(0xbeef));
+        assert_eq!(
+            message.action.details_hash(),
+            details_hash("Wrapped Ether", "WETH", 18)
+        );
+        assert_eq!(message.to_vec(), body);
+
+        // Historical fast transfers have the same layout
+        let mut fast = body.clone();
+        fast[TOKEN_ID_LEN] = BridgeMessageType::DeprecatedFastTransfer as u8;
+        let fast_message = BridgeMessage::read_from(&mut fast.as_slice()).unwrap();
+        match (&fast_message.action, &message.action) {
+            (BridgeAction::FastTransfer(fast_transfer), BridgeAction::Transfer(transfer)) => {
+                assert_eq!(fast_transfer, transfer)
+            }
+            actions => panic!("expected a fast transfer, got {:?}", actions),
+        }
+        assert_eq!(fast_message.to_vec(), fast);
+    }
+
+    #[test]
+    fn it_rejects_unknown_actions() {
+        for message_type in [
+            BridgeMessageType::TokenId,
+            BridgeMessageType::Message,
+            BridgeMessageType::ExtraData,
+        ] {
+            let mut encoded = token().to_vec();
+            encoded.push(message_type as u8);
+            encoded.extend_from_slice(&[0u8; 96]);
+            assert!(matches!(
+                BridgeMessage::read_from(&mut encoded.as_slice()),
+                Err(NomadError::UnknownBridgeMessageType(t)) if t == message_type as u8
+            ));
+        }
+
+        // truncated transfers
+        for message_type in [
+            BridgeMessageType::Transfer,
+            BridgeMessageType::DeprecatedFastTransfer,
+        ] {
+            let mut encoded = token().to_vec();
+            encoded.push(message_type as u8);
+            encoded.extend_from_slice(&[0u8; 64]);
+            assert!(BridgeMessage::read_from(&mut encoded.as_slice()).is_err());
+        }
+
+        // truncated transfer to hook
+        let mut encoded = token().to_vec();
+        encoded.push(BridgeMessageType::TransferToHook as u8);
+        encoded.extend_from_slice(&[0u8; 96]);
+        assert!(BridgeMessage::read_from(&mut encoded.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use std::convert::Infallible;
+
+use ethers::{
+    abi::{encode, Token},
+    prelude::{Address, U256},
+    types::{
+        transaction::eip712::{EIP712Domain, Eip712},
+        H256,
+    },
+    utils::keccak256,
+};
+use ethers_signers::Signer;
+
+use crate::{
+    FailureNotification, NomadError, SignatureScheme, SignedFailureNotification, SignedUpdate,
+    Update,
+};
+
+/// EIP-712 domain name of Nomad attestations
+pub const EIP712_DOMAIN_NAME: &str = "Nomad";
+/// EIP-712 domain version of Nomad attestations
+pub const EIP712_DOMAIN_VERSION: &str = "1";
+
+/// EIP-712 type of an `Update`
+pub const UPDATE_TYPE: &str = "Update(uint32 homeDomain,bytes32 previousRoot,bytes32 newRoot)";
+/// EIP-712 type of a `FailureNotification`
+pub const FAILURE_NOTIFICATION_TYPE: &str =
+    "FailureNotification(uint32 homeDomain,bytes32 updater)";
+
+/// The EIP-712 signing domain of the contract verifying an attestation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NomadDomain {
+    /// Chain id of the verifying contract's chain
+    pub chain_id: u64,
+    /// The verifying contract
+    pub verifying_contract: Address,
+}
+
+impl NomadDomain {
+    /// Instantiate a domain
+    pub fn new(chain_id: u64, verifying_contract: Address) -> Self {
+        Self {
+            chain_id,
+            verifying_contract,
+        }
+    }
+
+    /// The full EIP-712 domain
+    pub fn eip712_domain(&self) -> EIP712Domain {
+        EIP712Domain {
+            name: Some(EIP712_DOMAIN_NAME.to_owned()),
+            version: Some(EIP712_DOMAIN_VERSION.to_owned()),
+            chain_id: Some(self.chain_id.into()),
+            verifying_contract: Some(self.verifying_contract),
+            salt: None,
+        }
+    }
+}
+
+/// An `Update` in an EIP-712 signing domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypedUpdate {
+    /// The update
+    pub update: Update,
+    /// The signing domain
+    pub domain: NomadDomain,
+}
+
+impl Eip712 for TypedUpdate {
+    type Error = Infallible;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.eip712_domain())
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(UPDATE_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::Uint(U256::from(self.update.home_domain)),
+            Token::FixedBytes(self.update.previous_root.as_bytes().to_vec()),
+            Token::FixedBytes(self.update.new_root.as_bytes().to_vec()),
+        ])))
+    }
+}
+
+/// A `FailureNotification` in an EIP-712 signing domain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypedFailureNotification {
+    /// The failure notification
+    pub notification: FailureNotification,
+    /// The signing domain
+    pub domain: NomadDomain,
+}
+
+impl Eip712 for TypedFailureNotification {
+    type Error = Infallible;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.eip712_domain())
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(FAILURE_NOTIFICATION_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::Uint(U256::from(self.notification.home_domain)),
+            Token::FixedBytes(self.notification.updater.as_ref().to_vec()),
+        ])))
+    }
+}
+
+/// The EIP-712 digest signed for `payload`
+fn typed_digest<T: Eip712<Error = Infallible>>(payload: &T) -> H256 {
+    match payload.encode_eip712() {
+        Ok(digest) => digest.into(),
+        Err(never) => match never {},
+    }
+}
+
+impl Update {
+    /// Bind this update to an EIP-712 signing domain
+    pub fn typed(self, domain: NomadDomain) -> TypedUpdate {
+        TypedUpdate {
+            update: self,
+            domain,
+        }
+    }
+
+    /// The EIP-712 digest of this update in `domain`
+    pub fn eip712_hash(&self, domain: NomadDomain) -> H256 {
+        typed_digest(&self.typed(domain))
+    }
+
+    /// Sign this update as EIP-712 typed data in `domain`
+    pub async fn sign_typed_with<S: Signer>(
+        self,
+        domain: NomadDomain,
+        signer: &S,
+    ) -> Result<SignedUpdate, S::Error> {
+        let signature = signer.sign_typed_data(&self.typed(domain)).await?;
+        Ok(SignedUpdate {
+            update: self,
+            signature,
+            scheme: SignatureScheme::Secp256k1,
+        })
+    }
+}
+
+impl SignedUpdate {
+    /// Recover the Ethereum address of the signer of an EIP-712 signature
+    /// in `domain`
+    pub fn recover_typed(&self, domain: NomadDomain) -> Result<Address, NomadError> {
+        match self.scheme {
+            SignatureScheme::Secp256k1 => {
+                Ok(self.signature.recover(self.update.eip712_hash(domain))?)
+            }
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
+    }
+
+    /// Check whether an EIP-712 signature in `domain` was made by a specific
+    /// address
+    pub fn verify_typed(&self, domain: NomadDomain, signer: Address) -> Result<(), NomadError> {
+        match self.scheme {
+            SignatureScheme::Secp256k1 => Ok(self
+                .signature
+                .verify(self.update.eip712_hash(domain), signer)?),
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
+    }
+}
+
+impl FailureNotification {
+    /// Bind this notification to an EIP-712 signing domain
+    pub fn typed(self, domain: NomadDomain) -> TypedFailureNotification {
+        TypedFailureNotification {
+            notification: self,
+            domain,
+        }
+    }
+
+    /// The EIP-712 digest of this notification in `domain`
+    pub fn eip712_hash(&self, domain: NomadDomain) -> H256 {
+        typed_digest(&self.typed(domain))
+    }
+
+    /// Sign this notification as EIP-712 typed data in `domain`
+    pub async fn sign_typed_with<S: Signer>(
+        self,
+        domain: NomadDomain,
+        signer: &S,
+    ) -> Result<SignedFailureNotification, S::Error> {
+        let signature = signer.sign_typed_data(&self.typed(domain)).await?;
+        Ok(SignedFailureNotification {
+            notification: self,
+            signature,
+            scheme: SignatureScheme::Secp256k1,
+        })
+    }
+}
+
+impl SignedFailureNotification {
+    /// Recover the Ethereum address of the signer of an EIP-712 signature
+    /// in `domain`
+    pub fn recover_typed(&self, domain: NomadDomain) -> Result<Address, NomadError> {
+        match self.scheme {
+            SignatureScheme::Secp256k1 => Ok(self
+                .signature
+                .recover(self.notification.eip712_hash(domain))?),
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
+    }
+
+    /// Check whether an EIP-712 signature in `domain` was made by a specific
+    /// address
+    pub fn verify_typed(&self, domain: NomadDomain, signer: Address) -> Result<(), NomadError> {
+        match self.scheme {
+            SignatureScheme::Secp256k1 => Ok(self
+                .signature
+                .verify(self.notification.eip712_hash(domain), signer)?),
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethers::signers::LocalWallet;
+
+    fn signer() -> LocalWallet {
+        "1111111111111111111111111111111111111111111111111111111111111111"
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_signs_and_recovers_typed_attestations() {
+        let signer = signer();
+        let domain = NomadDomain::new(1, Address::repeat_byte(0x11));
+        let other_domain = NomadDomain::new(5, Address::repeat_byte(0x11));
+
+        let update = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        };
+        let signed = update.sign_typed_with(domain, &signer).await.unwrap();
+        assert_eq!(signed.recover_typed(domain).unwrap(), signer.address());
+        signed.verify_typed(domain, signer.address()).unwrap();
+        assert_ne!(
+            signed.recover_typed(other_domain).unwrap(),
+            signer.address()
+        );
+        // not interchangeable with legacy signatures
+        assert_ne!(signed.recover().unwrap(), signer.address());
+
+        let notification = FailureNotification {
+            home_domain: 2000,
+            updater: Address::repeat_byte(0x22).into(),
+        };
+        let signed = notification.sign_typed_with(domain, &signer).await.unwrap();
+        assert_eq!(signed.recover_typed(domain).unwrap(), signer.address());
+        assert!(signed.verify_typed(other_domain, signer.address()).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_does_not_recover_other_schemes() {
+        let signer = signer();
+        let domain = NomadDomain::new(1, Address::repeat_byte(0x11));
+
+        let mut signed = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        }
+        .sign_typed_with(domain, &signer)
+        .await
+        .unwrap();
+        signed.scheme = SignatureScheme::Sr25519;
+        assert!(matches!(
+            signed.recover_typed(domain),
+            Err(NomadError::UnrecoverableSignature(SignatureScheme::Sr25519))
+        ));
+        assert!(signed.verify_typed(domain, signer.address()).is_err());
+
+        let mut signed = FailureNotification {
+            home_domain: 2000,
+            updater: Address::repeat_byte(0x22).into(),
+        }
+        .sign_typed_with(domain, &signer)
+        .await
+        .unwrap();
+        signed.scheme = SignatureScheme::Ed25519;
+        assert!(matches!(
+            signed.recover_typed(domain),
+            Err(NomadError::UnrecoverableSignature(SignatureScheme::Ed25519))
+        ));
+        assert!(signed.verify_typed(domain, signer.address()).is_err());
+    }
+
+    #[test]
+    fn it_hashes_per_eip712() {
+        let domain = NomadDomain::new(1, Address::repeat_byte(0x11));
+        let update = Update {
+            home_domain: 1000,
+            previous_root: H256::repeat_byte(1),
+            new_root: H256::repeat_byte(2),
+        };
+
+        let mut encoded = vec![0x19, 0x01];
+        encoded.extend_from_slice(&domain.eip712_domain().separator());
+        encoded.extend_from_slice(&update.typed(domain).struct_hash().unwrap());
+        assert_eq!(update.eip712_hash(domain), H256::from(keccak256(encoded)));
+
+        // the struct hash commits to every field
+        let moved = Update {
+            home_domain: 1001,
+            ..update
+        };
+        assert_ne!(update.eip712_hash(domain), moved.eip712_hash(domain));
+    }
+}
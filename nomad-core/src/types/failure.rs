@@ -1,15 +1,16 @@
-use crate::{utils::home_domain_hash, NomadError, SignerExt};
+use crate::{utils::home_domain_hash, Attester, Decode, Encode, NomadError, SignatureScheme};
 use ethers::{
     prelude::{Address, Signature},
     types::H256,
     utils::hash_message,
 };
-use ethers_signers::Signer;
 use nomad_types::NomadIdentifier;
+use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 
 /// Failure notification produced by watcher
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
 pub struct FailureNotification {
     /// Domain of failed home
     pub home_domain: u32,
@@ -36,37 +37,150 @@ impl FailureNotification {
     /// Sign an `FailureNotification` using the specified signer
     pub async fn sign_with<S>(self, signer: &S) -> Result<SignedFailureNotification, S::Error>
     where
-        S: Signer,
+        S: Attester,
     {
-        let signature = signer
-            .sign_message_without_eip_155(self.signing_hash())
-            .await?;
+        let signature = signer.sign_attestation(self.signing_hash()).await?;
         Ok(SignedFailureNotification {
             notification: self,
             signature,
+            scheme: signer.scheme(),
         })
     }
 }
 
 /// Signed failure notification produced by watcher
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(
+    rename_all = "camelCase",
+    try_from = "UncheckedSignedFailureNotification"
+)]
 pub struct SignedFailureNotification {
     /// Failure notification
     pub notification: FailureNotification,
     /// Signature
     pub signature: Signature,
+    /// Signature scheme
+    pub scheme: SignatureScheme,
+}
+
+/// A `SignedFailureNotification` whose signature has not been checked
+/// against its scheme
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UncheckedSignedFailureNotification {
+    notification: FailureNotification,
+    signature: Signature,
+    /// Absent from notifications serialized before schemes were tagged,
+    /// which were all secp256k1
+    #[serde(default)]
+    scheme: SignatureScheme,
+}
+
+impl TryFrom<UncheckedSignedFailureNotification> for SignedFailureNotification {
+    type Error = NomadError;
+
+    fn try_from(unchecked: UncheckedSignedFailureNotification) -> Result<Self, Self::Error> {
+        unchecked.scheme.check(&unchecked.signature)?;
+        Ok(Self {
+            notification: unchecked.notification,
+            signature: unchecked.signature,
+            scheme: unchecked.scheme,
+        })
+    }
+}
+
+impl Encode for SignedFailureNotification {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let mut written = 0;
+        written += self.notification.write_to(writer)?;
+        written += self.signature.write_to(writer)?;
+        written += self.scheme.write_to(writer)?;
+        Ok(written)
+    }
+}
+
+impl Decode for SignedFailureNotification {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let notification = FailureNotification::read_from(reader)?;
+        let signature = Signature::read_from(reader)?;
+        let scheme = SignatureScheme::read_from(reader)?;
+        scheme.check(&signature)?;
+        Ok(Self {
+            notification,
+            signature,
+            scheme,
+        })
+    }
 }
 
 impl SignedFailureNotification {
-    /// Recover the Ethereum address of the signer
+    /// Recover the Ethereum address of the signer. Only secp256k1
+    /// signatures are recoverable
     pub fn recover(&self) -> Result<Address, NomadError> {
-        Ok(self.signature.recover(self.notification.prepended_hash())?)
+        match self.scheme {
+            SignatureScheme::Secp256k1 => {
+                Ok(self.signature.recover(self.notification.prepended_hash())?)
+            }
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
+    }
+
+    /// Check whether a message was signed by a specific signer: an Ethereum
+    /// address for secp256k1, or a public key otherwise
+    pub fn verify(&self, signer: impl Into<NomadIdentifier>) -> Result<(), NomadError> {
+        self.scheme.verify(
+            &self.signature,
+            self.notification.signing_hash(),
+            signer.into(),
+        )
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethers::signers::LocalWallet;
+
+    #[tokio::test]
+    async fn it_roundtrips_failure_notifications_through_json() {
+        let signer: LocalWallet =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+        let updater: LocalWallet =
+            "2222222222222222222222222222222222222222222222222222222222222222"
+                .parse()
+                .unwrap();
+
+        let signed = FailureNotification {
+            home_domain: 2000,
+            updater: updater.address().into(),
+        }
+        .sign_with(&signer)
+        .await
+        .unwrap();
+
+        let mut value = serde_json::to_value(&signed).unwrap();
+        assert_eq!(value["notification"]["homeDomain"], 2000);
+        assert_eq!(
+            value["notification"]["updater"],
+            serde_json::to_value(updater.address()).unwrap()
+        );
+
+        let deserialized: SignedFailureNotification =
+            serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(deserialized, signed);
+        assert_eq!(deserialized.recover().unwrap(), signer.address());
 
-    /// Check whether a message was signed by a specific address
-    pub fn verify(&self, signer: Address) -> Result<(), NomadError> {
-        Ok(self
-            .signature
-            .verify(self.notification.prepended_hash(), signer)?)
+        // serialized before schemes were tagged
+        value.as_object_mut().unwrap().remove("scheme");
+        let deserialized: SignedFailureNotification = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized, signed);
     }
 }
@@ -0,0 +1,309 @@
+use std::io::Read;
+
+use ethers::{types::H256, utils::keccak256};
+
+use crate::{Decode, Encode, Message, NomadError};
+
+/// 32 bytes to + 4 bytes data length
+const CALL_PREFIX_LEN: usize = 36;
+/// 1 byte type + 32 bytes batch hash
+const BATCH_LEN: usize = 33;
+/// 1 byte type + 4 bytes domain + 32 bytes address
+const TRANSFER_GOVERNOR_LEN: usize = 37;
+/// 1 byte type + 4 bytes domain + 32 bytes address
+const SET_ROUTER_LEN: usize = 37;
+
+/// GovernanceRouter message types. These mirror `GovernanceMessage.Types`
+/// in the core contracts, and must not be renumbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GovernanceMessageType {
+    /// A batch of calls to be executed on the destination chain
+    Batch = 1,
+    /// Transfer governorship to a new governor
+    TransferGovernor = 2,
+    /// Set the GovernanceRouter for a domain
+    SetRouter = 3,
+}
+
+impl TryFrom<u8> for GovernanceMessageType {
+    type Error = NomadError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use GovernanceMessageType::*;
+        match value {
+            1 => Ok(Batch),
+            2 => Ok(TransferGovernor),
+            3 => Ok(SetRouter),
+            _ => Err(NomadError::UnknownGovernanceMessageType(value)),
+        }
+    }
+}
+
+/// A call to be made by the GovernanceRouter
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Call {
+    /// 32  The contract to call, as a left-padded address
+    pub to: H256,
+    /// 4+  The calldata, prefixed with its length
+    pub data: Vec<u8>,
+}
+
+impl Encode for Call {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let len: u32 = self.data.len().try_into().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Call data too long")
+        })?;
+        writer.write_all(self.to.as_ref())?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&self.data)?;
+        Ok(CALL_PREFIX_LEN + self.data.len())
+    }
+}
+
+impl Decode for Call {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let to = H256::read_from(reader)?;
+        let len = u32::read_from(reader)?;
+
+        // Don't trust `len` with an allocation before the data is read
+        let mut data = vec![];
+        reader.take(len as u64).read_to_end(&mut data)?;
+        if data.len() != len as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Self { to, data })
+    }
+}
+
+/// A batch of calls. Governance messages commit to a batch by its hash, and
+/// the batch is executed on the destination chain by submitting its calls.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CallBatch {
+    calls: Vec<Call>,
+}
+
+impl CallBatch {
+    /// Instantiate a batch. A batch may contain at most 255 calls
+    pub fn new(calls: Vec<Call>) -> Result<Self, NomadError> {
+        if calls.len() > u8::MAX as usize {
+            return Err(NomadError::TooManyCalls(calls.len()));
+        }
+        Ok(Self { calls })
+    }
+
+    /// The calls in this batch
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// The hash committed to by a `GovernanceMessage::Batch`
+    pub fn batch_hash(&self) -> H256 {
+        keccak256(self.to_vec()).into()
+    }
+}
+
+impl Encode for CallBatch {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&[self.calls.len() as u8])?;
+        let mut written = 1;
+        for call in self.calls.iter() {
+            written += call.write_to(writer)?;
+        }
+        Ok(written)
+    }
+}
+
+impl Decode for CallBatch {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let mut len = [0u8; 1];
+        reader.read_exact(&mut len)?;
+        let calls = (0..len[0])
+            .map(|_| Call::read_from(reader))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { calls })
+    }
+}
+
+/// A GovernanceRouter message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GovernanceMessage {
+    /// Commit to a batch of calls by its hash
+    Batch(H256),
+    /// Transfer governorship
+    TransferGovernor {
+        /// 4   The domain of the new governor
+        domain: u32,
+        /// 32  The new governor
+        governor: H256,
+    },
+    /// Set the GovernanceRouter for a domain
+    SetRouter {
+        /// 4   The domain of the router
+        domain: u32,
+        /// 32  The router
+        router: H256,
+    },
+}
+
+impl GovernanceMessage {
+    /// Commit to `batch` by its hash
+    pub fn batch(batch: &CallBatch) -> Self {
+        GovernanceMessage::Batch(batch.batch_hash())
+    }
+
+    /// The message type
+    pub fn message_type(&self) -> GovernanceMessageType {
+        match self {
+            GovernanceMessage::Batch(_) => GovernanceMessageType::Batch,
+            GovernanceMessage::TransferGovernor { .. } => GovernanceMessageType::TransferGovernor,
+            GovernanceMessage::SetRouter { .. } => GovernanceMessageType::SetRouter,
+        }
+    }
+
+    /// Build a message to the GovernanceRouter `router` on `destination`,
+    /// ready for `Home::dispatch`
+    pub fn to_message(&self, destination: u32, router: H256) -> Message {
+        Message {
+            destination,
+            recipient: router,
+            body: self.to_vec(),
+        }
+    }
+}
+
+impl Encode for GovernanceMessage {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&[self.message_type() as u8])?;
+        match self {
+            GovernanceMessage::Batch(batch_hash) => {
+                writer.write_all(batch_hash.as_ref())?;
+                Ok(BATCH_LEN)
+            }
+            GovernanceMessage::TransferGovernor { domain, governor } => {
+                writer.write_all(&domain.to_be_bytes())?;
+                writer.write_all(governor.as_ref())?;
+                Ok(TRANSFER_GOVERNOR_LEN)
+            }
+            GovernanceMessage::SetRouter { domain, router } => {
+                writer.write_all(&domain.to_be_bytes())?;
+                writer.write_all(router.as_ref())?;
+                Ok(SET_ROUTER_LEN)
+            }
+        }
+    }
+}
+
+impl Decode for GovernanceMessage {
+    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
+    where
+        R: std::io::Read,
+    {
+        let mut message_type = [0u8; 1];
+        reader.read_exact(&mut message_type)?;
+
+        match GovernanceMessageType::try_from(message_type[0])? {
+            GovernanceMessageType::Batch => Ok(GovernanceMessage::Batch(H256::read_from(reader)?)),
+            GovernanceMessageType::TransferGovernor => Ok(GovernanceMessage::TransferGovernor {
+                domain: u32::read_from(reader)?,
+                governor: H256::read_from(reader)?,
+            }),
+            GovernanceMessageType::SetRouter => Ok(GovernanceMessage::SetRouter {
+                domain: u32::read_from(reader)?,
+                router: H256::read_from(reader)?,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for GovernanceMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GovernanceMessage::Batch(batch_hash) => write!(f, "Batch {:?}", batch_hash),
+            GovernanceMessage::TransferGovernor { domain, governor } => {
+                write!(f, "TransferGovernor to {}:{:?}", domain, governor)
+            }
+            GovernanceMessage::SetRouter { domain, router } => {
+                write!(f, "SetRouter for {} to {:?}", domain, router)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_roundtrips_governance_messages() {
+        let batch = CallBatch::new(vec![
+            Call {
+                to: H256::repeat_byte(1),
+                data: vec![0xde, 0xad, 0xbe, 0xef],
+            },
+            Call {
+                to: H256::repeat_byte(2),
+                data: vec![],
+            },
+        ])
+        .unwrap();
+
+        let encoded = batch.to_vec();
+        assert_eq!(encoded.len(), 1 + 2 * CALL_PREFIX_LEN + 4);
+        assert_eq!(encoded[0], 2);
+        assert_eq!(&encoded[33..37], &4u32.to_be_bytes());
+        assert_eq!(
+            CallBatch::read_from(&mut encoded.as_slice()).unwrap(),
+            batch
+        );
+        assert_eq!(batch.batch_hash(), H256::from(keccak256(&encoded)));
+
+        let messages = [
+            GovernanceMessage::batch(&batch),
+            GovernanceMessage::TransferGovernor {
+                domain: 1000,
+                governor: H256::repeat_byte(3),
+            },
+            GovernanceMessage::SetRouter {
+                domain: 2000,
+                router: H256::repeat_byte(4),
+            },
+        ];
+        for governance_message in messages {
+            let message = governance_message.to_message(3000, H256::repeat_byte(5));
+            assert_eq!(message.destination, 3000);
+            assert_eq!(message.body[0], governance_message.message_type() as u8);
+            assert_eq!(
+                GovernanceMessage::read_from(&mut message.body.as_slice()).unwrap(),
+                governance_message
+            );
+        }
+
+        assert!(CallBatch::new(vec![Call::default(); 256]).is_err());
+
+        // Length prefix claims more data than there is
+        let mut truncated = H256::repeat_byte(1).as_bytes().to_vec();
+        truncated.extend(u32::MAX.to_be_bytes());
+        truncated.extend([1, 2, 3]);
+        assert!(Call::read_from(&mut truncated.as_slice()).is_err());
+        assert!(matches!(
+            GovernanceMessage::read_from(&mut [4u8; 37].as_ref()),
+            Err(NomadError::UnknownGovernanceMessageType(4))
+        ));
+    }
+}
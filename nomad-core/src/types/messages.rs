@@ -1,11 +1,11 @@
 use ethers::{types::H256, utils::keccak256};
+use serde::{Deserialize, Serialize};
 
-use crate::{utils, Decode, Encode, NomadError};
-
-const NOMAD_MESSAGE_PREFIX_LEN: usize = 76;
+use crate::{utils, BridgeMessage, Decode, Encode, GovernanceMessage, NomadError};
 
 /// A full Nomad message between chains
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
 pub struct NomadMessage {
     /// 4   SLIP-44 ID
     pub origin: u32,
@@ -18,69 +18,23 @@ pub struct NomadMessage {
     /// 32  Address in destination convention
     pub recipient: H256,
     /// 0+  Message contents
+    #[serde(with = "crate::utils::hex_bytes")]
     pub body: Vec<u8>,
 }
 
 /// A partial Nomad message between chains
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Message {
     /// 4   SLIP-44 ID
     pub destination: u32,
     /// 32  Address in destination convention
     pub recipient: H256,
     /// 0+  Message contents
+    #[serde(with = "crate::utils::hex_bytes")]
     pub body: Vec<u8>,
 }
 
-impl Encode for NomadMessage {
-    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
-    where
-        W: std::io::Write,
-    {
-        writer.write_all(&self.origin.to_be_bytes())?;
-        writer.write_all(self.sender.as_ref())?;
-        writer.write_all(&self.nonce.to_be_bytes())?;
-        writer.write_all(&self.destination.to_be_bytes())?;
-        writer.write_all(self.recipient.as_ref())?;
-        writer.write_all(&self.body)?;
-        Ok(NOMAD_MESSAGE_PREFIX_LEN + self.body.len())
-    }
-}
-
-impl Decode for NomadMessage {
-    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
-    where
-        R: std::io::Read,
-    {
-        let mut origin = [0u8; 4];
-        reader.read_exact(&mut origin)?;
-
-        let mut sender = H256::zero();
-        reader.read_exact(sender.as_mut())?;
-
-        let mut nonce = [0u8; 4];
-        reader.read_exact(&mut nonce)?;
-
-        let mut destination = [0u8; 4];
-        reader.read_exact(&mut destination)?;
-
-        let mut recipient = H256::zero();
-        reader.read_exact(recipient.as_mut())?;
-
-        let mut body = vec![];
-        reader.read_to_end(&mut body)?;
-
-        Ok(Self {
-            origin: u32::from_be_bytes(origin),
-            sender,
-            destination: u32::from_be_bytes(destination),
-            recipient,
-            nonce: u32::from_be_bytes(nonce),
-            body,
-        })
-    }
-}
-
 impl NomadMessage {
     /// Convert the message to a leaf
     pub fn to_leaf(&self) -> H256 {
@@ -91,6 +45,35 @@ impl NomadMessage {
     pub fn destination_and_nonce(&self) -> u64 {
         utils::destination_and_nonce(self.destination, self.nonce)
     }
+
+    /// True if this message is a dispatch of the partial `message`
+    pub fn is_dispatch_of(&self, message: &Message) -> bool {
+        self.destination == message.destination
+            && self.recipient == message.recipient
+            && self.body == message.body
+    }
+
+    /// Try to decode the body as a BridgeRouter message. This does not check
+    /// that the sender is a BridgeRouter.
+    pub fn bridge_message(&self) -> Result<BridgeMessage, NomadError> {
+        let mut body = self.body.as_slice();
+        let message = BridgeMessage::read_from(&mut body)?;
+        if !body.is_empty() {
+            return Err(NomadError::TrailingBytes(body.len()));
+        }
+        Ok(message)
+    }
+
+    /// Try to decode the body as a GovernanceRouter message. This does not
+    /// check that the sender is a GovernanceRouter.
+    pub fn governance_message(&self) -> Result<GovernanceMessage, NomadError> {
+        let mut body = self.body.as_slice();
+        let message = GovernanceMessage::read_from(&mut body)?;
+        if !body.is_empty() {
+            return Err(NomadError::TrailingBytes(body.len()));
+        }
+        Ok(message)
+    }
 }
 
 impl std::fmt::Display for NomadMessage {
@@ -102,3 +85,111 @@ impl std::fmt::Display for NomadMessage {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BridgeAction, CommittedMessage, RawCommittedMessage, TokenId, Transfer};
+    use serde_json::json;
+
+    #[test]
+    fn it_decodes_bridge_messages() {
+        let bridge_message = BridgeMessage {
+            token: TokenId {
+                domain: 1000,
+                id: H256::repeat_byte(1),
+            },
+            action: BridgeAction::Transfer(Transfer::default()),
+        };
+        let mut message = NomadMessage {
+            body: bridge_message.to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(message.bridge_message().unwrap(), bridge_message);
+
+        message.body.push(0);
+        assert!(matches!(
+            message.bridge_message(),
+            Err(NomadError::TrailingBytes(1))
+        ));
+
+        message.body = vec![1, 2, 3];
+        assert!(message.bridge_message().is_err());
+    }
+
+    #[test]
+    fn it_roundtrips_messages_through_json() {
+        let message = NomadMessage {
+            origin: 1000,
+            sender: H256::repeat_byte(1),
+            nonce: 7,
+            destination: 2000,
+            recipient: H256::repeat_byte(2),
+            body: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+        let raw = RawCommittedMessage {
+            leaf_index: 3,
+            committed_root: H256::repeat_byte(3),
+            message: message.to_vec(),
+        };
+        let committed = CommittedMessage::try_from(raw.clone()).unwrap();
+
+        let value = serde_json::to_value(&committed).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "leafIndex": 3,
+                "committedRoot": H256::repeat_byte(3),
+                "message": {
+                    "origin": 1000,
+                    "sender": H256::repeat_byte(1),
+                    "nonce": 7,
+                    "destination": 2000,
+                    "recipient": H256::repeat_byte(2),
+                    "body": "0xdeadbeef",
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<CommittedMessage>(value).unwrap(),
+            committed
+        );
+
+        let value = serde_json::to_value(&raw).unwrap();
+        assert_eq!(value["message"], format!("0x{}", hex::encode(&raw.message)));
+        assert_eq!(
+            serde_json::from_value::<RawCommittedMessage>(value).unwrap(),
+            raw
+        );
+
+        let partial = Message {
+            destination: 2000,
+            recipient: H256::repeat_byte(2),
+            body: vec![],
+        };
+        let value = serde_json::to_value(&partial).unwrap();
+        assert_eq!(value["body"], "0x");
+        assert_eq!(serde_json::from_value::<Message>(value).unwrap(), partial);
+    }
+
+    #[test]
+    fn it_matches_dispatches() {
+        let partial = Message {
+            destination: 2000,
+            recipient: H256::repeat_byte(2),
+            body: vec![1, 2, 3],
+        };
+        let mut message = NomadMessage {
+            origin: 1000,
+            nonce: 4,
+            destination: partial.destination,
+            recipient: partial.recipient,
+            body: partial.body.clone(),
+            ..Default::default()
+        };
+        assert!(message.is_dispatch_of(&partial));
+
+        message.body.push(4);
+        assert!(!message.is_dispatch_of(&partial));
+    }
+}
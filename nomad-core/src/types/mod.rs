@@ -1,7 +1,13 @@
+mod bridge;
+mod eip712;
 mod failure;
+mod governance;
 mod messages;
 mod update;
 
+pub use bridge::*;
+pub use eip712::*;
 pub use failure::*;
+pub use governance::*;
 pub use messages::*;
 pub use update::*;
@@ -1,17 +1,17 @@
 use std::fmt::Display;
 
-use crate::{utils::home_domain_hash, Decode, Encode, NomadError, SignerExt};
+use crate::{utils::home_domain_hash, Attester, Decode, Encode, NomadError, SignatureScheme};
 use ethers::{
     prelude::{Address, Signature},
     types::H256,
     utils::hash_message,
 };
-use ethers_signers::Signer;
+use nomad_types::NomadIdentifier;
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 
 /// An Nomad update message
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct Update {
     /// The home chain
     pub home_domain: u32,
@@ -31,41 +31,6 @@ impl std::fmt::Display for Update {
     }
 }
 
-impl Encode for Update {
-    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
-    where
-        W: std::io::Write,
-    {
-        writer.write_all(&self.home_domain.to_be_bytes())?;
-        writer.write_all(self.previous_root.as_ref())?;
-        writer.write_all(self.new_root.as_ref())?;
-        Ok(4 + 32 + 32)
-    }
-}
-
-impl Decode for Update {
-    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
-    where
-        R: std::io::Read,
-        Self: Sized,
-    {
-        let mut home_domain = [0u8; 4];
-        reader.read_exact(&mut home_domain)?;
-
-        let mut previous_root = H256::zero();
-        reader.read_exact(previous_root.as_mut())?;
-
-        let mut new_root = H256::zero();
-        reader.read_exact(new_root.as_mut())?;
-
-        Ok(Self {
-            home_domain: u32::from_be_bytes(home_domain),
-            previous_root,
-            new_root,
-        })
-    }
-}
-
 impl Update {
     fn signing_hash(&self) -> H256 {
         // sign:
@@ -85,69 +50,26 @@ impl Update {
     }
 
     /// Sign an update using the specified signer
-    pub async fn sign_with<S: Signer>(self, signer: &S) -> Result<SignedUpdate, S::Error> {
-        let signature = signer
-            .sign_message_without_eip_155(self.signing_hash())
-            .await?;
+    pub async fn sign_with<S: Attester>(self, signer: &S) -> Result<SignedUpdate, S::Error> {
+        let signature = signer.sign_attestation(self.signing_hash()).await?;
         Ok(SignedUpdate {
             update: self,
             signature,
+            scheme: signer.scheme(),
         })
     }
 }
 
 /// Metadata stored about an update
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct UpdateMeta {
     /// Block number
     pub block_number: u64,
     /// Timestamp seconds (optional because fetching timestamp is fallible)
+    #[nomad(zero_as_none)]
     pub timestamp: Option<u64>,
 }
 
-impl Encode for UpdateMeta {
-    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
-    where
-        W: std::io::Write,
-    {
-        let mut written = 0;
-        written += self.block_number.write_to(writer)?;
-
-        if let Some(timestamp) = self.timestamp {
-            written += timestamp.write_to(writer)?;
-        } else {
-            written += 0_u64.write_to(writer)?;
-        }
-
-        Ok(written)
-    }
-}
-
-impl Decode for UpdateMeta {
-    fn read_from<R>(reader: &mut R) -> Result<Self, NomadError>
-    where
-        R: std::io::Read,
-        Self: Sized,
-    {
-        let mut block_number = [0u8; 8];
-        let mut timestamp_bytes = [0u8; 8];
-        reader.read_exact(&mut block_number)?;
-        reader.read_exact(&mut timestamp_bytes)?;
-
-        let timestamp_num = u64::from_be_bytes(timestamp_bytes);
-        let timestamp = if timestamp_num == 0 {
-            None
-        } else {
-            Some(timestamp_num)
-        };
-
-        Ok(Self {
-            block_number: u64::from_be_bytes(block_number),
-            timestamp,
-        })
-    }
-}
-
 /// A Signed Nomad Update with Metadata
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SignedUpdateWithMeta {
@@ -159,11 +81,38 @@ pub struct SignedUpdateWithMeta {
 
 /// A Signed Nomad Update
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedSignedUpdate")]
 pub struct SignedUpdate {
     /// The update
     pub update: Update,
     /// The signature
     pub signature: Signature,
+    /// The scheme of the signature
+    pub scheme: SignatureScheme,
+}
+
+/// A `SignedUpdate` whose signature has not been checked against its scheme
+#[derive(Deserialize)]
+struct UncheckedSignedUpdate {
+    update: Update,
+    signature: Signature,
+    /// Absent from updates serialized before schemes were tagged, which
+    /// were all secp256k1
+    #[serde(default)]
+    scheme: SignatureScheme,
+}
+
+impl TryFrom<UncheckedSignedUpdate> for SignedUpdate {
+    type Error = NomadError;
+
+    fn try_from(unchecked: UncheckedSignedUpdate) -> Result<Self, Self::Error> {
+        unchecked.scheme.check(&unchecked.signature)?;
+        Ok(Self {
+            update: unchecked.update,
+            signature: unchecked.signature,
+            scheme: unchecked.scheme,
+        })
+    }
 }
 
 impl Display for SignedUpdate {
@@ -183,6 +132,7 @@ impl Encode for SignedUpdate {
         let mut written = 0;
         written += self.update.write_to(writer)?;
         written += self.signature.write_to(writer)?;
+        written += self.scheme.write_to(writer)?;
         Ok(written)
     }
 }
@@ -195,20 +145,32 @@ impl Decode for SignedUpdate {
     {
         let update = Update::read_from(reader)?;
         let signature = Signature::read_from(reader)?;
-        Ok(Self { update, signature })
+        let scheme = SignatureScheme::read_from(reader)?;
+        scheme.check(&signature)?;
+        Ok(Self {
+            update,
+            signature,
+            scheme,
+        })
     }
 }
 
 impl SignedUpdate {
-    /// Recover the Ethereum address of the signer
+    /// Recover the Ethereum address of the signer. Only secp256k1
+    /// signatures are recoverable
     pub fn recover(&self) -> Result<Address, NomadError> {
-        Ok(self.signature.recover(self.update.prepended_hash())?)
+        match self.scheme {
+            SignatureScheme::Secp256k1 => {
+                Ok(self.signature.recover(self.update.prepended_hash())?)
+            }
+            scheme => Err(NomadError::UnrecoverableSignature(scheme)),
+        }
     }
 
-    /// Check whether a message was signed by a specific address
-    pub fn verify(&self, signer: Address) -> Result<(), NomadError> {
-        Ok(self
-            .signature
-            .verify(self.update.prepended_hash(), signer)?)
+    /// Check whether a message was signed by a specific signer: an Ethereum
+    /// address for secp256k1, or a public key otherwise
+    pub fn verify(&self, signer: impl Into<NomadIdentifier>) -> Result<(), NomadError> {
+        self.scheme
+            .verify(&self.signature, self.update.signing_hash(), signer.into())
     }
 }
@@ -19,3 +19,26 @@ pub fn destination_and_nonce(destination: u32, nonce: u32) -> u64 {
     assert!(nonce < u32::MAX);
     ((destination as u64) << 32) | nonce as u64
 }
+
+/// Serde helpers for bytes as 0x-prefixed hex strings. Use with
+/// `#[serde(with = "crate::utils::hex_bytes")]`
+pub mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    /// Serialize bytes as a 0x-prefixed hex string
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    /// Deserialize bytes from a hex string, with or without a 0x prefix
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,234 @@
+//! Derive macros for the `Encode` and `Decode` traits of `nomad-core`.
+//!
+//! Fields are encoded in declaration order, each by its own `Encode` impl.
+//! Two kinds of field are special-cased:
+//!
+//! - `Vec<u8>` is written raw, with no length, and so must be the last field.
+//!   Decoding reads to the end of the reader.
+//! - `Option<T>` is written as a 1-byte tag (`0` for `None`, `1` for `Some`)
+//!   followed by the value. Annotated `#[nomad(zero_as_none)]`, it is instead
+//!   written as `T::default()` when `None`, and decoded as `None` when equal
+//!   to `T::default()`.
+
+#![warn(missing_docs)]
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident,
+    PathArguments, Type,
+};
+
+/// How a field is laid out
+enum Layout<'a> {
+    /// By the field type's `Encode`/`Decode` impls
+    Plain,
+    /// Raw trailing bytes
+    TrailingBytes,
+    /// Tagged `Option`
+    Tagged(&'a Type),
+    /// `Option` with `None` encoded as the default value
+    ZeroAsNone(&'a Type),
+}
+
+/// The `T` of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// True if `ty` is `Vec<u8>`
+fn is_bytes(ty: &Type) -> bool {
+    let ty = quote!(#ty).to_string().replace(' ', "");
+    ty == "Vec<u8>" || ty == "std::vec::Vec<u8>"
+}
+
+/// True if the field is annotated `#[nomad(zero_as_none)]`
+fn zero_as_none(field: &Field) -> Result<bool, Error> {
+    match field.attrs.iter().find(|a| a.path.is_ident("nomad")) {
+        Some(attr) => {
+            let arg: Ident = attr.parse_args()?;
+            if arg != "zero_as_none" {
+                return Err(Error::new_spanned(arg, "unknown nomad attribute"));
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn layout(field: &Field) -> Result<Layout<'_>, Error> {
+    let zero_as_none = zero_as_none(field)?;
+    match option_inner(&field.ty) {
+        Some(inner) if zero_as_none => Ok(Layout::ZeroAsNone(inner)),
+        Some(inner) => Ok(Layout::Tagged(inner)),
+        None if zero_as_none => Err(Error::new_spanned(
+            &field.ty,
+            "zero_as_none is only valid on Option fields",
+        )),
+        None if is_bytes(&field.ty) => Ok(Layout::TrailingBytes),
+        None => Ok(Layout::Plain),
+    }
+}
+
+/// The named fields of a struct, with their layouts
+fn fields(input: &DeriveInput) -> Result<Vec<(&Ident, Layout<'_>)>, Error> {
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "only structs with named fields can be derived",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "only structs can be derived",
+            ))
+        }
+    };
+
+    let mut fields = Vec::with_capacity(named.len());
+    for (i, field) in named.iter().enumerate() {
+        let layout = layout(field)?;
+        if matches!(layout, Layout::TrailingBytes) && i != named.len() - 1 {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "Vec<u8> fields are unprefixed, and must be last",
+            ));
+        }
+        fields.push((field.ident.as_ref().expect("named"), layout));
+    }
+    Ok(fields)
+}
+
+/// Derive `nomad_core::Encode`
+#[proc_macro_derive(Encode, attributes(nomad))]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_encode(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let writes = fields(input)?
+        .into_iter()
+        .map(|(name, layout)| match layout {
+            Layout::Plain => quote! {
+                written += nomad_core::Encode::write_to(&self.#name, writer)?;
+            },
+            Layout::TrailingBytes => quote! {
+                writer.write_all(&self.#name)?;
+                written += self.#name.len();
+            },
+            Layout::Tagged(_) => quote! {
+                match &self.#name {
+                    Some(value) => {
+                        writer.write_all(&[1])?;
+                        written += 1 + nomad_core::Encode::write_to(value, writer)?;
+                    }
+                    None => {
+                        writer.write_all(&[0])?;
+                        written += 1;
+                    }
+                }
+            },
+            Layout::ZeroAsNone(inner) => quote! {
+                written += match &self.#name {
+                    Some(value) => nomad_core::Encode::write_to(value, writer)?,
+                    None => nomad_core::Encode::write_to(
+                        &<#inner as ::std::default::Default>::default(),
+                        writer,
+                    )?,
+                };
+            },
+        });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics nomad_core::Encode for #ident #ty_generics #where_clause {
+            fn write_to<W>(&self, writer: &mut W) -> ::std::io::Result<usize>
+            where
+                W: ::std::io::Write,
+            {
+                let mut written = 0;
+                #(#writes)*
+                Ok(written)
+            }
+        }
+    })
+}
+
+/// Derive `nomad_core::Decode`
+#[proc_macro_derive(Decode, attributes(nomad))]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_decode(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = fields(input)?;
+    let reads = fields.iter().map(|(name, layout)| match layout {
+        Layout::Plain => quote! {
+            let #name = nomad_core::Decode::read_from(reader)?;
+        },
+        Layout::TrailingBytes => quote! {
+            let mut #name = vec![];
+            reader.read_to_end(&mut #name)?;
+        },
+        Layout::Tagged(inner) => quote! {
+            let mut tag = [0u8; 1];
+            reader.read_exact(&mut tag)?;
+            let #name = match tag[0] {
+                0 => None,
+                1 => Some(<#inner as nomad_core::Decode>::read_from(reader)?),
+                tag => return Err(nomad_core::NomadError::InvalidOptionTag(tag)),
+            };
+        },
+        Layout::ZeroAsNone(inner) => quote! {
+            let #name = <#inner as nomad_core::Decode>::read_from(reader)?;
+            let #name = if #name == <#inner as ::std::default::Default>::default() {
+                None
+            } else {
+                Some(#name)
+            };
+        },
+    });
+    let names = fields.iter().map(|(name, _)| name);
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics nomad_core::Decode for #ident #ty_generics #where_clause {
+            fn read_from<R>(reader: &mut R) -> ::std::result::Result<Self, nomad_core::NomadError>
+            where
+                R: ::std::io::Read,
+                Self: Sized,
+            {
+                #(#reads)*
+                Ok(Self { #(#names),* })
+            }
+        }
+    })
+}
@@ -36,6 +36,11 @@ mock! {
 
         pub fn _dispatch(&self, message: &Message) -> Result<TxOutcome, MockError> {}
 
+        pub fn _dispatch_committed(
+            &self,
+            message: &Message,
+        ) -> Result<CommittedMessage, MockError> {}
+
         pub fn _queue_length(&self) -> Result<U256, MockError> {}
 
         pub fn _queue_contains(&self, root: H256) -> Result<bool, MockError> {}
@@ -97,6 +102,13 @@ impl Home for MockHomeContract {
         self._dispatch(message)
     }
 
+    async fn dispatch_committed(
+        &self,
+        message: &Message,
+    ) -> Result<CommittedMessage, <Self as Common>::Error> {
+        self._dispatch_committed(message)
+    }
+
     async fn queue_length(&self) -> Result<U256, <Self as Common>::Error> {
         self._queue_length()
     }
@@ -13,7 +13,7 @@ mock! {
 
         pub fn _fetch_sorted_updates(&self, from: u32, to: u32) -> Result<Vec<SignedUpdateWithMeta>, MockError> {}
 
-        pub fn _fetch_sorted_messages(&self, from: u32, to: u32) -> Result<Vec<RawCommittedMessage>, MockError> {}
+        pub fn _fetch_sorted_messages(&self, from: u32, to: u32) -> Result<Vec<RawCommittedMessageWithMeta>, MockError> {}
     }
 }
 
@@ -46,7 +46,7 @@ impl HomeIndexer for MockIndexer {
         &self,
         from: u32,
         to: u32,
-    ) -> Result<Vec<RawCommittedMessage>, <Self as CommonIndexer>::Error> {
+    ) -> Result<Vec<RawCommittedMessageWithMeta>, <Self as CommonIndexer>::Error> {
         self._fetch_sorted_messages(from, to)
     }
 }
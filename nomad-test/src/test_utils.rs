@@ -17,8 +17,22 @@ pub fn setup_db(db_path: String) -> DB {
         .into()
 }
 
-/// Runs test for a db
+/// Runs test for a db. The db is held in memory, so tests don't touch disk
+/// or collide with each other
 pub async fn run_test_db<T, Fut>(test: T)
+where
+    T: FnOnce(DB) -> Fut + panic::UnwindSafe,
+    Fut: Future<Output = ()>,
+{
+    let result = {
+        let func = panic::AssertUnwindSafe(async { test(DB::in_memory()).await });
+        func.catch_unwind().await
+    };
+    assert!(result.is_ok())
+}
+
+/// Runs test for a rocksdb-backed db
+pub async fn run_test_rocks_db<T, Fut>(test: T)
 where
     T: FnOnce(DB) -> Fut + panic::UnwindSafe,
     Fut: Future<Output = ()>,
@@ -6,4 +6,7 @@ pub enum NomadTypeError {
     /// Failed to perform conversion to 20 byte address
     #[error("Failed to convert 32 byte address into 20 byte address: {0}")]
     AddressConversionError(NomadIdentifier),
+    /// Failed to decode an SS58 address
+    #[error("Invalid SS58 address {0}: {1}")]
+    InvalidSs58(String, String),
 }
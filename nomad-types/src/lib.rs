@@ -6,6 +6,9 @@ pub use error::*;
 mod macros;
 pub use macros::*;
 
+mod ss58;
+pub use ss58::*;
+
 use color_eyre::{eyre::bail, Report, Result};
 use ethers::prelude::{Address, H160, H256};
 use serde::{de, Deserializer};
@@ -167,7 +170,7 @@ impl<'de> de::Visitor<'de> for NomadIdentifierVisitor {
     type Value = NomadIdentifier;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("a 20- or 32-byte 0x-prepended hexadecimal string")
+        formatter.write_str("a 20- or 32-byte 0x-prepended hexadecimal string or an SS58 address")
     }
 
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -180,8 +183,13 @@ impl<'de> de::Visitor<'de> for NomadIdentifierVisitor {
         if let Ok(a) = v.parse::<Address>() {
             return Ok(a.into());
         }
+        if let Ok((id, _)) = NomadIdentifier::from_ss58(v) {
+            return Ok(id);
+        }
 
-        Err(E::custom("Unable to parse H256 or Address from string"))
+        Err(E::custom(
+            "Unable to parse H256, Address or SS58 from string",
+        ))
     }
 }
 
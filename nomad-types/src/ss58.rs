@@ -0,0 +1,187 @@
+use blake2::{Blake2b, Digest};
+use ethers::prelude::H256;
+
+use crate::{NomadIdentifier, NomadTypeError};
+
+/// SS58 prefix of the generic Substrate network
+pub const SS58_GENERIC_PREFIX: u16 = 42;
+
+/// Largest SS58 network prefix
+pub const SS58_MAX_PREFIX: u16 = 16383;
+/// Prefixes below this are encoded in a single byte
+const SS58_SIMPLE_PREFIX_LIMIT: u16 = 64;
+/// Checksum preimage prefix
+const SS58_CHECKSUM_PREFIX: &[u8] = b"SS58PRE";
+/// Bytes of checksum
+const SS58_CHECKSUM_LEN: usize = 2;
+
+/// Format to display a `NomadIdentifier` in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// Ethereum address when the top 12 bytes are empty, otherwise H256
+    Ethereum,
+    /// SS58 with a network prefix
+    Ss58(u16),
+}
+
+fn ss58_checksum(data: &[u8]) -> [u8; SS58_CHECKSUM_LEN] {
+    let hash = Blake2b::new()
+        .chain(SS58_CHECKSUM_PREFIX)
+        .chain(data)
+        .finalize();
+    [hash[0], hash[1]]
+}
+
+/// Deserialize an optional SS58 network prefix, rejecting prefixes greater
+/// than `SS58_MAX_PREFIX`
+pub fn deser_ss58_prefix<'de, D>(deserializer: D) -> Result<Option<u16>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let prefix: Option<u16> = serde::Deserialize::deserialize(deserializer)?;
+    match prefix {
+        Some(prefix) if prefix > SS58_MAX_PREFIX => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Unsigned(prefix as u64),
+            &"an SS58 prefix of at most 16383",
+        )),
+        _ => Ok(prefix),
+    }
+}
+
+impl NomadIdentifier {
+    /// Encode as an SS58 address with network `prefix`.
+    ///
+    /// Panics if `prefix` is greater than `SS58_MAX_PREFIX`. Prefixes from
+    /// config are checked by `deser_ss58_prefix` when it is loaded.
+    pub fn to_ss58(&self, prefix: u16) -> String {
+        assert!(prefix <= SS58_MAX_PREFIX, "SS58 prefix out of range");
+
+        let mut data = if prefix < SS58_SIMPLE_PREFIX_LIMIT {
+            vec![prefix as u8]
+        } else {
+            vec![
+                ((prefix & 0b0000_0000_1111_1100) as u8 >> 2) | 0b0100_0000,
+                (prefix >> 8) as u8 | ((prefix & 0b0000_0000_0000_0011) as u8) << 6,
+            ]
+        };
+        data.extend_from_slice(self.as_bytes());
+        let checksum = ss58_checksum(&data);
+        data.extend_from_slice(&checksum);
+
+        bs58::encode(data).into_string()
+    }
+
+    /// Decode an SS58 address, returning the identifier and network prefix
+    pub fn from_ss58(s: &str) -> Result<(Self, u16), NomadTypeError> {
+        let invalid = |reason: &str| NomadTypeError::InvalidSs58(s.to_owned(), reason.to_owned());
+
+        let data = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| invalid("not base58"))?;
+
+        let (prefix_len, prefix) = match data.first() {
+            Some(b) if *b < 64 => (1, *b as u16),
+            Some(b) if *b < 128 && data.len() > 1 => {
+                let lower = (b << 2) | (data[1] >> 6);
+                let upper = data[1] & 0b0011_1111;
+                (2, lower as u16 | (upper as u16) << 8)
+            }
+            _ => return Err(invalid("unknown prefix encoding")),
+        };
+
+        if data.len() != prefix_len + 32 + SS58_CHECKSUM_LEN {
+            return Err(invalid("wrong length"));
+        }
+
+        let (body, checksum) = data.split_at(prefix_len + 32);
+        if ss58_checksum(body) != checksum {
+            return Err(invalid("bad checksum"));
+        }
+
+        Ok((H256::from_slice(&body[prefix_len..]).into(), prefix))
+    }
+
+    /// Display in `format`
+    pub fn display_as(&self, format: AddressFormat) -> String {
+        match format {
+            AddressFormat::Ethereum => match self.as_ethereum_address() {
+                Ok(address) => format!("{:?}", address),
+                Err(_) => format!("{:?}", self.0),
+            },
+            AddressFormat::Ss58(prefix) => self.to_ss58(prefix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethers::prelude::Address;
+    use serde_json::json;
+
+    // Alice's well-known sr25519 dev key
+    const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+    const ALICE_SS58: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    #[test]
+    fn it_converts_ss58() {
+        let alice: NomadIdentifier = ALICE.parse::<H256>().unwrap().into();
+
+        assert_eq!(alice.to_ss58(SS58_GENERIC_PREFIX), ALICE_SS58);
+        assert_eq!(
+            NomadIdentifier::from_ss58(ALICE_SS58).unwrap(),
+            (alice, SS58_GENERIC_PREFIX)
+        );
+
+        // single-byte (below 64) and two-byte prefixes
+        for prefix in [0, 63, 64, 255, 1000, SS58_MAX_PREFIX] {
+            let encoded = alice.to_ss58(prefix);
+            assert_eq!(
+                NomadIdentifier::from_ss58(&encoded).unwrap(),
+                (alice, prefix)
+            );
+        }
+
+        let mut corrupted = ALICE_SS58.to_owned();
+        corrupted.replace_range(10..11, "x");
+        assert!(NomadIdentifier::from_ss58(&corrupted).is_err());
+        assert!(NomadIdentifier::from_ss58("0xdeadbeef").is_err());
+    }
+
+    #[test]
+    fn it_displays_and_desers_identifiers() {
+        let alice: NomadIdentifier = ALICE.parse::<H256>().unwrap().into();
+        let address: NomadIdentifier = Address::repeat_byte(0x11).into();
+
+        assert_eq!(
+            address.display_as(AddressFormat::Ethereum),
+            "0x1111111111111111111111111111111111111111"
+        );
+        assert_eq!(
+            alice.display_as(AddressFormat::Ethereum),
+            format!("0x{}", ALICE)
+        );
+        assert_eq!(
+            alice.display_as(AddressFormat::Ss58(SS58_GENERIC_PREFIX)),
+            ALICE_SS58
+        );
+
+        let deserialized: NomadIdentifier = serde_json::from_value(json!(ALICE_SS58)).unwrap();
+        assert_eq!(deserialized, alice);
+    }
+
+    #[test]
+    fn it_desers_ss58_prefixes_in_range() {
+        #[derive(serde::Deserialize)]
+        struct Specs {
+            #[serde(default, deserialize_with = "deser_ss58_prefix")]
+            prefix: Option<u16>,
+        }
+
+        let specs: Specs = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(specs.prefix, None);
+        let specs: Specs = serde_json::from_value(json!({ "prefix": SS58_MAX_PREFIX })).unwrap();
+        assert_eq!(specs.prefix, Some(SS58_MAX_PREFIX));
+        assert!(serde_json::from_value::<Specs>(json!({ "prefix": SS58_MAX_PREFIX + 1 })).is_err());
+    }
+}
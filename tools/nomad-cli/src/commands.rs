@@ -1,6 +1,6 @@
 use structopt::StructOpt;
 
-use crate::subcommands::{db_state::DbStateCommand, prove::ProveCommand};
+use crate::subcommands::{db_state::DbStateCommand, migrate::MigrateCommand, prove::ProveCommand};
 
 #[derive(StructOpt)]
 pub enum Commands {
@@ -8,4 +8,12 @@ pub enum Commands {
     Prove(ProveCommand),
     /// Print the processor's db state
     DbState(DbStateCommand),
+    /// Manage an agent's db
+    Db(DbCommands),
+}
+
+#[derive(StructOpt)]
+pub enum DbCommands {
+    /// Apply pending schema migrations to an agent's db
+    Migrate(MigrateCommand),
 }
@@ -6,7 +6,7 @@ mod replicas;
 mod rpc;
 mod subcommands;
 
-use commands::Commands;
+use commands::{Commands, DbCommands};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -15,5 +15,6 @@ async fn main() -> Result<()> {
     match command {
         Commands::Prove(prove) => prove.run().await,
         Commands::DbState(db_state) => db_state.run().await,
+        Commands::Db(DbCommands::Migrate(migrate)) => migrate.run().await,
     }
 }
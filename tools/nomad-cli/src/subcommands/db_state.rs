@@ -1,10 +1,11 @@
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use serde_json::{json, Value};
 use std::{collections::HashMap, convert::TryInto, fs::OpenOptions, io::Write};
 use structopt::StructOpt;
 
 use nomad_base::NomadDB;
-use nomad_core::{db::DB, CommittedMessage};
+use nomad_core::{db::DB, CommittedMessage, MessageMeta};
+use nomad_xyz_configuration::{get_builtin, NomadConfig};
 
 use ethers::types::H256;
 
@@ -21,13 +22,31 @@ pub struct DbStateCommand {
     /// Save output to json file
     #[structopt(long)]
     json: bool,
+
+    /// Block explorer base URL, used to link dispatch transactions
+    #[structopt(long)]
+    explorer: Option<String>,
+
+    /// Built-in config to display senders and recipients with, in the address
+    /// format of their network. Defaults to Ethereum addresses
+    #[structopt(long)]
+    config: Option<String>,
 }
 
-type OutputVec = Vec<((H256, u64), Vec<CommittedMessage>)>;
+/// A committed message and its metadata, if indexed
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageEntry {
+    #[serde(flatten)]
+    message: CommittedMessage,
+    metadata: Option<MessageMeta>,
+}
+
+type OutputVec = Vec<((H256, u64), Vec<MessageEntry>)>;
 
 impl DbStateCommand {
     pub async fn run(&self) -> Result<()> {
-        let db = NomadDB::new(&self.home_name, DB::from_path(&self.db_path)?);
+        let db = NomadDB::new(&self.home_name, DB::from_path_read_only(&self.db_path)?);
 
         let messages_by_committed_roots = self.create_comitted_root_to_message_map(&db)?;
 
@@ -36,7 +55,12 @@ impl DbStateCommand {
         if self.json {
             DbStateCommand::save_to_json(output_vec)?;
         } else {
-            DbStateCommand::print_output(output_vec);
+            let config = self
+                .config
+                .as_deref()
+                .map(|name| get_builtin(name).ok_or_else(|| eyre!("Unknown config {}", name)))
+                .transpose()?;
+            self.print_output(output_vec, config);
         }
 
         Ok(())
@@ -45,35 +69,34 @@ impl DbStateCommand {
     fn create_comitted_root_to_message_map(
         &self,
         db: &NomadDB,
-    ) -> Result<HashMap<H256, Vec<CommittedMessage>>> {
-        let mut messages_by_committed_roots: HashMap<H256, Vec<CommittedMessage>> = HashMap::new();
-        for index in 0.. {
-            match db.message_by_leaf_index(index)? {
-                Some(message) => {
-                    if db.proof_by_leaf_index(index)?.is_none() {
-                        println!("Failed to find proof for leaf index {}!", index);
-                    }
+    ) -> Result<HashMap<H256, Vec<MessageEntry>>> {
+        let mut messages_by_committed_roots: HashMap<H256, Vec<MessageEntry>> = HashMap::new();
+        for res in db.messages_by_leaf_index(..) {
+            let (index, message) = res?;
+            if db.proof_by_leaf_index(index)?.is_none() {
+                println!("Failed to find proof for leaf index {}!", index);
+            }
 
-                    let committed_root = message.committed_root;
-                    let bucket_opt = messages_by_committed_roots.get_mut(&committed_root);
-
-                    // Get reference to bucket for committed root
-                    let bucket = match bucket_opt {
-                        Some(bucket) => bucket,
-                        None => {
-                            messages_by_committed_roots
-                                .insert(committed_root, Vec::<CommittedMessage>::new());
-                            messages_by_committed_roots
-                                .get_mut(&committed_root)
-                                .unwrap()
-                        }
-                    };
-
-                    // Add message to bucket for committed root
-                    bucket.push(message.try_into()?);
+            let committed_root = message.committed_root;
+            let bucket_opt = messages_by_committed_roots.get_mut(&committed_root);
+
+            // Get reference to bucket for committed root
+            let bucket = match bucket_opt {
+                Some(bucket) => bucket,
+                None => {
+                    messages_by_committed_roots.insert(committed_root, Vec::<MessageEntry>::new());
+                    messages_by_committed_roots
+                        .get_mut(&committed_root)
+                        .unwrap()
                 }
-                None => break,
-            }
+            };
+
+            // Add message to bucket for committed root
+            let metadata = db.retrieve_message_metadata(message.leaf())?;
+            bucket.push(MessageEntry {
+                message: message.try_into()?,
+                metadata,
+            });
         }
 
         Ok(messages_by_committed_roots)
@@ -82,10 +105,10 @@ impl DbStateCommand {
     fn create_output_vec(
         &self,
         db: &NomadDB,
-        messages_by_committed_roots: HashMap<H256, Vec<CommittedMessage>>,
+        messages_by_committed_roots: HashMap<H256, Vec<MessageEntry>>,
     ) -> Result<OutputVec> {
         // Create mapping of (update root, block_number) to [messages]
-        let mut output_map: HashMap<(H256, u64), Vec<CommittedMessage>> = HashMap::new();
+        let mut output_map: HashMap<(H256, u64), Vec<MessageEntry>> = HashMap::new();
         for (committed_root, bucket) in messages_by_committed_roots {
             let containing_update_opt = db.update_by_previous_root(committed_root)?;
 
@@ -112,31 +135,68 @@ impl DbStateCommand {
         Ok(output_vec)
     }
 
-    fn print_output(output_vec: OutputVec) {
+    fn print_output(&self, output_vec: OutputVec, config: Option<&NomadConfig>) {
+        let display = |domain: u32, id: H256| match config {
+            Some(config) => config.display_identifier(domain.into(), &id.into()),
+            None => format!("{:?}", id),
+        };
+
         for ((update_root, block_number), mut bucket) in output_vec {
             println!("Update root: {:?}", update_root);
             println!("Block number: {}", block_number);
 
-            bucket.sort_by(|x, y| x.leaf_index.cmp(&y.leaf_index));
+            bucket.sort_by(|x, y| x.message.leaf_index.cmp(&y.message.leaf_index));
             print!("Leaves:");
-            for message in bucket {
-                print!(" {} ", message.leaf_index);
+            for entry in bucket.iter() {
+                print!(" {} ", entry.message.leaf_index);
+            }
+            println!();
+
+            for entry in bucket {
+                let leaf_index = entry.message.leaf_index;
+                let message = &entry.message.message;
+                println!(
+                    "  {}: {} on {} to {} on {}",
+                    leaf_index,
+                    display(message.origin, message.sender),
+                    message.origin,
+                    display(message.destination, message.recipient),
+                    message.destination
+                );
+                if let Ok(bridge_message) = entry.message.message.bridge_message() {
+                    println!("  {}: {}", leaf_index, bridge_message);
+                }
+                if let Some(tx_hash) = entry.metadata.and_then(|meta| meta.tx_hash) {
+                    match &self.explorer {
+                        Some(explorer) => println!(
+                            "  {}: {}/tx/{:?}",
+                            leaf_index,
+                            explorer.trim_end_matches('/'),
+                            tx_hash
+                        ),
+                        None => println!("  {}: tx {:?}", leaf_index, tx_hash),
+                    }
+                }
             }
 
-            println!("\n");
+            println!();
         }
     }
 
     fn save_to_json(output_vec: OutputVec) -> Result<()> {
         let mut json_entries: Vec<Value> = Vec::new();
         for ((update_root, block_number), mut bucket) in output_vec {
-            bucket.sort_by(|x, y| x.leaf_index.cmp(&y.leaf_index));
-            let leaf_indexes: Vec<_> = bucket.iter().map(|leaf| leaf.leaf_index).collect();
+            bucket.sort_by(|x, y| x.message.leaf_index.cmp(&y.message.leaf_index));
+            let leaf_indexes: Vec<_> = bucket
+                .iter()
+                .map(|entry| entry.message.leaf_index)
+                .collect();
 
             json_entries.push(json!({
                 "updateRoot": update_root,
                 "blockNumber": block_number,
                 "leaves": leaf_indexes,
+                "messages": bucket,
             }));
         }
 
@@ -0,0 +1,51 @@
+use color_eyre::Result;
+use structopt::StructOpt;
+
+use nomad_core::db::{MigrationOutcome, CURRENT_SCHEMA_VERSION, DB};
+
+#[derive(StructOpt, Debug)]
+pub struct MigrateCommand {
+    /// Path to agent db
+    #[structopt(long)]
+    db_path: String,
+
+    /// Print pending migrations without applying them. Opens the db
+    /// read-only, so can be run alongside a live agent
+    #[structopt(long)]
+    dry_run: bool,
+}
+
+impl MigrateCommand {
+    pub async fn run(&self) -> Result<()> {
+        let db = match self.dry_run {
+            true => DB::from_path_read_only_unmigrated(&self.db_path)?,
+            false => DB::from_path_unmigrated(&self.db_path)?,
+        };
+
+        match db.schema_version()? {
+            Some(version) => println!("Schema version: {}", version),
+            None => println!("Schema version: unversioned"),
+        }
+        println!("Supported schema version: {}", CURRENT_SCHEMA_VERSION);
+
+        if self.dry_run {
+            Self::print_outcomes("Pending migrations", &db.plan_migrations()?);
+        } else {
+            Self::print_outcomes("Applied migrations", &db.migrate()?);
+        }
+
+        Ok(())
+    }
+
+    fn print_outcomes(header: &str, outcomes: &[MigrationOutcome]) {
+        if outcomes.is_empty() {
+            println!("{}: none", header);
+            return;
+        }
+
+        println!("{}:", header);
+        for outcome in outcomes {
+            println!("  {}", outcome);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod db_state;
+pub mod migrate;
 pub mod prove;
 
 pub use db_state::*;
+pub use migrate::*;
 pub use prove::*;
@@ -4,10 +4,12 @@ use structopt::StructOpt;
 use crate::{replicas, rpc};
 
 use nomad_core::{
-    accumulator::NomadProof, db::DB, ContractLocator, Decode, MessageStatus, NomadMessage, Replica,
+    accumulator::{MerkleProof, NomadProof},
+    db::DB,
+    Common, ContractLocator, Decode, MessageStatus, NomadMessage, Replica,
 };
 
-use nomad_base::NomadDB;
+use nomad_base::{NomadDB, PersistentTree};
 use nomad_ethereum::{EthereumReplica, EthereumSigners, TxSubmitter};
 
 use ethers::{
@@ -39,6 +41,11 @@ pub struct ProveCommand {
     #[structopt(long, required_unless = "leaf")]
     leaf_index: Option<u32>,
 
+    /// Root to prove against. Defaults to the root of the stored proof, or to
+    /// the replica's committed root if the replica has not accepted that root
+    #[structopt(long)]
+    root: Option<H256>,
+
     /// The name of the home chain, used to lookup keys in the db
     #[structopt(long)]
     home_name: String,
@@ -70,13 +77,28 @@ pub struct ProveCommand {
 
 impl ProveCommand {
     pub async fn run(&self) -> Result<()> {
-        let db = NomadDB::new(&self.home_name, DB::from_path(&self.db_path)?);
-        let (message, proof) = self.fetch_proof(db)?;
+        let db = NomadDB::new(&self.home_name, DB::from_path_read_only(&self.db_path)?);
+        let (message, proof) = self.fetch_proof(&db)?;
         let replica = self.replica(message.origin, message.destination).await?;
 
         let status = replica.message_status(message.to_leaf()).await?;
         let outcome = match status {
-            MessageStatus::None => replica.prove_and_process(&message, &proof).await?,
+            MessageStatus::None => {
+                let proof = match self.root {
+                    Some(root) => self.historical_proof(&db, proof.index, root)?,
+                    None if !replica.acceptable_root(proof.root()).await? => {
+                        let root = replica.committed_root().await?;
+                        println!(
+                            "Replica has not accepted root {:?}. Proving against its committed root {:?}.",
+                            proof.root(),
+                            root
+                        );
+                        self.historical_proof(&db, proof.index, root)?
+                    }
+                    None => proof,
+                };
+                replica.prove_and_process(&message, &proof).await?
+            }
             MessageStatus::Proven(_) => replica.process(&message).await?,
             _ => {
                 println!("Message already processed.");
@@ -113,7 +135,7 @@ impl ProveCommand {
         }
     }
 
-    fn fetch_proof(&self, db: NomadDB) -> Result<(NomadMessage, NomadProof)> {
+    fn fetch_proof(&self, db: &NomadDB) -> Result<(NomadMessage, NomadProof)> {
         let idx = match (self.leaf_index, self.leaf) {
             (Some(idx), _) => idx,
             (None, Some(digest)) => match db.message_by_leaf(digest)? {
@@ -130,6 +152,28 @@ impl ProveCommand {
         Ok((message, proof))
     }
 
+    /// Prove the leaf at `index` against an older root recorded by the
+    /// processor's prover
+    fn historical_proof(&self, db: &NomadDB, index: usize, root: H256) -> Result<NomadProof> {
+        let count = match db.tree_size_by_root(root)? {
+            Some(count) => count as usize,
+            None => bail!("No tree size known for root {:?}", root),
+        };
+
+        // Nodes are read as they were at `count`, so the tree is not rebuilt
+        let tree = PersistentTree::load(db.clone())?;
+        let proof = tree.prove_at(index, count)?;
+        if proof.root() != root {
+            bail!(
+                "Proof against tree of {} leaves has root {:?}, expected {:?}",
+                count,
+                proof.root(),
+                root
+            );
+        }
+        Ok(proof)
+    }
+
     async fn replica(&self, origin: u32, destination: u32) -> Result<ConcreteReplica> {
         // bit ugly. Tries passed-in rpc first, then defaults to lookup by
         // domain